@@ -0,0 +1,589 @@
+//! Solidity ABI decoding of call arguments and encoding of return values, including dynamic
+//! types.
+//!
+//! A tuple is a run of head words followed by tails: static values sit in their heads, while a
+//! dynamic value (`bytes`, `string`, `T[]`, or a tuple or fixed array holding one) has a head
+//! holding the offset of its tail from the start of the enclosing tuple. Array elements form a
+//! tuple of their own, right after the length word.
+//!
+//! The decoder reads the calldata in place through the host, so large arguments are never
+//! copied whole, and checks every offset and length against `call_data_size` before use.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::vec;
+
+use crate::host;
+use crate::uint::U256;
+
+pub type Word = [u8; 32];
+
+/// A cursor over an ABI-encoded tuple in the calldata. Each read takes the next head word;
+/// dynamic values follow it to their tail, relative to `base`.
+#[derive(Clone, Copy)]
+pub struct Decoder {
+    base: u64,
+    head: u64,
+    size: u64,
+}
+
+impl Decoder {
+    /// The call arguments, after the 4-byte selector.
+    pub fn arguments() -> Self {
+        Decoder { base: 4, head: 4, size: host::call_data_size() }
+    }
+
+    fn at(&self, position: u64) -> Self {
+        Decoder { base: position, head: position, size: self.size }
+    }
+
+    pub fn word(&mut self) -> Option<Word> {
+        if self.head.checked_add(32)? > self.size { return None; }
+        let mut word = [0u8; 32];
+        host::call_data_copy(&mut word, self.head.try_into().ok()?);
+        self.head += 32;
+        Some(word)
+    }
+
+    pub fn uint256(&mut self) -> Option<U256> {
+        Some(U256::from_be_bytes(&self.word()?))
+    }
+
+    /// A `uint64`, or any `uintN` value that fits one; `None` if the word holds more.
+    pub fn uint64(&mut self) -> Option<u64> {
+        let word = self.word()?;
+        if word[..24].iter().any(|&b| b != 0) { return None; }
+        Some(u64::from_be_bytes(word[24..].try_into().unwrap()))
+    }
+
+    /// An `int64`, or any narrower `intN`; `None` unless the word is its sign extension.
+    pub fn int64(&mut self) -> Option<i64> {
+        let word = self.word()?;
+        let value = i64::from_be_bytes(word[24..].try_into().unwrap());
+        let extension = if value < 0 { 0xFF } else { 0 };
+        if word[..24].iter().any(|&b| b != extension) { return None; }
+        Some(value)
+    }
+
+    /// An `int256` as its sign and magnitude.
+    pub fn int256(&mut self) -> Option<(bool, U256)> {
+        let value = self.uint256()?;
+        let negative = value.bit(255);
+        Some((negative, if negative { U256::ZERO.wrapping_sub(&value) } else { value }))
+    }
+
+    pub fn bool(&mut self) -> Option<bool> {
+        match self.uint64()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    pub fn address(&mut self) -> Option<[u8; 20]> {
+        let word = self.word()?;
+        if word[..12].iter().any(|&b| b != 0) { return None; }
+        Some(word[12..].try_into().unwrap())
+    }
+
+    /// A decoder over the tail that `head` points at, for values whose head word was already
+    /// read.
+    pub fn follow(&self, head: &Word) -> Option<Self> {
+        if head[..24].iter().any(|&b| b != 0) { return None; }
+        let position = self.base.checked_add(u64::from_be_bytes(head[24..].try_into().unwrap()))?;
+        if position > self.size { return None; }
+        Some(self.at(position))
+    }
+
+    /// A dynamic tuple, or a fixed array of dynamic elements: a decoder over its own heads.
+    /// Static tuples and fixed arrays are read inline instead.
+    pub fn tuple(&mut self) -> Option<Self> {
+        let head = self.word()?;
+        self.follow(&head)
+    }
+
+    pub fn bytes(&mut self) -> Option<Vec<u8>> {
+        self.tuple()?.into_bytes()
+    }
+
+    /// A `string`; `None` unless it is UTF-8.
+    pub fn string(&mut self) -> Option<String> {
+        String::from_utf8(self.bytes()?).ok()
+    }
+
+    /// A dynamic array `T[]`, each element read by `element` from the array's own tuple.
+    pub fn array_of<T>(&mut self, element: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        self.tuple()?.into_array_of(element)
+    }
+
+    /// A fixed array `T[len]` of static elements, read inline.
+    pub fn fixed_array_of<T>(&mut self, len: usize, mut element: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        (0..len).map(|_| element(self)).collect()
+    }
+
+    /// The `bytes` at this tail: a length word, then the data.
+    pub fn into_bytes(mut self) -> Option<Vec<u8>> {
+        let len = self.uint64()?;
+        if len > self.size - self.head { return None; }
+        let mut bytes = vec![0u8; len as usize];
+        host::call_data_copy(&mut bytes, self.head.try_into().ok()?);
+        Some(bytes)
+    }
+
+    /// The tuple ABI-encoded inside the `bytes` at this tail, as `abi.encode` gives it, read in
+    /// place with its offsets relative to the start of the data and bounded by its length.
+    pub fn into_embedded(mut self) -> Option<Self> {
+        let len = self.uint64()?;
+        if len > self.size - self.head { return None; }
+        Some(Decoder { base: self.head, head: self.head, size: self.head + len })
+    }
+
+    /// The `T[]` at this tail: a length word, then the elements. Every element takes at least
+    /// one head word, which bounds the length before anything is allocated.
+    pub fn into_array_of<T>(mut self, mut element: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        let len = self.uint64()?;
+        if len > (self.size - self.head) / 32 { return None; }
+        let mut elements = self.at(self.head);
+        (0..len).map(|_| element(&mut elements)).collect()
+    }
+}
+
+/// Builds an ABI-encoded tuple. Values are appended in order; dynamic ones put a placeholder
+/// in the head that `finish` fills with the offset of their tail.
+#[derive(Default)]
+pub struct Encoder {
+    head: Vec<u8>,
+    tail: Vec<u8>,
+    /// `(head position, tail position)` of every dynamic value.
+    offsets: Vec<(usize, usize)>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn word(&mut self, word: &Word) -> &mut Self {
+        self.head.extend_from_slice(word);
+        self
+    }
+
+    pub fn uint256(&mut self, value: &U256) -> &mut Self {
+        self.word(&value.to_be_bytes())
+    }
+
+    pub fn uint64(&mut self, value: u64) -> &mut Self {
+        self.uint256(&U256::from_u64(value))
+    }
+
+    /// Sign-extended.
+    pub fn int64(&mut self, value: i64) -> &mut Self {
+        let mut word = if value < 0 { [0xFF; 32] } else { [0; 32] };
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        self.word(&word)
+    }
+
+    pub fn bool(&mut self, value: bool) -> &mut Self {
+        self.uint64(value as u64)
+    }
+
+    pub fn address(&mut self, address: &[u8; 20]) -> &mut Self {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(address);
+        self.word(&word)
+    }
+
+    /// Appends a dynamic value whose tail is `tail`.
+    fn dynamic(&mut self, tail: &[u8]) -> &mut Self {
+        self.offsets.push((self.head.len(), self.tail.len()));
+        self.head.extend_from_slice(&[0; 32]);
+        self.tail.extend_from_slice(tail);
+        self
+    }
+
+    /// `bytes`: the length, then the data padded to whole words.
+    pub fn bytes(&mut self, data: &[u8]) -> &mut Self {
+        let mut tail = Vec::with_capacity(32 + data.len().div_ceil(32) * 32);
+        tail.extend_from_slice(&U256::from_u64(data.len() as u64).to_be_bytes());
+        tail.extend_from_slice(data);
+        tail.resize(tail.len().div_ceil(32) * 32, 0);
+        self.dynamic(&tail)
+    }
+
+    pub fn string(&mut self, value: &str) -> &mut Self {
+        self.bytes(value.as_bytes())
+    }
+
+    /// A dynamic tuple, or a fixed array of dynamic elements; static ones are appended inline.
+    pub fn tuple(&mut self, mut inner: Encoder) -> &mut Self {
+        self.dynamic(&inner.finish())
+    }
+
+    /// A dynamic array `T[]`, with `element` appending each item to the array's own tuple.
+    pub fn array_of<T>(&mut self, items: &[T], mut element: impl FnMut(&mut Encoder, &T)) -> &mut Self {
+        let mut inner = Encoder::new();
+        for item in items {
+            element(&mut inner, item);
+        }
+        let mut tail = U256::from_u64(items.len() as u64).to_be_bytes().to_vec();
+        tail.extend_from_slice(&inner.finish());
+        self.dynamic(&tail)
+    }
+
+    /// The encoded tuple, leaving the encoder empty.
+    pub fn finish(&mut self) -> Vec<u8> {
+        let mut output = core::mem::take(&mut self.head);
+        let head_len = output.len();
+        for (position, tail_position) in self.offsets.drain(..) {
+            output[position..position + 32].copy_from_slice(&U256::from_u64((head_len + tail_position) as u64).to_be_bytes());
+        }
+        output.append(&mut self.tail);
+        output
+    }
+}
+
+/// A value with a Solidity ABI type. Tuples are flattened into the enclosing tuple, so a
+/// handler's whole result is one value and `encode` gives its return data.
+///
+/// `u8` has no impl, so that `Vec<u8>` can be `bytes` while other vectors are `T[]`.
+pub trait AbiEncode {
+    fn encode(&self, encoder: &mut Encoder);
+
+    /// Appends what stands in for an absent value of this type: zero words for static types,
+    /// empty ones for dynamic types.
+    fn encode_absent(encoder: &mut Encoder);
+}
+
+impl Encoder {
+    pub fn value<T: AbiEncode + ?Sized>(&mut self, value: &T) -> &mut Self {
+        value.encode(self);
+        self
+    }
+}
+
+/// The return data for `value`.
+pub fn encode<T: AbiEncode + ?Sized>(value: &T) -> Vec<u8> {
+    Encoder::new().value(value).finish()
+}
+
+macro_rules! impl_abi_encode_word {
+    ($($ty:ty => $method:ident),*) => {$(
+        impl AbiEncode for $ty {
+            fn encode(&self, encoder: &mut Encoder) {
+                encoder.$method((*self).into());
+            }
+
+            fn encode_absent(encoder: &mut Encoder) {
+                encoder.word(&[0; 32]);
+            }
+        }
+    )*};
+}
+
+impl_abi_encode_word!(bool => bool, u32 => uint64, u64 => uint64, i64 => int64);
+
+impl AbiEncode for U256 {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.uint256(self);
+    }
+
+    fn encode_absent(encoder: &mut Encoder) {
+        encoder.word(&[0; 32]);
+    }
+}
+
+/// `bytes32`.
+impl AbiEncode for Word {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.word(self);
+    }
+
+    fn encode_absent(encoder: &mut Encoder) {
+        encoder.word(&[0; 32]);
+    }
+}
+
+/// `address`.
+impl AbiEncode for [u8; 20] {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.address(self);
+    }
+
+    fn encode_absent(encoder: &mut Encoder) {
+        encoder.word(&[0; 32]);
+    }
+}
+
+/// `bytes`.
+impl AbiEncode for Vec<u8> {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.bytes(self);
+    }
+
+    fn encode_absent(encoder: &mut Encoder) {
+        encoder.bytes(&[]);
+    }
+}
+
+/// `string`.
+impl AbiEncode for String {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.string(self);
+    }
+
+    fn encode_absent(encoder: &mut Encoder) {
+        encoder.string("");
+    }
+}
+
+/// `T[]`; dynamic elements get their own offsets within the array.
+impl<T: AbiEncode> AbiEncode for Vec<T> {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.array_of(self, |inner, item| item.encode(inner));
+    }
+
+    fn encode_absent(encoder: &mut Encoder) {
+        encoder.array_of::<T>(&[], |_, _| {});
+    }
+}
+
+/// `(bool, T)`: `Some(value)` is `(true, value)` and `None` is `(false, absent)`. This is the
+/// one representation of a result that is undefined or rejected, with no in-band sentinels.
+impl<T: AbiEncode> AbiEncode for Option<T> {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.bool(self.is_some());
+        match self {
+            Some(value) => value.encode(encoder),
+            None => T::encode_absent(encoder),
+        }
+    }
+
+    fn encode_absent(encoder: &mut Encoder) {
+        encoder.bool(false);
+        T::encode_absent(encoder);
+    }
+}
+
+macro_rules! impl_abi_encode_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: AbiEncode),*> AbiEncode for ($($name,)*) {
+            #[allow(non_snake_case)]
+            fn encode(&self, encoder: &mut Encoder) {
+                let ($($name,)*) = self;
+                $($name.encode(encoder);)*
+            }
+
+            fn encode_absent(encoder: &mut Encoder) {
+                $($name::encode_absent(encoder);)*
+            }
+        }
+    };
+}
+
+impl_abi_encode_tuple!(A, B);
+impl_abi_encode_tuple!(A, B, C);
+impl_abi_encode_tuple!(A, B, C, D);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    fn word(value: u64) -> Word {
+        U256::from_u64(value).to_be_bytes()
+    }
+
+    fn words(values: &[u64]) -> Vec<u8> {
+        values.iter().flat_map(|&value| word(value)).collect()
+    }
+
+    /// Makes `args` the calldata, behind a zero selector, and returns a decoder over them.
+    fn arguments(args: &[u8]) -> Decoder {
+        let mut call = vec![0; 4];
+        call.extend_from_slice(args);
+        host::set_call_data(&call);
+        Decoder::arguments()
+    }
+
+    /// The Solidity ABI specification's `f(uint256,uint32[],bytes10,bytes)` example, called with
+    /// `(0x123, [0x456, 0x789], "1234567890", "Hello, world!")`.
+    const F_ARGUMENTS: &str = concat!(
+        "0000000000000000000000000000000000000000000000000000000000000123",
+        "0000000000000000000000000000000000000000000000000000000000000080",
+        "3132333435363738393000000000000000000000000000000000000000000000",
+        "00000000000000000000000000000000000000000000000000000000000000e0",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "0000000000000000000000000000000000000000000000000000000000000456",
+        "0000000000000000000000000000000000000000000000000000000000000789",
+        "000000000000000000000000000000000000000000000000000000000000000d",
+        "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+    );
+
+    /// The specification's `g(uint256[][],string[])` example, called with
+    /// `([[1, 2], [3]], ["one", "two", "three"])`.
+    const G_ARGUMENTS: &str = concat!(
+        "0000000000000000000000000000000000000000000000000000000000000040",
+        "0000000000000000000000000000000000000000000000000000000000000140",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "0000000000000000000000000000000000000000000000000000000000000040",
+        "00000000000000000000000000000000000000000000000000000000000000a0",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "0000000000000000000000000000000000000000000000000000000000000060",
+        "00000000000000000000000000000000000000000000000000000000000000a0",
+        "00000000000000000000000000000000000000000000000000000000000000e0",
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "6f6e650000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "74776f0000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000005",
+        "7468726565000000000000000000000000000000000000000000000000000000",
+    );
+
+    #[test]
+    fn specification_examples_round_trip() {
+        let mut args = arguments(&bytes(F_ARGUMENTS));
+        assert_eq!(args.uint256(), Some(U256::from_u64(0x123)));
+        assert_eq!(args.array_of(Decoder::uint64).unwrap(), [0x456, 0x789]);
+        assert_eq!(&args.word().unwrap()[..10], b"1234567890");
+        assert_eq!(args.bytes().unwrap(), b"Hello, world!");
+
+        let mut bytes10 = [0u8; 32];
+        bytes10[..10].copy_from_slice(b"1234567890");
+        let mut encoder = Encoder::new();
+        encoder.uint64(0x123).value(&vec![0x456u64, 0x789]).word(&bytes10).bytes(b"Hello, world!");
+        assert_eq!(encoder.finish(), bytes(F_ARGUMENTS));
+
+        let mut args = arguments(&bytes(G_ARGUMENTS));
+        assert_eq!(args.array_of(|inner| inner.array_of(Decoder::uint64)).unwrap(), [vec![1, 2], vec![3]]);
+        assert_eq!(args.array_of(Decoder::string).unwrap(), ["one", "two", "three"]);
+
+        let strings = vec!["one".to_string(), "two".to_string(), "three".to_string()];
+        assert_eq!(encode(&(vec![vec![1u64, 2], vec![3]], strings)), bytes(G_ARGUMENTS));
+    }
+
+    #[test]
+    fn nested_bytes_offsets_are_relative_to_their_array() {
+        // `(bytes[])` with ["", "ab"]: the element offsets count from the first of them.
+        let encoded = encode(&vec![Vec::new(), b"ab".to_vec()]);
+        let mut expected = words(&[0x20, 2, 0x40, 0x60, 0]);
+        expected.extend(words(&[2]));
+        expected.extend(b"ab");
+        expected.resize(expected.len() + 30, 0);
+        assert_eq!(encoded, expected);
+        assert_eq!(arguments(&encoded).array_of(Decoder::bytes).unwrap(), [b"".to_vec(), b"ab".to_vec()]);
+    }
+
+    #[test]
+    fn rejects_offsets_outside_the_calldata() {
+        let tail = words(&[1, 0xab]);
+        let with_offset = |offset: Word| {
+            let mut args = offset.to_vec();
+            args.extend(&tail);
+            args
+        };
+        assert_eq!(arguments(&with_offset(word(0x20))).bytes().unwrap(), [0]);
+        // Past the end, at the end, into the high bytes, and wrapping around.
+        assert_eq!(arguments(&with_offset(word(0x60))).bytes(), None);
+        assert_eq!(arguments(&with_offset(word(0x40))).bytes(), None);
+        let mut high = word(0x20);
+        high[0] = 1;
+        assert_eq!(arguments(&with_offset(high)).bytes(), None);
+        assert_eq!(arguments(&with_offset(word(u64::MAX))).bytes(), None);
+        assert_eq!(arguments(&with_offset(word(u64::MAX - 3))).array_of(Decoder::word), None);
+        // No head word at all.
+        assert_eq!(arguments(&[0; 31]).bytes(), None);
+    }
+
+    #[test]
+    fn rejects_lengths_past_the_calldata() {
+        // A `bytes` tail that ends exactly at the calldata end need not be padded.
+        let mut args = words(&[0x20, 33]);
+        args.extend([7; 33]);
+        assert_eq!(arguments(&args).bytes().unwrap(), [7; 33]);
+        args.pop();
+        assert_eq!(arguments(&args).bytes(), None);
+
+        assert_eq!(arguments(&words(&[0x20, u64::MAX])).bytes(), None);
+        assert_eq!(arguments(&words(&[0x20, u64::MAX])).array_of(Decoder::word), None);
+        assert_eq!(arguments(&words(&[0x20, 3, 1, 2])).array_of(Decoder::uint64), None);
+        assert_eq!(arguments(&words(&[0x20, 2, 1, 2])).array_of(Decoder::uint64).unwrap(), [1, 2]);
+        let mut huge = words(&[0x20]);
+        huge.extend([0xff; 32]);
+        assert_eq!(arguments(&huge).bytes(), None);
+    }
+
+    #[test]
+    fn rejects_hostile_bytes_array_elements() {
+        // `(bytes[])` with two elements whose offsets are patched below.
+        let valid = encode(&vec![b"x".to_vec(), b"yz".to_vec()]);
+        let patched = |element: usize, offset: Word| {
+            let mut args = valid.clone();
+            args[0x40 + 32 * element..0x60 + 32 * element].copy_from_slice(&offset);
+            args
+        };
+        assert_eq!(arguments(&valid).array_of(Decoder::bytes).unwrap(), [b"x".to_vec(), b"yz".to_vec()]);
+        assert_eq!(arguments(&patched(1, word(0x1000))).array_of(Decoder::bytes), None);
+        assert_eq!(arguments(&patched(1, word(u64::MAX))).array_of(Decoder::bytes), None);
+        assert_eq!(arguments(&patched(0, [0xff; 32])).array_of(Decoder::bytes), None);
+        // An element pointing at the other's length word reads that word as its own length.
+        assert_eq!(arguments(&patched(1, word(0x40))).array_of(Decoder::bytes).unwrap(), [b"x".to_vec(), b"x".to_vec()]);
+        // An element whose length runs past the calldata.
+        let mut args = valid.clone();
+        let last_length = args.len() - 64;
+        args[last_length..last_length + 32].copy_from_slice(&word(0x41));
+        assert_eq!(arguments(&args).array_of(Decoder::bytes), None);
+    }
+
+    #[test]
+    fn embedded_tuples_are_bounded_by_their_bytes() {
+        // `(bytes fields)` with `fields = abi.encode(uint256(7), bytes("hi"))`.
+        let fields = encode(&(U256::from_u64(7), b"hi".to_vec()));
+        assert_eq!(fields.len(), 128);
+        let args = encode(&fields);
+        let mut embedded = arguments(&args).tuple().unwrap().into_embedded().unwrap();
+        assert_eq!(embedded.uint256(), Some(U256::from_u64(7)));
+        assert_eq!(embedded.bytes().unwrap(), b"hi");
+
+        // Cut the embedded length to the heads: the tail is still in the calldata, but no
+        // longer inside the value.
+        let mut truncated = args.clone();
+        truncated[32..64].copy_from_slice(&word(64));
+        let mut embedded = arguments(&truncated).tuple().unwrap().into_embedded().unwrap();
+        assert_eq!(embedded.uint256(), Some(U256::from_u64(7)));
+        assert_eq!(embedded.bytes(), None);
+
+        let mut overlong = args.clone();
+        overlong[32..64].copy_from_slice(&word(129));
+        assert!(arguments(&overlong).tuple().unwrap().into_embedded().is_none());
+    }
+
+    #[test]
+    fn static_values_are_checked() {
+        let mut high = word(1);
+        high[23] = 1;
+        assert_eq!(arguments(&high).uint64(), None);
+        assert_eq!(arguments(&word(u64::MAX)).uint64(), Some(u64::MAX));
+        assert_eq!(arguments(&word(2)).bool(), None);
+        assert_eq!(arguments(&word(1)).bool(), Some(true));
+        assert_eq!(arguments(&[0xff; 32]).int64(), Some(-1));
+        assert_eq!(arguments(&word(u64::MAX)).int64(), None);
+        let mut negative = [0xff; 32];
+        negative[24] = 0x7f;
+        assert_eq!(arguments(&negative).int64(), None);
+        let mut address = [0u8; 32];
+        address[12..].fill(0xaa);
+        assert_eq!(arguments(&address).address(), Some([0xaa; 20]));
+        address[11] = 1;
+        assert_eq!(arguments(&address).address(), None);
+        assert_eq!(encode(&-1i64), [0xff; 32]);
+    }
+}
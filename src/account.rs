@@ -0,0 +1,171 @@
+//! Substrate account identifiers: SS58 addresses, and the mapping `pallet-revive` uses between
+//! 32-byte account ids and the 20-byte addresses contracts see.
+//!
+//! An SS58 address is the base58 of the network prefix, the account id and the first two bytes
+//! of `blake2b_512("SS58PRE" || prefix || account)`. Prefixes below 64 take one byte; the rest,
+//! up to 16383, take two, with the first in `64..128`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::base58;
+use crate::hash;
+use crate::host;
+
+pub type AccountId = [u8; 32];
+pub type Address = [u8; 20];
+
+const CHECKSUM_LEN: usize = 2;
+
+fn checksum(prefixed: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut preimage = Vec::with_capacity(7 + prefixed.len());
+    preimage.extend_from_slice(b"SS58PRE");
+    preimage.extend_from_slice(prefixed);
+    let hash: [u8; 64] = hash::blake2b(&preimage);
+    [hash[0], hash[1]]
+}
+
+/// `None` if `prefix` needs more than 14 bits.
+pub fn ss58_encode(account: &AccountId, prefix: u16) -> Option<String> {
+    let mut data = match prefix {
+        0..=63 => Vec::from([prefix as u8]),
+        64..=16383 => Vec::from([
+            0b0100_0000 | (prefix >> 2 & 0b0011_1111) as u8,
+            (prefix >> 8) as u8 | (prefix as u8) << 6,
+        ]),
+        _ => return None,
+    };
+    data.extend_from_slice(account);
+    let checksum = checksum(&data);
+    data.extend_from_slice(&checksum);
+    Some(base58::encode(&data))
+}
+
+/// The prefix and account id of an address; `None` if it is not base58, has a bad checksum
+/// or does not hold a 32-byte account id.
+pub fn ss58_decode(address: &[u8]) -> Option<(u16, AccountId)> {
+    let data = base58::decode(address)?;
+    let (prefix_len, prefix) = match *data.first()? {
+        first @ 0..=63 => (1, first as u16),
+        first @ 64..=127 => {
+            let second = *data.get(1)?;
+            let lower = first << 2 | second >> 6;
+            let upper = second & 0b0011_1111;
+            (2, lower as u16 | (upper as u16) << 8)
+        }
+        _ => return None,
+    };
+    if data.len() != prefix_len + 32 + CHECKSUM_LEN { return None; }
+    let (body, check) = data.split_at(prefix_len + 32);
+    if checksum(body) != check { return None; }
+    Some((prefix, body[prefix_len..].try_into().unwrap()))
+}
+
+/// Whether the account id is the fallback of an Ethereum address: the address followed by
+/// twelve `0xEE` bytes.
+fn is_eth_derived(account: &AccountId) -> bool {
+    account[20..].iter().all(|&b| b == 0xEE)
+}
+
+/// The address `pallet-revive` gives an account: an Ethereum-derived account maps back to its
+/// address, any other to the last 20 bytes of its keccak256.
+pub fn to_address(account: &AccountId) -> Address {
+    if is_eth_derived(account) {
+        return account[..20].try_into().unwrap();
+    }
+    let mut hash = [0u8; 32];
+    host::hash_keccak_256(account, &mut hash);
+    hash[12..].try_into().unwrap()
+}
+
+/// The account id `pallet-revive` gives an address with no mapped account: the address padded
+/// with `0xEE`. A mapped address resolves to its original account instead, which only the
+/// pallet's storage knows.
+pub fn to_fallback_account_id(address: &Address) -> AccountId {
+    let mut account = [0xEE; 32];
+    account[..20].copy_from_slice(address);
+    account
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes<const N: usize>(text: &str) -> [u8; N] {
+        crate::hex::decode(text.as_bytes()).unwrap().try_into().unwrap()
+    }
+
+    /// The `//Alice` development account.
+    const ALICE: &str = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+
+    /// Alice's addresses from `sp-core`'s `to_ss58check_with_version`: Polkadot, Kusama, the
+    /// generic prefix, and both ends of the one- and two-byte ranges.
+    const ALICE_ADDRESSES: [(u16, &str); 7] = [
+        (0, "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"),
+        (2, "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F"),
+        (42, "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+        (63, "7NPoMQbiA6trJKkjB35uk96MeJD4PGWkLQLH7k7hXEkZpiba"),
+        (64, "cEaNSpz4PxFcZ7nT1VEKrKewH67rfx6MfcM6yKojyyPz7qaqp"),
+        (1000, "vji5kpxBaPKwct6PAdHiJUPCU1hqBEAPaLMF59sXAjn4NeEaJ"),
+        (16383, "yNa8JpqfFB3q8A29rCwSgxvdU94ufJw2yKKxDgznS5m1PoFvn"),
+    ];
+
+    #[test]
+    fn ss58_vectors() {
+        let alice = bytes(ALICE);
+        for (prefix, address) in ALICE_ADDRESSES {
+            assert_eq!(ss58_encode(&alice, prefix).as_deref(), Some(address), "{prefix}");
+            assert_eq!(ss58_decode(address.as_bytes()), Some((prefix, alice)), "{prefix}");
+        }
+        assert_eq!(ss58_encode(&alice, 16384), None);
+    }
+
+    #[test]
+    fn ss58_decode_rejects_malformed_addresses() {
+        let alice = bytes::<32>(ALICE);
+        for (_, address) in ALICE_ADDRESSES {
+            let data = base58::decode(address.as_bytes()).unwrap();
+            // Either checksum byte, or the account, changed.
+            for index in [data.len() - 1, data.len() - 2, data.len() - 3] {
+                let mut corrupted = data.clone();
+                corrupted[index] ^= 1;
+                assert_eq!(ss58_decode(base58::encode(&corrupted).as_bytes()), None, "{address}");
+            }
+            // A checksum left off, or an extra byte.
+            assert_eq!(ss58_decode(base58::encode(&data[..data.len() - 1]).as_bytes()), None);
+            let mut longer = data.clone();
+            longer.push(0);
+            assert_eq!(ss58_decode(base58::encode(&longer).as_bytes()), None);
+        }
+        // A 20-byte account id with a valid checksum, as some chains use.
+        let mut short = Vec::from([42]);
+        short.extend_from_slice(&alice[..20]);
+        short.extend_from_slice(&checksum(&short));
+        assert_eq!(ss58_decode(base58::encode(&short).as_bytes()), None);
+        // A first byte of 128 or more is reserved.
+        let mut reserved = Vec::from([128, 0]);
+        reserved.extend_from_slice(&alice);
+        reserved.extend_from_slice(&checksum(&reserved));
+        assert_eq!(ss58_decode(base58::encode(&reserved).as_bytes()), None);
+        assert_eq!(ss58_decode(b"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQ0"), None);
+        assert_eq!(ss58_decode(b""), None);
+    }
+
+    #[test]
+    fn revive_address_mapping() {
+        // Alice's address on a `pallet-revive` chain: the last 20 bytes of `keccak256(alice)`,
+        // from the `sha3` crate.
+        let alice = bytes(ALICE);
+        assert_eq!(to_address(&alice), bytes("9621dde636de098b43efb0fa9b61facfe328f99d"));
+
+        // The fallback account of an address maps straight back to it.
+        let address = [0xaa; 20];
+        let fallback = to_fallback_account_id(&address);
+        assert_eq!(fallback, bytes("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaeeeeeeeeeeeeeeeeeeeeeeee"));
+        assert_eq!(to_address(&fallback), address);
+        // One byte of the suffix off and the account is hashed like any other.
+        let mut almost = fallback;
+        almost[31] = 0xef;
+        assert_eq!(to_address(&almost), bytes("69d1431757b276bb59f8be235655a9e7c436fced"));
+    }
+}
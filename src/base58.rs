@@ -0,0 +1,131 @@
+//! Base58 with the Bitcoin alphabet, as used by SS58 and Bitcoin addresses: the bytes read as
+//! one big-endian number in base 58, with each leading zero byte kept as a leading `1`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::host;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+pub fn encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    // Little-endian base-58 digits of the rest, multiplied in a byte at a time.
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut output = String::with_capacity(zeros + digits.len());
+    output.extend(core::iter::repeat_n('1', zeros));
+    output.extend(digits.iter().rev().map(|&d| ALPHABET[d as usize] as char));
+    output
+}
+
+/// `None` on any character outside the alphabet.
+pub fn decode(text: &[u8]) -> Option<Vec<u8>> {
+    let zeros = text.iter().take_while(|&&c| c == b'1').count();
+    // Little-endian bytes of the rest, multiplied in a digit at a time.
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len() * 733 / 1000 + 1);
+    for &c in &text[zeros..] {
+        let mut carry = ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut output = alloc::vec![0u8; zeros];
+    output.extend(bytes.iter().rev());
+    Some(output)
+}
+
+/// Base58Check: `data` followed by the first four bytes of its double SHA-256.
+pub fn encode_check(data: &[u8]) -> String {
+    let mut payload = data.to_vec();
+    payload.extend_from_slice(&double_sha256(data)[..4]);
+    encode(&payload)
+}
+
+/// `None` if `text` is not base58 or its checksum does not match.
+pub fn decode_check(text: &[u8]) -> Option<Vec<u8>> {
+    let mut payload = decode(text)?;
+    let split = payload.len().checked_sub(4)?;
+    if double_sha256(&payload[..split])[..4] != payload[split..] { return None; }
+    payload.truncate(split);
+    Some(payload)
+}
+
+fn double_sha256(data: &[u8]) -> [u8; 32] {
+    let mut first = [0u8; 32];
+    host::hash_sha2_256(data, &mut first);
+    let mut second = [0u8; 32];
+    host::hash_sha2_256(&first, &mut second);
+    second
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    /// From the base-x fixtures (cryptocoinjs/base-x), as the `bs58` crate uses them.
+    const VECTORS: [(&str, &str); 13] = [
+        ("", ""),
+        ("61", "2g"),
+        ("626262", "a3gV"),
+        ("636363", "aPEr"),
+        ("572e4794", "3EFU7m"),
+        ("10c8511e", "Rt5zm"),
+        ("516b6fcd0f", "ABnLTmg"),
+        ("bf4f89001e670274dd", "3SEo3LWLoPntC"),
+        ("ecac89cad93923c02321", "EJDM8drfXA6uyA"),
+        ("00000000000000000000", "1111111111"),
+        ("ffffffffffffffffffff", "FPBt6CHo3fovdL"),
+        ("73696d706c792061206c6f6e6720737472696e67", "2cFupjhnEsSn59qHXstmK2ffpLv2"),
+        ("00eb15231dfceb60925886b67d065299925915aeb172c06647", "1NS17iag9jJgTHD1VXjvLCEnZuQ3rJDE9L"),
+    ];
+
+    #[test]
+    fn base_x_vectors() {
+        for (data, text) in VECTORS {
+            assert_eq!(encode(&bytes(data)), text);
+            assert_eq!(decode(text.as_bytes()).unwrap(), bytes(data));
+        }
+        for text in ["0", "O", "I", "l", "2g+", "2g "] {
+            assert_eq!(decode(text.as_bytes()), None, "{text}");
+        }
+    }
+
+    /// Base58Check cases from the `bs58` crate.
+    const CHECK_VECTORS: [(&str, &str); 5] = [("", "3QJmnh"), ("31", "6bdbJ1U"), ("39", "7VsrQCP"), ("2d31", "PWEu9GGN"), ("3131", "RVnPfpC2")];
+
+    #[test]
+    fn base58check_vectors() {
+        for (data, text) in CHECK_VECTORS {
+            assert_eq!(encode_check(&bytes(data)), text);
+            assert_eq!(decode_check(text.as_bytes()).unwrap(), bytes(data));
+        }
+        // The genesis block's P2PKH address: version 0 and the key hash.
+        let payload = bytes("0062e907b15cbf27d5425399ebf6f0fb50ebb88f18");
+        assert_eq!(encode_check(&payload), "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+        assert_eq!(decode_check(b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").unwrap(), payload);
+        assert_eq!(decode_check(b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb"), None);
+        assert_eq!(decode_check(b"111"), None);
+    }
+}
@@ -0,0 +1,93 @@
+//! Base64 (RFC 4648): the standard alphabet padded with `=`, as in `data:` URIs, and the
+//! URL-safe alphabet (`-` and `_`) unpadded, as in JWTs.
+//!
+//! Decoding is strict: padding must be exactly what the standard form needs, and may be left
+//! out or given in full in the URL-safe form; the unused low bits of the last digit must be
+//! zero.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub fn encode(data: &[u8], url_safe: bool) -> String {
+    let alphabet = if url_safe { URL_SAFE } else { STANDARD };
+    let mut output = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &b)| bits | (b as u32) << (16 - 8 * i));
+        let digits = chunk.len() + 1;
+        for i in 0..4 {
+            if i < digits {
+                output.push(alphabet[(bits >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else if !url_safe {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+pub fn decode(text: &[u8], url_safe: bool) -> Option<Vec<u8>> {
+    let alphabet = if url_safe { URL_SAFE } else { STANDARD };
+    let digits = text.iter().take_while(|&&c| c != b'=').count();
+    let padding = text.len() - digits;
+    if text[digits..].iter().any(|&c| c != b'=') || digits % 4 == 1 { return None; }
+    let padded = padding == 0 || (digits + padding) % 4 == 0 && padding < 3;
+    if !padded || (!url_safe && text.len() % 4 != 0) { return None; }
+
+    let mut output = Vec::with_capacity(digits * 3 / 4);
+    for chunk in text[..digits].chunks(4) {
+        let mut bits = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = alphabet.iter().position(|a| a == c)? as u32;
+            bits |= value << (18 - 6 * i);
+        }
+        let bytes = chunk.len() * 6 / 8;
+        // The bits below the last whole byte belong to no byte and must be clear.
+        if bits & (0xFF_FFFF >> (8 * bytes)) != 0 { return None; }
+        output.extend_from_slice(&bits.to_be_bytes()[1..1 + bytes]);
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 4648 §10.
+    const VECTORS: [(&str, &str); 7] =
+        [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("foob", "Zm9vYg=="), ("fooba", "Zm9vYmE="), ("foobar", "Zm9vYmFy")];
+
+    #[test]
+    fn rfc4648_vectors() {
+        for (data, text) in VECTORS {
+            assert_eq!(encode(data.as_bytes(), false), text);
+            assert_eq!(decode(text.as_bytes(), false).unwrap(), data.as_bytes());
+            // The URL-safe form drops the padding, and accepts it back in full.
+            let unpadded = text.trim_end_matches('=');
+            assert_eq!(encode(data.as_bytes(), true), unpadded);
+            assert_eq!(decode(unpadded.as_bytes(), true).unwrap(), data.as_bytes());
+            assert_eq!(decode(text.as_bytes(), true).unwrap(), data.as_bytes());
+        }
+        assert_eq!(encode(&[0xfb, 0xff, 0xbf], false), "+/+/");
+        assert_eq!(encode(&[0xfb, 0xff, 0xbf], true), "-_-_");
+    }
+
+    #[test]
+    fn rejects_loose_padding_and_alphabet() {
+        for text in ["Zg", "Zg=", "Zg===", "Zm8", "Z===", "Zm9v=", "Zm9v====", "Zg==Zg==", "=", "Z", "Zm9vY"] {
+            assert_eq!(decode(text.as_bytes(), false), None, "{text}");
+        }
+        for text in ["Zg=", "Zg===", "Z", "Zm9v=", "Zg==Zg", "Zm9vY"] {
+            assert_eq!(decode(text.as_bytes(), true), None, "{text}");
+        }
+        // Set bits past the last whole byte.
+        for text in ["Zh==", "Zm9=", "Zm9vYh=="] {
+            assert_eq!(decode(text.as_bytes(), false), None, "{text}");
+        }
+        assert_eq!(decode(b"-_-_", false), None);
+        assert_eq!(decode(b"+/+/", true), None);
+        assert_eq!(decode(b"Zm 9v", false), None);
+    }
+}
@@ -0,0 +1,280 @@
+//! Bech32 (BIP-173) and bech32m (BIP-350) strings, and the segwit addresses built from them.
+//!
+//! A string is a human-readable part, the separator `1`, and 5-bit data digits ending in a
+//! six-digit BCH checksum over both; the two variants differ only in the constant the checksum
+//! is compared against. Byte data is regrouped into 5-bit digits, the last one zero-padded.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const MAX_LEN: usize = 90;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    /// Maps the ABI variant identifier: 0 = bech32, 1 = bech32m.
+    pub fn from_id(id: u64) -> Option<Self> {
+        match id {
+            0 => Some(Variant::Bech32),
+            1 => Some(Variant::Bech32m),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        self as u32
+    }
+
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2BC830A3,
+        }
+    }
+}
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3B6A57B2, 0x26508E6D, 0x1EA119FA, 0x3D4233DD, 0x2A1462B3];
+    values.fold(1, |checksum, value| {
+        let top = checksum >> 25;
+        let mut checksum = (checksum & 0x1FF_FFFF) << 5 ^ value as u32;
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if top >> i & 1 == 1 { checksum ^= g; }
+        }
+        checksum
+    })
+}
+
+/// The checksum input: the high bits of each `hrp` character, a zero, their low bits, and then
+/// the data.
+fn checksum_input<'a>(hrp: &'a [u8], data: &'a [u8]) -> impl Iterator<Item = u8> + 'a {
+    hrp.iter().map(|c| c >> 5).chain([0]).chain(hrp.iter().map(|c| c & 31)).chain(data.iter().copied())
+}
+
+/// Regroups `data` from `from`-bit to `to`-bit values. Padding the last value with zeros is
+/// allowed only if `pad`; otherwise leftover bits must be fewer than `from` and zero.
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    // Only the bits not yet emitted are kept, so `acc` cannot overflow.
+    let keep = (1u32 << (from + to - 1)) - 1;
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut output = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for &value in data {
+        if value as u32 >> from != 0 { return None; }
+        acc = (acc << from | value as u32) & keep;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            output.push((acc >> bits & ((1 << to) - 1)) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 { output.push((acc << (to - bits) & ((1 << to) - 1)) as u8); }
+    } else if bits >= from || acc & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(output)
+}
+
+/// `hrp`, lowercased, and the 5-bit digits `data` with their checksum. `None` if `hrp` is empty
+/// or not printable ASCII, a digit is out of range, or the result exceeds 90 characters.
+pub fn encode(hrp: &[u8], data: &[u8], variant: Variant) -> Option<String> {
+    if hrp.is_empty() || hrp.iter().any(|c| !(33..=126).contains(c)) { return None; }
+    if hrp.len() + 1 + data.len() + 6 > MAX_LEN || data.iter().any(|&d| d >= 32) { return None; }
+    let hrp = hrp.to_ascii_lowercase();
+    let checksum = polymod(checksum_input(&hrp, data).chain([0; 6])) ^ variant.constant();
+    let mut output = String::with_capacity(hrp.len() + 1 + data.len() + 6);
+    output.extend(hrp.iter().map(|&c| c as char));
+    output.push('1');
+    output.extend(data.iter().map(|&d| CHARSET[d as usize] as char));
+    output.extend((0..6).map(|i| CHARSET[(checksum >> (5 * (5 - i)) & 31) as usize] as char));
+    Some(output)
+}
+
+/// The lowercased human-readable part, the 5-bit digits without the checksum, and the variant
+/// the checksum matches. `None` on mixed case, a bad character or checksum, or overlong text.
+pub fn decode(text: &[u8]) -> Option<(String, Vec<u8>, Variant)> {
+    if text.len() > MAX_LEN || text.iter().any(|c| !(33..=126).contains(c)) { return None; }
+    if text.iter().any(u8::is_ascii_lowercase) && text.iter().any(u8::is_ascii_uppercase) { return None; }
+    let text = text.to_ascii_lowercase();
+    let separator = text.iter().rposition(|&c| c == b'1')?;
+    if separator == 0 || separator + 7 > text.len() { return None; }
+    let (hrp, digits) = (&text[..separator], &text[separator + 1..]);
+    let data: Vec<u8> = digits.iter().map(|c| CHARSET.iter().position(|a| a == c).map(|d| d as u8)).collect::<Option<_>>()?;
+    let variant = match polymod(checksum_input(hrp, &data)) {
+        c if c == Variant::Bech32.constant() => Variant::Bech32,
+        c if c == Variant::Bech32m.constant() => Variant::Bech32m,
+        _ => return None,
+    };
+    let hrp = String::from_utf8(hrp.to_vec()).ok()?;
+    Some((hrp, data[..data.len() - 6].to_vec(), variant))
+}
+
+/// The variant for a witness version and program; `None` for a version above 16 or a program
+/// of the wrong length.
+fn segwit_variant(version: u8, program: &[u8]) -> Option<Variant> {
+    match (version, program.len()) {
+        (0, 20 | 32) => Some(Variant::Bech32),
+        (1..=16, 2..=40) => Some(Variant::Bech32m),
+        _ => None,
+    }
+}
+
+/// A segwit address: version 0 in bech32, later versions in bech32m.
+pub fn segwit_encode(hrp: &[u8], version: u8, program: &[u8]) -> Option<String> {
+    let variant = segwit_variant(version, program)?;
+    let mut data = Vec::from([version]);
+    data.extend(convert_bits(program, 8, 5, true)?);
+    encode(hrp, &data, variant)
+}
+
+/// The version and witness program of a segwit address for `hrp`.
+pub fn segwit_decode(hrp: &[u8], address: &[u8]) -> Option<(u8, Vec<u8>)> {
+    let (decoded_hrp, data, variant) = decode(address)?;
+    if !decoded_hrp.as_bytes().eq_ignore_ascii_case(hrp) { return None; }
+    let (&version, program) = data.split_first()?;
+    let program = convert_bits(program, 5, 8, false)?;
+    if segwit_variant(version, &program)? != variant { return None; }
+    Some((version, program))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    /// BIP-173 and BIP-350 valid strings, with the variant their checksum is in.
+    const VALID: [(&str, Variant); 14] = [
+        ("A12UEL5L", Variant::Bech32),
+        ("a12uel5l", Variant::Bech32),
+        ("an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs", Variant::Bech32),
+        ("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw", Variant::Bech32),
+        ("11qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqc8247j", Variant::Bech32),
+        ("split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w", Variant::Bech32),
+        ("?1ezyfcl", Variant::Bech32),
+        ("A1LQFN3A", Variant::Bech32m),
+        ("a1lqfn3a", Variant::Bech32m),
+        ("an83characterlonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11sg7hg6", Variant::Bech32m),
+        ("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx", Variant::Bech32m),
+        ("11llllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllludsr8", Variant::Bech32m),
+        ("split1checkupstagehandshakeupstreamerranterredcaperredlc445v", Variant::Bech32m),
+        ("?1v759aa", Variant::Bech32m),
+    ];
+
+    /// BIP-173 and BIP-350 invalid strings: bad characters, lengths and separators, and
+    /// checksums computed over the uppercase form.
+    const INVALID: [&str; 25] = [
+        " 1nwldj5",
+        "\u{7f}1axkwrx",
+        "\u{80}1eym55h",
+        "an84characterslonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1569pvx",
+        "pzry9x0s0muk",
+        "1pzry9x0s0muk",
+        "x1b4n0q5v",
+        "li1dgmt3",
+        "de1lg7wt\u{ff}",
+        "A1G7SGD8",
+        "10a06t8",
+        "1qzzfhee",
+        "\u{20}1xj0phk",
+        "\u{7f}1g6xzxy",
+        "an84characterslonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11d6pts4",
+        "qyrz8wqd2c9m",
+        "1qyrz8wqd2c9m",
+        "y1b0jsk6g",
+        "lt1igcx5c0",
+        "in1muywd",
+        "mm1crxm3i",
+        "au1s5cgom",
+        "M1VUXWEZ",
+        "16plkw9",
+        "1p2gdwpf",
+    ];
+
+    #[test]
+    fn bip173_bip350_strings() {
+        for (text, variant) in VALID {
+            let (hrp, data, decoded) = decode(text.as_bytes()).unwrap();
+            assert!(decoded == variant, "{text}");
+            assert_eq!(encode(hrp.as_bytes(), &data, variant).unwrap(), text.to_ascii_lowercase());
+        }
+        for text in INVALID {
+            assert!(decode(text.as_bytes()).is_none(), "{text:?}");
+        }
+    }
+
+    /// BIP-350 valid addresses and their scriptPubKeys: the version opcode, the program length
+    /// and the program.
+    const ADDRESSES: [(&str, &str); 8] = [
+        ("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4", "0014751e76e8199196d454941c45d1b3a323f1433bd6"),
+        ("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7", "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262"),
+        (
+            "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y",
+            "5128751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6",
+        ),
+        ("BC1SW50QGDZ25J", "6002751e"),
+        ("bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs", "5210751e76e8199196d454941c45d1b3a323"),
+        ("tb1qqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesrxh6hy", "0020000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433"),
+        ("tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c", "5120000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433"),
+        ("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0", "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+    ];
+
+    /// BIP-350 invalid addresses (wrong hrp, wrong variant for the version, bad version,
+    /// program length or padding, mixed case, empty data), then BIP-173's version 1 to 16
+    /// addresses, which BIP-350 makes invalid by their bech32 checksum.
+    const INVALID_ADDRESSES: [&str; 18] = [
+        "tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut",
+        "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+        "tb1z0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqglt7rf",
+        "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL",
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh",
+        "tb1q0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq24jc47",
+        "bc1p38j9r5y49hruaue7wxjce0updqjuyyx0kh56v8s25huc6995vvpql3jow4",
+        "BC130XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ7ZWS8R",
+        "bc1pw5dgrnzv",
+        "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v8n0nx0muaewav253zgeav",
+        "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P",
+        "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47Zagq",
+        "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v07qwwzcrf",
+        "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vpggkg4j",
+        "bc1gmk9yu",
+        "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7k7grplx",
+        "BC1SW50QA3JX3S",
+        "bc1zw508d6qejxtdg4y5r3zarvaryvg6kdaj",
+    ];
+
+    #[test]
+    fn bip350_addresses() {
+        for (address, script) in ADDRESSES {
+            let hrp = &address.as_bytes()[..2];
+            let (version, program) = segwit_decode(hrp, address.as_bytes()).unwrap();
+            let script = bytes(script);
+            assert_eq!(version, if script[0] == 0 { 0 } else { script[0] - 0x50 });
+            assert_eq!(program, &script[2..]);
+            assert_eq!(segwit_encode(hrp, version, &program).unwrap(), address.to_ascii_lowercase());
+        }
+        for address in INVALID_ADDRESSES {
+            for hrp in [b"bc", b"tb"] {
+                assert_eq!(segwit_decode(hrp, address.as_bytes()), None, "{address}");
+            }
+        }
+    }
+
+    #[test]
+    fn convert_bits_rejects_bad_padding() {
+        assert_eq!(convert_bits(&[0xff], 8, 5, true).unwrap(), [31, 28]);
+        assert_eq!(convert_bits(&[31, 28], 5, 8, false).unwrap(), [0xff]);
+        // Nonzero leftover bits, a whole leftover digit, and a digit out of range.
+        assert_eq!(convert_bits(&[31, 29], 5, 8, false), None);
+        assert_eq!(convert_bits(&[31, 28, 0], 5, 8, false), None);
+        assert_eq!(convert_bits(&[32], 5, 8, true), None);
+    }
+}
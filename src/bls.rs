@@ -0,0 +1,130 @@
+//! BLS signatures on BLS12-381 with public keys in G1 and signatures in G2, under the
+//! proof-of-possession ciphersuite Ethereum consensus uses. Keys and signatures are in the
+//! standard compressed encoding (48 and 96 bytes).
+//!
+//! Aggregation is only sound for keys whose possession has been proven, which is the caller's
+//! responsibility; the identity key is rejected outright.
+
+use alloc::vec::Vec;
+
+use crate::bls12_381::{self, G1};
+use crate::curve::JacobianPoint;
+use crate::pairing::G2Jacobian;
+
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+fn decode_public_key(bytes: &[u8]) -> Option<JacobianPoint<6>> {
+    bls12_381::decode_g1(bytes).filter(|pk| !pk.is_infinity())
+}
+
+/// `e(-G1, sig) * e(pk_1, H(m_1)) * ... * e(pk_n, H(m_n)) == 1`.
+fn check(terms: Vec<(JacobianPoint<6>, G2Jacobian<6>)>, signature: &[u8]) -> Option<bool> {
+    let signature = bls12_381::decode_g2(signature)?;
+    let generator = G1.to_jacobian(&G1.generator)?;
+    let neg_generator = JacobianPoint { y: G1.field.neg(&generator.y), ..generator };
+    let mut pairs = terms;
+    pairs.push((neg_generator, signature));
+    Some(bls12_381::pairing_check(&pairs))
+}
+
+/// Verifies one signature. Returns `false` for malformed or identity keys and signatures.
+pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let Some(pk) = decode_public_key(public_key) else { return false };
+    let Some(h) = bls12_381::hash_to_g2(message, DST) else { return false };
+    check(alloc::vec![(pk, h)], signature).unwrap_or(false)
+}
+
+/// Verifies an aggregate signature by several keys over one message, by summing the keys.
+/// `public_keys` is the concatenation of 48-byte compressed keys, at least one.
+pub fn fast_aggregate_verify(public_keys: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    if public_keys.is_empty() || public_keys.len() % 48 != 0 { return false; }
+    let mut aggregate = JacobianPoint::INFINITY;
+    for chunk in public_keys.chunks_exact(48) {
+        let Some(pk) = decode_public_key(chunk) else { return false };
+        aggregate = G1.add(&aggregate, &pk);
+    }
+    let Some(h) = bls12_381::hash_to_g2(message, DST) else { return false };
+    check(alloc::vec![(aggregate, h)], signature).unwrap_or(false)
+}
+
+/// Verifies an aggregate signature over one message per key. `public_keys` is the
+/// concatenation of 48-byte compressed keys and must match `messages` in number.
+pub fn aggregate_verify(public_keys: &[u8], messages: &[Vec<u8>], signature: &[u8]) -> bool {
+    if messages.is_empty() || public_keys.len() != 48 * messages.len() { return false; }
+    let mut terms = Vec::with_capacity(messages.len() + 1);
+    for (chunk, message) in public_keys.chunks_exact(48).zip(messages) {
+        let Some(pk) = decode_public_key(chunk) else { return false };
+        let Some(h) = bls12_381::hash_to_g2(message, DST) else { return false };
+        terms.push((pk, h));
+    }
+    check(terms, signature).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    /// Keys and signatures from the `bls12_381` crate with secret keys `0x12345678`,
+    /// `0xdeadbeef * (2^32 + 1)` and `-7`: `(public key, message, signature)`.
+    const SIGNATURES: [(&str, &str, &str); 3] = [
+        (
+            "a3654cd53fae9db11ab648e96d68eb9b89121243650946062976b1e1b582f971b828df8f959df74dfb16628130c28940",
+            "",
+            "8aa9d8a16b53bd9c2b2815eb4912380f3b63988f5242ddebaa856ceff44e21549409f5b1df7d5e301b1e7f72e50176b0104ff16641008d46569cc713237ef0718f1583549139084818768ad84d5b212ed5486858d4b11f2af7cc101591c2420b",
+        ),
+        (
+            "a495dff7f34866de6bbc2e74178f50cec631fac2ace7235ab69482436f6a5b67249efcf04e6d6ceb2c26c81c30b3b84a",
+            "68656c6c6f",
+            "8a9b50bc06efb8945fec578e60d69066b8ca44a7d1287af45b3af5ad7fdd40b1507a4262facf4979d0d117290653738809ea30170aaa85a337c0822a5bd5226aec2c6dba29aa5b33026b882ec4d85c955df3b336c5aa5c696e58a97c0a13f082",
+        ),
+        (
+            "9928f3beb93519eecf0145da903b40a4c97dca00b21f12ac0df3be9116ef2ef27b2ae6bcd4c5bc2d54ef5a70627efcb7",
+            "424c5331322d333831",
+            "b839d5586f002d32fa201c97c43065e29050fd3ab8d960b09ccd65cb2e9ed8985c56fe196354ba9199ef48295042601013b2c009053330662217dda6d1e38b5d5b28f9f03097da176df7794b3d9dca5fc706568abbf8fb9d7e9ecf3086256532",
+        ),
+    ];
+
+    /// The three keys' signatures on "hello", summed.
+    const FAST_AGGREGATE: &str = "968c5824ad38ed95b52d317ad3285536f1ced9853b0069e1d74ccde2ef0d36017fa68c78babcdac7d6a3cc98620317ed19ce1fb8fce0017fbc9a242309b564f1d5b9e775ca9d265e650b98d3ae61911228385f11c1b813a0d689b10a9987835c";
+
+    /// The three signatures above, summed.
+    const AGGREGATE: &str = "8d1f4a89d0043dd727460abc4ab0b28a6fe86355522de664821eb1d83738271bc92eddc905f2eeac6c7d33da4900b2d20a33e1dfd14382bfcf1bb28df34aa2c18ad28f6868b1c20d7a3ba9b68ef87d475366219e7ccc4b1f4468c803638d249d";
+
+    #[test]
+    fn verify_single_signatures() {
+        for (i, (public_key, message, signature)) in SIGNATURES.into_iter().enumerate() {
+            let (public_key, message, signature) = (bytes(public_key), bytes(message), bytes(signature));
+            assert!(verify(&public_key, &message, &signature));
+            assert!(!verify(&public_key, b"other", &signature));
+            let other_key = bytes(SIGNATURES[(i + 1) % 3].0);
+            assert!(!verify(&other_key, &message, &signature));
+        }
+        // The identity key with the identity signature would pass the pairing check.
+        let mut infinity = [0u8; 96];
+        infinity[0] = 0xc0;
+        assert!(!verify(&infinity[..48], b"", &infinity));
+    }
+
+    #[test]
+    fn verify_aggregates() {
+        let public_keys: Vec<u8> = SIGNATURES.iter().flat_map(|(public_key, _, _)| bytes(public_key)).collect();
+        let messages: Vec<Vec<u8>> = SIGNATURES.iter().map(|(_, message, _)| bytes(message)).collect();
+        assert!(fast_aggregate_verify(&public_keys, b"hello", &bytes(FAST_AGGREGATE)));
+        assert!(!fast_aggregate_verify(&public_keys[..96], b"hello", &bytes(FAST_AGGREGATE)));
+        assert!(!fast_aggregate_verify(&[], b"hello", &bytes(FAST_AGGREGATE)));
+        assert!(aggregate_verify(&public_keys, &messages, &bytes(AGGREGATE)));
+        // Messages swapped between keys, and one fewer message than keys.
+        let swapped = vec![messages[1].clone(), messages[0].clone(), messages[2].clone()];
+        assert!(!aggregate_verify(&public_keys, &swapped, &bytes(AGGREGATE)));
+        assert!(!aggregate_verify(&public_keys, &messages[..2], &bytes(AGGREGATE)));
+        // A single signature is an aggregate of one.
+        let (public_key, message, signature) = SIGNATURES[1];
+        assert!(fast_aggregate_verify(&bytes(public_key), &bytes(message), &bytes(signature)));
+        assert!(aggregate_verify(&bytes(public_key), &[bytes(message)], &bytes(signature)));
+    }
+}
@@ -0,0 +1,506 @@
+//! BLS12-381: G1 and G2, the optimal-ate pairing, the standard compressed point encoding and
+//! hashing to G2 (RFC 9380, `BLS12381G2_XMD:SHA-256_SSWU_RO_`).
+//!
+//! G1 is `y^2 = x^3 + 4` over the 381-bit field; G2 is the M-type twist `y^2 = x^3 + 4(1 + u)`.
+//! Both have cofactors, so every decoded point is checked to lie in the order-`r` subgroup.
+
+use alloc::vec::Vec;
+
+use crate::curve::{AffinePoint, Curve, JacobianPoint};
+use crate::host;
+use crate::pairing::{G2Affine, G2Curve, G2Jacobian, TwistType};
+use crate::tower::{self, Fp12, Fp2, Fp2Hex, Tower};
+use crate::uint::Uint;
+
+pub type Fp = Uint<6>;
+
+pub const G1: Curve<6> = Curve::new(
+    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+    "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+    "0",
+    "4",
+    "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+    "08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1",
+);
+
+/// `(p + 1) / 2`: canonical values at or above it are the "lexicographically largest" roots.
+const P_PLUS_1_DIV_2: &str =
+    "0d0088f51cbff34d258dd3db21a5d66bb23ba5c279c2895fb39869507b587b120f55ffff58a9ffffdcff7fffffffd556";
+
+pub const G2: G2Curve<6> = G2Curve::new(
+    Tower::new(
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+        ("1", "1"),
+        [
+            ("1", "0"),
+            ("0", "1a0111ea397fe699ec02408663d4de85aa0d857d89759ad4897d29650fb85f9b409427eb4f49fffd8bfd00000000aaac"),
+            ("00000000000000005f19672fdf76ce51ba69c6076a0f77eaddb3a93be6f89688de17d813620a00022e01fffffffefffe", "0"),
+            ("0", "1"),
+        ],
+        [
+            ("1", "0"),
+            ("1a0111ea397fe699ec02408663d4de85aa0d857d89759ad4897d29650fb85f9b409427eb4f49fffd8bfd00000000aaad", "0"),
+            ("1a0111ea397fe699ec02408663d4de85aa0d857d89759ad4897d29650fb85f9b409427eb4f49fffd8bfd00000000aaac", "0"),
+            ("1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaaa", "0"),
+        ],
+        [
+            ("1", "0"),
+            ("1904d3bf02bb0667c231beb4202c0d1f0fd603fd3cbd5f4f7b2443d784bab9c4f67ea53d63e7813d8d0775ed92235fb8", "00fc3e2b36c4e03288e9e902231f9fb854a14787b6c7b36fec0c8ec971f63c5f282d5ac14d6c7ec22cf78a126ddc4af3"),
+            ("00000000000000005f19672fdf76ce51ba69c6076a0f77eaddb3a93be6f89688de17d813620a00022e01fffffffeffff", "0"),
+            ("135203e60180a68ee2e9c448d77a2cd91c3dedd930b1cf60ef396489f61eb45e304466cf3e67fa0af1ee7b04121bdea2", "06af0e0437ff400b6831e36d6bd17ffe48395dabc2d3435e77f76e17009241c5ee67992f72ec05f4c81084fbede3cc09"),
+        ],
+    ),
+    ("4", "4"),
+    "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+    P_PLUS_1_DIV_2,
+    TwistType::M,
+);
+
+/// `|x|` for the curve parameter `x = -0xd201000000010000`.
+const X: u64 = 0xd201000000010000;
+
+// --- Pairing ---
+
+/// The Miller loop over the bits of `|x|`, conjugated at the end because `x` is negative.
+fn miller_loop(pairs: &mut [crate::pairing::MillerPair<6>]) -> Fp12<6> {
+    let t = &G2.tower;
+    let mut f = t.fp12_one();
+    for i in (0..63 - X.leading_zeros()).rev() {
+        f = t.fp12_square(&f);
+        for pair in pairs.iter_mut() {
+            f = G2.line_double(&f, pair);
+            if (X >> i) & 1 == 1 {
+                let (qx, qy) = (pair.qx, pair.qy);
+                f = G2.line_add(&f, pair, &qx, &qy);
+            }
+        }
+    }
+    t.fp12_conjugate(&f)
+}
+
+/// `f^x` for `f` in the cyclotomic subgroup.
+fn exp_by_x(f: &Fp12<6>) -> Fp12<6> {
+    let t = &G2.tower;
+    let mut acc = t.fp12_one();
+    for i in (0..u64::BITS - X.leading_zeros()).rev() {
+        acc = t.fp12_square(&acc);
+        if (X >> i) & 1 == 1 { acc = t.fp12_mul(&acc, f); }
+    }
+    t.fp12_conjugate(&acc)
+}
+
+/// The final exponentiation, with the hard part from ePrint 2020/875; `None` if `f` is zero.
+fn final_exponentiation(f: &Fp12<6>) -> Option<Fp12<6>> {
+    let t = &G2.tower;
+    // Easy part: f^((p^6 - 1)(p^2 + 1)).
+    let r = t.fp12_mul(&t.fp12_conjugate(f), &t.fp12_inv(f)?);
+    let r = t.fp12_mul(&t.fp12_frobenius(&r, 2), &r);
+    // Hard part.
+    let y0 = t.fp12_square(&r);
+    let y1 = t.fp12_mul(&exp_by_x(&r), &t.fp12_conjugate(&r));
+    let y2 = exp_by_x(&y1);
+    let y1 = t.fp12_mul(&t.fp12_conjugate(&y1), &y2);
+    let y2 = exp_by_x(&y1);
+    let y1 = t.fp12_mul(&t.fp12_frobenius(&y1, 1), &y2);
+    let r = t.fp12_mul(&r, &y0);
+    let y0 = exp_by_x(&y1);
+    let y2 = exp_by_x(&y0);
+    let y0 = t.fp12_frobenius(&y1, 2);
+    let y1 = t.fp12_mul(&t.fp12_mul(&t.fp12_conjugate(&y1), &y2), &y0);
+    Some(t.fp12_mul(&r, &y1))
+}
+
+/// Checks `e(P1, Q1) * ... * e(Pn, Qn) == 1` for points already known to lie in G1 and G2.
+pub fn pairing_check(pairs: &[(JacobianPoint<6>, G2Jacobian<6>)]) -> bool {
+    let fp = &G1.field;
+    let mut miller_pairs = Vec::with_capacity(pairs.len());
+    for (p, q) in pairs {
+        let (AffinePoint::Coordinate { x: px, y: py }, G2Affine::Coordinate { x: qx, y: qy }) =
+            (G1.to_affine(p), G2.to_affine(q))
+        else {
+            continue;
+        };
+        let fp2 = |a: &Fp2<6>| Fp2 { c0: fp.element_reduced(&a.c0), c1: fp.element_reduced(&a.c1) };
+        miller_pairs.push(G2.miller_pair(fp.element_reduced(&px), fp.element_reduced(&py), fp2(&qx), fp2(&qy)));
+    }
+    let f = miller_loop(&mut miller_pairs);
+    final_exponentiation(&f) == Some(G2.tower.fp12_one())
+}
+
+// --- Compressed encoding ---
+//
+// The first byte carries three flags: 0x80 marks the compressed form, 0x40 the point at
+// infinity (all other bits zero), and 0x20 that `y` is the lexicographically largest root.
+
+const FLAG_COMPRESSED: u8 = 0x80;
+const FLAG_INFINITY: u8 = 0x40;
+const FLAG_LARGEST: u8 = 0x20;
+
+/// Splits off the flag bits. `Ok(None)` is infinity; `Ok(Some((largest, bytes)))` a point.
+fn split_flags(bytes: &[u8]) -> Option<Option<(bool, Vec<u8>)>> {
+    let flags = bytes[0] & 0xe0;
+    if flags & FLAG_COMPRESSED == 0 { return None; }
+    let mut rest = bytes.to_vec();
+    rest[0] &= 0x1f;
+    if flags & FLAG_INFINITY != 0 {
+        let canonical = flags == FLAG_COMPRESSED | FLAG_INFINITY && rest.iter().all(|&b| b == 0);
+        return canonical.then_some(None);
+    }
+    Some(Some((flags & FLAG_LARGEST != 0, rest)))
+}
+
+fn fp_is_largest(y: &Fp) -> bool { *y >= Uint::from_be_hex(P_PLUS_1_DIV_2) }
+
+/// `Fp2` roots compare by their `c1` coefficient first.
+fn fp2_is_largest(y: &Fp2<6>) -> bool {
+    if y.c1.is_zero() { fp_is_largest(&y.c0) } else { fp_is_largest(&y.c1) }
+}
+
+/// Decodes a 48-byte compressed G1 point, checking the curve equation and the subgroup.
+pub fn decode_g1(bytes: &[u8]) -> Option<JacobianPoint<6>> {
+    if bytes.len() != 48 { return None; }
+    let Some((largest, x)) = split_flags(bytes)? else { return Some(JacobianPoint::INFINITY) };
+    let f = &G1.field;
+    let x = f.element(&Uint::from_be_slice(&x)?)?;
+    let y = f.sqrt(&G1.rhs(&x))?;
+    let y = if fp_is_largest(&f.canonical(&y)) == largest { y } else { f.neg(&y) };
+    let p = JacobianPoint { x, y, z: f.one };
+    G1.scalar_mul(&p, &G1.scalar.modulus).is_infinity().then_some(p)
+}
+
+pub fn encode_g1(p: &JacobianPoint<6>) -> Vec<u8> {
+    let mut out = alloc::vec![0u8; 48];
+    match G1.to_affine(p) {
+        AffinePoint::Infinity => out[0] = FLAG_COMPRESSED | FLAG_INFINITY,
+        AffinePoint::Coordinate { x, y } => {
+            x.write_be_bytes(&mut out);
+            out[0] |= FLAG_COMPRESSED | if fp_is_largest(&y) { FLAG_LARGEST } else { 0 };
+        }
+    }
+    out
+}
+
+/// Decodes a 96-byte compressed G2 point, `x.c1 || x.c0`, checking the twist equation and the
+/// subgroup.
+pub fn decode_g2(bytes: &[u8]) -> Option<G2Jacobian<6>> {
+    if bytes.len() != 96 { return None; }
+    let Some((largest, x)) = split_flags(bytes)? else { return Some(G2Jacobian::INFINITY) };
+    let t = &G2.tower;
+    let x = t.fp2_element(&Fp2 { c0: Uint::from_be_slice(&x[48..])?, c1: Uint::from_be_slice(&x[..48])? })?;
+    let y = t.fp2_sqrt(&t.fp2_add(&t.fp2_mul(&t.fp2_square(&x), &x), &G2.b))?;
+    let y = if fp2_is_largest(&t.fp2_canonical(&y)) == largest { y } else { t.fp2_neg(&y) };
+    let p = G2Jacobian { x, y, z: t.fp2_one() };
+    G2.is_in_subgroup(&p).then_some(p)
+}
+
+pub fn encode_g2(p: &G2Jacobian<6>) -> Vec<u8> {
+    let mut out = alloc::vec![0u8; 96];
+    match G2.to_affine(p) {
+        G2Affine::Infinity => out[0] = FLAG_COMPRESSED | FLAG_INFINITY,
+        G2Affine::Coordinate { x, y } => {
+            x.c1.write_be_bytes(&mut out[..48]);
+            x.c0.write_be_bytes(&mut out[48..]);
+            out[0] |= FLAG_COMPRESSED | if fp2_is_largest(&y) { FLAG_LARGEST } else { 0 };
+        }
+    }
+    out
+}
+
+// --- Hash to G2 ---
+
+/// `E2': y^2 = x^3 + A' x + B'`, 3-isogenous to G2's twist, with `Z` for simplified SWU.
+const ISO_A: Fp2<6> = tower::fp2_from_hex(&G2.tower.fp, ("0", "f0"));
+const ISO_B: Fp2<6> = tower::fp2_from_hex(&G2.tower.fp, ("3f4", "3f4"));
+const SWU_Z: Fp2<6> = tower::fp2_from_hex(
+    &G2.tower.fp,
+    (
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaa9",
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaaa",
+    ),
+);
+/// `-B' / A'` and `B' / (Z A')`, the two candidate `x` values of the exceptional case.
+const SWU_MINUS_B_OVER_A: Fp2<6> = tower::fp2_from_hex(
+    &G2.tower.fp,
+    (
+        "083c12791abdd5d2fe2f284f0cc6e5aa9b8c2d3f6f3f792302cf75e62bfc4df1d6834443da498888725d8cccccccb1c3",
+        "11c4ff711ec210c74cec7f673684c72cc8eb1e458445999c64615cbacab4a8324828bbbad70a777747a173333332f8e8",
+    ),
+);
+const SWU_B_OVER_ZA: Fp2<6> = tower::fp2_from_hex(
+    &G2.tower.fp,
+    (
+        "01a59d4b6bbf912a32d63b43028e2deeebe8d5d97ca64b6d66f64ac7a265a9305e1a40da5edb81b4e3ac4f5c28f5bd27",
+        "15103a07f641331bb298f5ed3ba1230aa0bcc9f87d923077324df24a0f7ffa93045d3d6f94c17ae10efa11eb851e7336",
+    ),
+);
+
+/// The 3-isogeny `E2' -> E2` (RFC 9380, appendix E.3): `x = x_num / x_den`,
+/// `y = y' * y_num / y_den`, coefficients from the constant term up.
+const ISO_X_NUM: [Fp2Hex; 4] = [
+    ("5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6", "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6"),
+    ("0", "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71a"),
+    ("11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71e", "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38d"),
+    ("171d6541fa38ccfaed6dea691f5fb614cb14b4e7f4e810aa22d6108f142b85757098e38d0f671c7188e2aaaaaaaa5ed1", "0"),
+];
+const ISO_X_DEN: [Fp2Hex; 3] = [
+    ("0", "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa63"),
+    ("c", "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa9f"),
+    ("1", "0"),
+];
+const ISO_Y_NUM: [Fp2Hex; 4] = [
+    ("1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649bf54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706", "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649bf54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706"),
+    ("0", "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97be"),
+    ("11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71c", "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38f"),
+    ("124c9ad43b6cf79bfbf7043de3811ad0761b0f37a1e26286b0e977c69aa274524e79097a56dc4bd9e1b371c71c718b10", "0"),
+];
+const ISO_Y_DEN: [Fp2Hex; 4] = [
+    ("1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb", "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb"),
+    ("0", "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa9d3"),
+    ("12", "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa99"),
+    ("1", "0"),
+];
+
+/// The effective cofactor `h_eff` that clears G2's cofactor (RFC 9380, section 8.8.2).
+const H_EFF: Uint<10> = Uint::from_be_hex(
+    "0bc69f08f2ee75b3584c6a0ea91b352888e2a8e9145ad7689986ff031508ffe1329c2f178731db956d82bf015d1212b02ec0ec69d7477c1ae954cbc06689f6a359894c0adebbf6b4e8020005aaa95551",
+);
+
+/// `expand_message_xmd` with SHA-256; `None` if the DST exceeds 255 bytes.
+fn expand_message_xmd(message: &[u8], dst: &[u8], len_in_bytes: usize) -> Option<Vec<u8>> {
+    let dst_len = u8::try_from(dst.len()).ok()?;
+    let ell = len_in_bytes.div_ceil(32);
+    let mut buf = Vec::with_capacity(64 + message.len() + 4 + dst.len());
+    buf.extend_from_slice(&[0u8; 64]);
+    buf.extend_from_slice(message);
+    buf.extend_from_slice(&(len_in_bytes as u16).to_be_bytes());
+    buf.push(0);
+    buf.extend_from_slice(dst);
+    buf.push(dst_len);
+    let mut b0 = [0u8; 32];
+    host::hash_sha2_256(&buf, &mut b0);
+
+    let mut out = Vec::with_capacity(ell * 32);
+    let mut b_prev = [0u8; 32];
+    for i in 1..=ell {
+        buf.clear();
+        buf.extend(b0.iter().zip(&b_prev).map(|(a, b)| a ^ b));
+        buf.push(i as u8);
+        buf.extend_from_slice(dst);
+        buf.push(dst_len);
+        host::hash_sha2_256(&buf, &mut b_prev);
+        out.extend_from_slice(&b_prev);
+    }
+    out.truncate(len_in_bytes);
+    Some(out)
+}
+
+/// `hash_to_field` for two `Fp2` elements, each coefficient from 64 bytes reduced mod `p`.
+fn hash_to_field(message: &[u8], dst: &[u8]) -> Option<[Fp2<6>; 2]> {
+    let bytes = expand_message_xmd(message, dst, 256)?;
+    let fp = &G1.field;
+    let mut modulus = [0u64; 8];
+    modulus[..6].copy_from_slice(&fp.modulus.0);
+    let reduce = |chunk: &[u8]| {
+        let (_, rem) = Uint::<8>::from_be_slice(chunk)?.div_rem(&Uint(modulus))?;
+        fp.element(&Uint(rem.0[..6].try_into().ok()?))
+    };
+    let mut elements = [Fp2::ZERO; 2];
+    for (element, chunk) in elements.iter_mut().zip(bytes.chunks_exact(128)) {
+        *element = Fp2 { c0: reduce(&chunk[..64])?, c1: reduce(&chunk[64..])? };
+    }
+    Some(elements)
+}
+
+/// `sgn0` for `Fp2`, on canonical coefficients.
+fn sgn0(a: &Fp2<6>) -> bool {
+    a.c0.is_odd() || (a.c0.is_zero() && a.c1.is_odd())
+}
+
+/// Simplified SWU onto `E2'`, then the isogeny onto G2's twist.
+fn map_to_curve(u: &Fp2<6>) -> Option<G2Jacobian<6>> {
+    let t = &G2.tower;
+    let z_u2 = t.fp2_mul(&SWU_Z, &t.fp2_square(u));
+    let x1 = match t.fp2_inv(&t.fp2_add(&t.fp2_square(&z_u2), &z_u2)) {
+        Some(tv1) => t.fp2_mul(&SWU_MINUS_B_OVER_A, &t.fp2_add(&t.fp2_one(), &tv1)),
+        None => SWU_B_OVER_ZA,
+    };
+    let gx = |x: &Fp2<6>| t.fp2_add(&t.fp2_mul(&t.fp2_add(&t.fp2_square(x), &ISO_A), x), &ISO_B);
+    let (x, y) = match t.fp2_sqrt(&gx(&x1)) {
+        Some(y) => (x1, y),
+        None => {
+            let x2 = t.fp2_mul(&z_u2, &x1);
+            (x2, t.fp2_sqrt(&gx(&x2))?)
+        }
+    };
+    let y = if sgn0(&t.fp2_canonical(u)) == sgn0(&t.fp2_canonical(&y)) { y } else { t.fp2_neg(&y) };
+
+    let horner = |coeffs: &[Fp2Hex]| {
+        coeffs.iter().rev().fold(Fp2::ZERO, |acc, &c| t.fp2_add(&t.fp2_mul(&acc, &x), &tower::fp2_from_hex(&t.fp, c)))
+    };
+    let (x_den, y_den) = (horner(&ISO_X_DEN), horner(&ISO_Y_DEN));
+    // A vanishing denominator means the isogeny sends the point to infinity.
+    let Some(den_inv) = t.fp2_inv(&t.fp2_mul(&x_den, &y_den)) else { return Some(G2Jacobian::INFINITY) };
+    let x = t.fp2_mul(&t.fp2_mul(&horner(&ISO_X_NUM), &y_den), &den_inv);
+    let y = t.fp2_mul(&t.fp2_mul(&t.fp2_mul(&y, &horner(&ISO_Y_NUM)), &x_den), &den_inv);
+    Some(G2Jacobian { x, y, z: t.fp2_one() })
+}
+
+/// `hash_to_curve` onto G2 with the given domain separation tag; `None` if the DST is longer
+/// than 255 bytes.
+pub fn hash_to_g2(message: &[u8], dst: &[u8]) -> Option<G2Jacobian<6>> {
+    let [u0, u1] = hash_to_field(message, dst)?;
+    let q = G2.add(&map_to_curve(&u0)?, &map_to_curve(&u1)?);
+    Some(G2.scalar_mul(&q, &H_EFF))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    const DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+    /// RFC 9380 Appendix J.10.1: messages and `P = (x_0 + x_1 * u, y_0 + y_1 * u)` as
+    /// `[x_1, x_0, y_1, y_0]`.
+    const HASH_TO_G2: [(&str, [&str; 4]); 3] = [
+        (
+            "",
+            [
+                "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+                "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+                "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+                "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+            ],
+        ),
+        (
+            "abc",
+            [
+                "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8",
+                "02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6",
+                "00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16",
+                "1787327b68159716a37440985269cf584bcb1e621d3a7202be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48",
+            ],
+        ),
+        (
+            "abcdef0123456789",
+            [
+                "190d119345b94fbd15497bcba94ecf7db2cbfd1e1fe7da034d26cbba169fb3968288b3fafb265f9ebd380512a71c3f2c",
+                "121982811d2491fde9ba7ed31ef9ca474f0e1501297f68c298e9f4c0028add35aea8bb83d53c08cfc007c1e005723cd0",
+                "0bb5e7572275c567462d91807de765611490205a941a5a6af3b1691bfe596c31225d3aabdf15faff860cb4ef17c7c3be",
+                "05571a0f8d3c08d094576981f4a3b8eda0a8e771fcdcc8ecceaf1356a6acf17574518acb506e435b639353c2e14827c8",
+            ],
+        ),
+    ];
+
+    /// The long messages of J.10.1, `q128_` and `a512_`, as the same four coordinates.
+    const HASH_TO_G2_LONG: [(char, usize, [&str; 4]); 2] = [
+        (
+            'q',
+            128,
+            [
+                "0934aba516a52d8ae479939a91998299c76d39cc0c035cd18813bec433f587e2d7a4fef038260eef0cef4d02aae3eb91",
+                "19a84dd7248a1066f737cc34502ee5555bd3c19f2ecdb3c7d9e24dc65d4e25e50d83f0f77105e955d78f4762d33c17da",
+                "09bcccfa036b4847c9950780733633f13619994394c23ff0b32fa6b795844f4a0673e20282d07bc69641cee04f5e5662",
+                "14f81cd421617428bc3b9fe25afbb751d934a00493524bc4e065635b0555084dd54679df1536101b2c979c0152d09192",
+            ],
+        ),
+        (
+            'a',
+            512,
+            [
+                "11fca2ff525572795a801eed17eb12785887c7b63fb77a42be46ce4a34131d71f7a73e95fee3f812aea3de78b4d01569",
+                "01a6ba2f9a11fa5598b2d8ace0fbe0a0eacb65deceb476fbbcb64fd24557c2f4b18ecfc5663e54ae16a84f5ab7f62534",
+                "03a47f8e6d1763ba0cad63d6114c0accbef65707825a511b251a660a9b3994249ae4e63fac38b23da0c398689ee2ab52",
+                "0b6798718c8aed24bc19cb27f866f1c9effcdbf92397ad6448b5c9db90d2b9da6cbabf48adc1adf59a1a28344e79d57e",
+            ],
+        ),
+    ];
+
+    fn check_hash_to_g2(message: &[u8], [x1, x0, y1, y0]: [&str; 4]) {
+        let p = hash_to_g2(message, DST).unwrap();
+        let fp2 = |c0, c1| Fp2 { c0: Uint::from_be_hex(c0), c1: Uint::from_be_hex(c1) };
+        assert_eq!(G2.to_affine(&p), G2Affine::Coordinate { x: fp2(x0, x1), y: fp2(y0, y1) });
+        // The compressed form is `x_1 || x_0` with the flags, and decodes back to `P`.
+        let encoded = encode_g2(&p);
+        let mut x = bytes(x1);
+        x.extend(bytes(x0));
+        assert_eq!((encoded[0] & 0x1f, &encoded[1..]), (x[0], &x[1..]));
+        assert_eq!(encoded[0] & (FLAG_COMPRESSED | FLAG_INFINITY), FLAG_COMPRESSED);
+        assert_eq!(G2.to_affine(&decode_g2(&encoded).unwrap()), G2.to_affine(&p));
+    }
+
+    #[test]
+    fn rfc9380_hash_to_g2() {
+        for (message, point) in HASH_TO_G2 {
+            check_hash_to_g2(message.as_bytes(), point);
+        }
+        for (letter, len, point) in HASH_TO_G2_LONG {
+            let mut message = alloc::format!("{letter}{len}_").into_bytes();
+            message.resize(message.len() + len, letter as u8);
+            check_hash_to_g2(&message, point);
+        }
+        assert!(hash_to_g2(b"abc", &[b'x'; 256]).is_none());
+    }
+
+    const G1_GENERATOR: &str = "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+    const G2_GENERATOR: &str = concat!(
+        "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e",
+        "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8",
+    );
+
+    #[test]
+    fn compressed_encoding() {
+        let g1 = decode_g1(&bytes(G1_GENERATOR)).unwrap();
+        assert_eq!(G1.to_affine(&g1), G1.generator);
+        assert_eq!(encode_g1(&g1), bytes(G1_GENERATOR));
+        assert_eq!(encode_g2(&decode_g2(&bytes(G2_GENERATOR)).unwrap()), bytes(G2_GENERATOR));
+        // The other root of the same x.
+        let mut negated = bytes(G1_GENERATOR);
+        negated[0] ^= FLAG_LARGEST;
+        assert_eq!(G1.to_affine(&G1.add(&g1, &decode_g1(&negated).unwrap())), AffinePoint::Infinity);
+
+        let mut infinity = [0u8; 96];
+        infinity[0] = FLAG_COMPRESSED | FLAG_INFINITY;
+        assert!(decode_g1(&infinity[..48]).unwrap().is_infinity());
+        assert!(decode_g2(&infinity).unwrap().is_infinity());
+        assert_eq!(encode_g1(&JacobianPoint::INFINITY), infinity[..48]);
+        assert_eq!(encode_g2(&G2Jacobian::INFINITY), infinity);
+        // Infinity must be all zeros, with no sign flag.
+        let mut flagged = infinity;
+        flagged[0] |= FLAG_LARGEST;
+        assert!(decode_g1(&flagged[..48]).is_none());
+        assert!(decode_g2(&flagged).is_none());
+        let mut dirty = infinity;
+        dirty[47] = 1;
+        assert!(decode_g1(&dirty[..48]).is_none());
+        dirty[95] = 1;
+        assert!(decode_g2(&dirty).is_none());
+
+        // Uncompressed flag, wrong lengths, and x = p.
+        let mut uncompressed = bytes(G1_GENERATOR);
+        uncompressed[0] &= !FLAG_COMPRESSED;
+        assert!(decode_g1(&uncompressed).is_none());
+        assert!(decode_g1(&bytes(G1_GENERATOR)[..47]).is_none());
+        assert!(decode_g2(&bytes(G1_GENERATOR)).is_none());
+        let mut p = bytes("1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab");
+        p[0] |= FLAG_COMPRESSED;
+        assert!(decode_g1(&p).is_none());
+        let mut g2_x_c1_p = p.clone();
+        g2_x_c1_p.extend(&bytes(G2_GENERATOR)[48..]);
+        assert!(decode_g2(&g2_x_c1_p).is_none());
+
+        // Points on the curves outside the order-r subgroups: G1 with x = 4, and G2 with x = 2,
+        // the first such x found by the `bls12_381` crate's unchecked decoding.
+        let mut g1_x4 = [0u8; 48];
+        g1_x4[0] = FLAG_COMPRESSED;
+        g1_x4[47] = 4;
+        assert!(decode_g1(&g1_x4).is_none());
+        let mut g2_x2 = [0u8; 96];
+        g2_x2[0] = FLAG_COMPRESSED;
+        g2_x2[95] = 2;
+        assert!(decode_g2(&g2_x2).is_none());
+    }
+}
@@ -0,0 +1,145 @@
+//! The optimal-ate pairing on BN254 (alt_bn128), as checked by the EIP-197 precompile.
+//!
+//! G1 is `curve::BN254`; G2 is the D-type twist `y^2 = x^3 + 3 / (9 + u)` over `Fp2`. The
+//! Miller loop runs over the NAF of `6x + 2` followed by the two Frobenius lines, and the hard
+//! part of the final exponentiation follows Fuentes-Castañeda et al., which yields a fixed
+//! power of the reduced pairing coprime to `r`; that is all an `== 1` check needs.
+
+use alloc::vec::Vec;
+
+use crate::curve::{self, AffinePoint};
+use crate::pairing::{G2Affine, G2Curve, MillerPair, TwistType};
+use crate::tower::{self, Fp12, Fp2, Tower};
+
+pub const G2: G2Curve<4> = G2Curve::new(
+    Tower::new(
+        "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+        ("9", "1"),
+        [
+            ("1", "0"),
+            ("2fb347984f7911f74c0bec3cf559b143b78cc310c2c3330c99e39557176f553d", "16c9e55061ebae204ba4cc8bd75a079432ae2a1d0b7c9dce1665d51c640fcba2"),
+            ("30644e72e131a0295e6dd9e7e0acccb0c28f069fbb966e3de4bd44e5607cfd48", "0"),
+            ("0856e078b755ef0abaff1c77959f25ac805ffd3d5d6942d37b746ee87bdcfb6d", "04f1de41b3d1766fa9f30e6dec26094f0fdf31bf98ff2631380cab2baaa586de"),
+        ],
+        [
+            ("1", "0"),
+            ("05b54f5e64eea80180f3c0b75a181e84d33365f7be94ec72848a1f55921ea762", "2c145edbe7fd8aee9f3a80b03b0b1c923685d2ea1bdec763c13b4711cd2b8126"),
+            ("000000000000000059e26bcea0d48bacd4f263f1acdb5c4f5763473177fffffe", "0"),
+            ("0bc58c6611c08dab19bee0f7b5b2444ee633094575b06bcb0e1a92bc3ccbf066", "23d5e999e1910a12feb0f6ef0cd21d04a44a9e08737f96e55fe3ed9d730c239f"),
+        ],
+        [
+            ("1", "0"),
+            ("1284b71c2865a7dfe8b99fdd76e68b605c521e08292f2176d60b35dadcc9e470", "246996f3b4fae7e6a6327cfe12150b8e747992778eeec7e5ca5cf05f80f362ac"),
+            ("30644e72e131a0295e6dd9e7e0acccb0c28f069fbb966e3de4bd44e5607cfd49", "0"),
+            ("19dc81cfcc82e4bbefe9608cd0acaa90894cb38dbe55d24ae86f7d391ed4a67f", "00abf8b60be77d7306cbeee33576139d7f03a5e397d439ec7694aa2bf4c0c101"),
+        ],
+    ),
+    ("2b149d40ceb8aaae81be18991be06ac3b5b4c5e559dbefa33267e6dc24a138e5", "009713b03af0fed4cd2cafadeed8fdf4a74fa084e52d1852e4a2bd0685c315d2"),
+    "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+    "183227397098d014dc2822db40c0ac2ecbc0b548b438e5469e10460b6c3e7ea4",
+    TwistType::D,
+);
+
+/// `xi^((p - 1) / 3)` and `xi^((p - 1) / 2)`: the untwist-Frobenius-twist endomorphism maps
+/// `(x, y)` to `(conj(x) * TWIST_MUL_BY_Q_X, conj(y) * TWIST_MUL_BY_Q_Y)`.
+const TWIST_MUL_BY_Q_X: Fp2<4> = tower::fp2_from_hex(
+    &G2.tower.fp,
+    ("2fb347984f7911f74c0bec3cf559b143b78cc310c2c3330c99e39557176f553d", "16c9e55061ebae204ba4cc8bd75a079432ae2a1d0b7c9dce1665d51c640fcba2"),
+);
+const TWIST_MUL_BY_Q_Y: Fp2<4> = tower::fp2_from_hex(
+    &G2.tower.fp,
+    ("063cf305489af5dcdc5ec698b6e2f9b9dbaae0eda9c95998dc54014671a0135a", "07c03cbcac41049a0704b5a7ec796f2b21807dc98fa25bd282d37f632623b0e3"),
+);
+
+/// The curve parameter `x`.
+const X: u64 = 4965661367192848881;
+
+/// The NAF of `6x + 2`, least significant digit first.
+const ATE_LOOP_COUNT: [i8; 66] = [
+    0, 0, 0, 1, 0, 1, 0, -1, 0, 0, -1, 0, 0, 0, 1, 0, 0, -1, 0, -1, 0, 0, 0, 1, 0, -1, 0, 0, 0, 0, -1, 0, 0,
+    1, 0, -1, 0, 0, 1, 0, 0, 0, 0, 0, -1, 0, 0, -1, 0, 1, 0, -1, 0, 0, 0, -1, 0, -1, 0, 0, 0, 1, 0, -1, 0, 1,
+];
+
+fn mul_by_char(x: &Fp2<4>, y: &Fp2<4>) -> (Fp2<4>, Fp2<4>) {
+    let t = &G2.tower;
+    (t.fp2_mul(&t.fp2_frobenius(x, 1), &TWIST_MUL_BY_Q_X), t.fp2_mul(&t.fp2_frobenius(y, 1), &TWIST_MUL_BY_Q_Y))
+}
+
+/// The product of the Miller functions of all pairs, sharing one `Fp12` accumulator.
+fn miller_loop(pairs: &mut [MillerPair<4>]) -> Fp12<4> {
+    let t = &G2.tower;
+    let mut f = t.fp12_one();
+    for i in (1..ATE_LOOP_COUNT.len()).rev() {
+        if i != ATE_LOOP_COUNT.len() - 1 { f = t.fp12_square(&f); }
+        for pair in pairs.iter_mut() {
+            f = G2.line_double(&f, pair);
+            let (qx, qy) = (pair.qx, pair.qy);
+            match ATE_LOOP_COUNT[i - 1] {
+                1 => f = G2.line_add(&f, pair, &qx, &qy),
+                -1 => f = G2.line_add(&f, pair, &qx, &t.fp2_neg(&qy)),
+                _ => {}
+            }
+        }
+    }
+    for pair in pairs.iter_mut() {
+        let (q1x, q1y) = mul_by_char(&pair.qx, &pair.qy);
+        let (q2x, q2y) = mul_by_char(&q1x, &q1y);
+        f = G2.line_add(&f, pair, &q1x, &q1y);
+        f = G2.line_add(&f, pair, &q2x, &t.fp2_neg(&q2y));
+    }
+    f
+}
+
+/// `f^(-x)` for `f` in the cyclotomic subgroup, where inversion is conjugation.
+fn exp_by_neg_x(f: &Fp12<4>) -> Fp12<4> {
+    let t = &G2.tower;
+    let mut acc = t.fp12_one();
+    for i in (0..u64::BITS - X.leading_zeros()).rev() {
+        acc = t.fp12_square(&acc);
+        if (X >> i) & 1 == 1 { acc = t.fp12_mul(&acc, f); }
+    }
+    t.fp12_conjugate(&acc)
+}
+
+/// Raises `f` to `(p^12 - 1) / r` times a fixed factor coprime to `r`; `None` if `f` is zero.
+fn final_exponentiation(f: &Fp12<4>) -> Option<Fp12<4>> {
+    let t = &G2.tower;
+    // Easy part: f^((p^6 - 1)(p^2 + 1)).
+    let r = t.fp12_mul(&t.fp12_conjugate(f), &t.fp12_inv(f)?);
+    let r = t.fp12_mul(&t.fp12_frobenius(&r, 2), &r);
+    // Hard part.
+    let y0 = exp_by_neg_x(&r);
+    let y1 = t.fp12_square(&y0);
+    let y2 = t.fp12_square(&y1);
+    let y3 = t.fp12_mul(&y2, &y1);
+    let y4 = exp_by_neg_x(&y3);
+    let y5 = t.fp12_square(&y4);
+    let y6 = t.fp12_conjugate(&exp_by_neg_x(&y5));
+    let y3 = t.fp12_conjugate(&y3);
+    let y7 = t.fp12_mul(&y6, &y4);
+    let y8 = t.fp12_mul(&y7, &y3);
+    let y9 = t.fp12_mul(&y8, &y1);
+    let y10 = t.fp12_mul(&y8, &y4);
+    let y11 = t.fp12_mul(&y10, &r);
+    let y13 = t.fp12_mul(&t.fp12_frobenius(&y9, 1), &y11);
+    let y14 = t.fp12_mul(&t.fp12_frobenius(&y8, 2), &y13);
+    let y15 = t.fp12_frobenius(&t.fp12_mul(&t.fp12_conjugate(&r), &y9), 3);
+    Some(t.fp12_mul(&y15, &y14))
+}
+
+/// Checks `e(P1, Q1) * ... * e(Pn, Qn) == 1`, as EIP-197 does; an empty product passes.
+/// Returns `None` if a point is not on its curve or a G2 point lies outside the order-`r`
+/// subgroup. Pairs with a point at infinity contribute 1 and are skipped.
+pub fn pairing_check(pairs: &[(AffinePoint, G2Affine<4>)]) -> Option<bool> {
+    let mut miller_pairs = Vec::with_capacity(pairs.len());
+    for (p, q) in pairs {
+        let p_jacobian = curve::BN254.to_jacobian(p)?;
+        let q_jacobian = G2.to_jacobian(q)?;
+        if !G2.is_in_subgroup(&q_jacobian) { return None; }
+        if p_jacobian.is_infinity() || q_jacobian.is_infinity() { continue; }
+        // Both lifts are affine (z = 1), so their coordinates are already in Montgomery form.
+        miller_pairs.push(G2.miller_pair(p_jacobian.x, p_jacobian.y, q_jacobian.x, q_jacobian.y));
+    }
+    let f = miller_loop(&mut miller_pairs);
+    Some(final_exponentiation(&f) == Some(G2.tower.fp12_one()))
+}
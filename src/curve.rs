@@ -0,0 +1,477 @@
+//! Short Weierstrass curves `y^2 = x^3 + ax + b` over 256-bit prime fields.
+//!
+//! Arithmetic runs in Jacobian coordinates `(X, Y, Z)`, standing for `(X/Z^2, Y/Z^3)`, so a
+//! whole scalar multiplication needs a single field inversion in `to_affine`. Jacobian
+//! coordinates are kept in Montgomery form; `AffinePoint` carries canonical values.
+//!
+//! None of this is constant time: it is meant for verifying public data, not for handling
+//! secret scalars.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::field::PrimeField;
+use crate::msm;
+use crate::uint::{Uint, U256};
+
+pub struct Curve<const L: usize = 4> {
+    pub field: PrimeField<L>,
+    /// The prime order `n` of the generator, as a field for scalar arithmetic.
+    pub scalar: PrimeField<4>,
+    /// Coefficients `a` and `b`, in Montgomery form.
+    pub a: Uint<L>,
+    pub b: Uint<L>,
+    pub generator: AffinePoint<L>,
+}
+
+impl<const L: usize> Curve<L> {
+    pub const fn new(modulus: &str, order: &str, a: &str, b: &str, gx: &str, gy: &str) -> Self {
+        let field = PrimeField::new(Uint::from_be_hex(modulus));
+        let a = field.element_reduced(&Uint::from_be_hex(a));
+        let b = field.element_reduced(&Uint::from_be_hex(b));
+        let generator = AffinePoint::Coordinate { x: Uint::from_be_hex(gx), y: Uint::from_be_hex(gy) };
+        Curve { field, scalar: PrimeField::new(U256::from_be_hex(order)), a, b, generator }
+    }
+}
+
+pub const SECP256K1: Curve = Curve::new(
+    "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+    "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+    "0",
+    "7",
+    "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+    "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+);
+
+pub const P256: Curve = Curve::new(
+    "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff",
+    "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551",
+    "ffffffff00000001000000000000000000000000fffffffffffffffffffffffc",
+    "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b",
+    "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+    "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5",
+);
+
+/// The BN254 (alt_bn128) G1 group used by the EIP-196/197 precompiles.
+pub const BN254: Curve = Curve::new(
+    "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+    "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+    "0",
+    "3",
+    "1",
+    "2",
+);
+
+/// Maps the ABI curve identifier to its parameters: 0 = secp256k1, 1 = P-256, 2 = BN254.
+pub fn curve_by_id(id: u64) -> Option<&'static Curve> {
+    match id {
+        0 => Some(&SECP256K1),
+        1 => Some(&P256),
+        2 => Some(&BN254),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AffinePoint<const L: usize = 4> {
+    Infinity,
+    Coordinate { x: Uint<L>, y: Uint<L> },
+}
+
+/// A point in Jacobian coordinates; `z == 0` is the point at infinity.
+#[derive(Debug, Clone, Copy)]
+pub struct JacobianPoint<const L: usize = 4> {
+    pub x: Uint<L>,
+    pub y: Uint<L>,
+    pub z: Uint<L>,
+}
+
+impl<const L: usize> JacobianPoint<L> {
+    pub const INFINITY: Self = JacobianPoint { x: Uint::ZERO, y: Uint::ZERO, z: Uint::ZERO };
+
+    pub fn is_infinity(&self) -> bool { self.z.is_zero() }
+}
+
+impl<const L: usize> Curve<L> {
+    /// `x^3 + ax + b` for `x` in Montgomery form.
+    pub fn rhs(&self, x: &Uint<L>) -> Uint<L> {
+        let f = &self.field;
+        let x3 = f.mul(&f.square(x), x);
+        f.add(&f.add(&x3, &f.mul(&self.a, x)), &self.b)
+    }
+
+    pub fn is_on_curve(&self, p: &AffinePoint<L>) -> bool {
+        self.to_jacobian(p).is_some()
+    }
+
+    /// Lifts an affine point, or `None` if a coordinate is not a field element or the point
+    /// does not satisfy the curve equation. Every entry point goes through here, which rules
+    /// out invalid-curve inputs.
+    pub fn to_jacobian(&self, p: &AffinePoint<L>) -> Option<JacobianPoint<L>> {
+        match p {
+            AffinePoint::Infinity => Some(JacobianPoint::INFINITY),
+            AffinePoint::Coordinate { x, y } => {
+                let x = self.field.element(x)?;
+                let y = self.field.element(y)?;
+                if self.field.square(&y) != self.rhs(&x) { return None; }
+                Some(JacobianPoint { x, y, z: self.field.one })
+            }
+        }
+    }
+
+    /// SEC1 compressed encoding: `0x02 | x` or `0x03 | x` by the parity of `y`, or the single
+    /// byte `0x00` for infinity.
+    pub fn encode_compressed(&self, p: &AffinePoint<L>) -> Option<Vec<u8>> {
+        self.to_jacobian(p)?;
+        Some(match p {
+            AffinePoint::Infinity => vec![0x00],
+            AffinePoint::Coordinate { x, y } => {
+                let mut out = vec![0u8; 1 + 8 * L];
+                out[0] = 0x02 | y.is_odd() as u8;
+                x.write_be_bytes(&mut out[1..]);
+                out
+            }
+        })
+    }
+
+    /// Decodes any SEC1 encoding (infinity, compressed or uncompressed), recovering `y` from
+    /// the curve equation for compressed input.
+    pub fn decode_sec1(&self, bytes: &[u8]) -> Option<AffinePoint<L>> {
+        let f = &self.field;
+        let size = 8 * L;
+        let point = match (bytes.first()?, bytes.len() - 1) {
+            (0x00, 0) => AffinePoint::Infinity,
+            (0x04, len) if len == 2 * size => AffinePoint::Coordinate {
+                x: Uint::from_be_slice(&bytes[1..=size])?,
+                y: Uint::from_be_slice(&bytes[1 + size..])?,
+            },
+            (&prefix @ (0x02 | 0x03), len) if len == size => {
+                let x = Uint::from_be_slice(&bytes[1..])?;
+                let root = f.canonical(&f.sqrt(&self.rhs(&f.element(&x)?))?);
+                let y = if root.is_odd() == (prefix == 0x03) { root } else { f.modulus.wrapping_sub(&root) };
+                // A zero root has no odd counterpart; it only matches prefix 0x02.
+                if y == f.modulus { return None; }
+                AffinePoint::Coordinate { x, y }
+            }
+            _ => return None,
+        };
+        self.to_jacobian(&point).map(|_| point)
+    }
+
+    pub fn to_affine(&self, p: &JacobianPoint<L>) -> AffinePoint<L> {
+        let f = &self.field;
+        let Some(z_inv) = f.inv(&p.z) else { return AffinePoint::Infinity };
+        let z_inv2 = f.square(&z_inv);
+        let x = f.mul(&p.x, &z_inv2);
+        let y = f.mul(&p.y, &f.mul(&z_inv2, &z_inv));
+        AffinePoint::Coordinate { x: f.canonical(&x), y: f.canonical(&y) }
+    }
+
+    /// Doubling ("dbl-2007-bl"), skipping the `a * Z^4` term on `a = 0` curves.
+    pub fn double(&self, p: &JacobianPoint<L>) -> JacobianPoint<L> {
+        let f = &self.field;
+        if p.is_infinity() || p.y.is_zero() { return JacobianPoint::INFINITY; }
+        let xx = f.square(&p.x);
+        let yy = f.square(&p.y);
+        let yyyy = f.square(&yy);
+        // S = 2 * ((X + YY)^2 - XX - YYYY) = 4 * X * YY
+        let s = f.double(&f.sub(&f.sub(&f.square(&f.add(&p.x, &yy)), &xx), &yyyy));
+        // M = 3 * XX + a * Z^4
+        let mut m = f.add(&f.double(&xx), &xx);
+        if !self.a.is_zero() {
+            m = f.add(&m, &f.mul(&self.a, &f.square(&f.square(&p.z))));
+        }
+        let x = f.sub(&f.square(&m), &f.double(&s));
+        let eight_yyyy = f.double(&f.double(&f.double(&yyyy)));
+        let y = f.sub(&f.mul(&m, &f.sub(&s, &x)), &eight_yyyy);
+        let z = f.double(&f.mul(&p.y, &p.z));
+        JacobianPoint { x, y, z }
+    }
+
+    pub fn add(&self, p: &JacobianPoint<L>, q: &JacobianPoint<L>) -> JacobianPoint<L> {
+        let f = &self.field;
+        if p.is_infinity() { return *q; }
+        if q.is_infinity() { return *p; }
+        let z1z1 = f.square(&p.z);
+        let z2z2 = f.square(&q.z);
+        let u1 = f.mul(&p.x, &z2z2);
+        let u2 = f.mul(&q.x, &z1z1);
+        let s1 = f.mul(&p.y, &f.mul(&q.z, &z2z2));
+        let s2 = f.mul(&q.y, &f.mul(&p.z, &z1z1));
+        let h = f.sub(&u2, &u1);
+        let r = f.sub(&s2, &s1);
+        if h.is_zero() {
+            return if r.is_zero() { self.double(p) } else { JacobianPoint::INFINITY };
+        }
+        let hh = f.square(&h);
+        let hhh = f.mul(&h, &hh);
+        let v = f.mul(&u1, &hh);
+        let x = f.sub(&f.sub(&f.square(&r), &hhh), &f.double(&v));
+        let y = f.sub(&f.mul(&r, &f.sub(&v, &x)), &f.mul(&s1, &hhh));
+        let z = f.mul(&f.mul(&p.z, &q.z), &h);
+        JacobianPoint { x, y, z }
+    }
+
+    /// `[0, p, 2p, ..., 15p]` for the 4-bit windowed multiplications.
+    fn window_table(&self, p: &JacobianPoint<L>) -> [JacobianPoint<L>; 16] {
+        let mut table = [JacobianPoint::INFINITY; 16];
+        table[1] = *p;
+        for i in 2..16 {
+            table[i] = if i % 2 == 0 { self.double(&table[i / 2]) } else { self.add(&table[i - 1], p) };
+        }
+        table
+    }
+
+    /// `k * p` with a fixed 4-bit window: four doublings and at most one addition per nibble.
+    pub fn scalar_mul(&self, p: &JacobianPoint<L>, k: &U256) -> JacobianPoint<L> {
+        self.double_scalar_mul(p, k, &JacobianPoint::INFINITY, &U256::ZERO)
+    }
+
+    /// `k1 * p + k2 * q` (Straus): both 4-bit windows share a single chain of doublings, which
+    /// is what signature verification needs.
+    pub fn double_scalar_mul(&self, p: &JacobianPoint<L>, k1: &U256, q: &JacobianPoint<L>, k2: &U256) -> JacobianPoint<L> {
+        let (p_table, q_table) = (self.window_table(p), self.window_table(q));
+        let nibble = |k: &U256, window: usize| ((k.0[window / 16] >> (4 * (window % 16))) & 0xF) as usize;
+        let mut acc = JacobianPoint::INFINITY;
+        for window in (0..k1.bits().max(k2.bits()).div_ceil(4) as usize).rev() {
+            for _ in 0..4 {
+                acc = self.double(&acc);
+            }
+            acc = self.add(&acc, &p_table[nibble(k1, window)]);
+            acc = self.add(&acc, &q_table[nibble(k2, window)]);
+        }
+        acc
+    }
+
+    /// `k_1 * P_1 + ... + k_n * P_n` by Pippenger's method, over pairs of `points` and `scalars`.
+    pub fn msm(&self, points: &[JacobianPoint<L>], scalars: &[U256]) -> JacobianPoint<L> {
+        let add = |p: &JacobianPoint<L>, q: &JacobianPoint<L>| Some(self.add(p, q));
+        let double = |p: &JacobianPoint<L>| Some(self.double(p));
+        msm::pippenger(points, scalars, JacobianPoint::INFINITY, add, double).unwrap_or(JacobianPoint::INFINITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(text: &str) -> AffinePoint {
+        let bytes = crate::hex::decode(text.as_bytes()).unwrap();
+        let (x, y) = bytes.split_at(32);
+        AffinePoint::Coordinate { x: U256::from_be_slice(x).unwrap(), y: U256::from_be_slice(y).unwrap() }
+    }
+
+    /// `2G`, `3G` and `7G` from the `k256` and `p256` crates and arkworks' `ark-bn254`.
+    const MULTIPLES: [(&Curve, [&str; 3]); 3] = [
+        (&SECP256K1, [
+            "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee51ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a",
+            "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672",
+            "5cbdf0646e5db4eaa398f365f2ea7a0e3d419b7e0330e39ce92bddedcac4f9bc6aebca40ba255960a3178d6d861a54dba813d0b813fde7b5a5082628087264da",
+        ]),
+        (&P256, [
+            "7cf27b188d034f7e8a52380304b51ac3c08969e277f21b35a60b48fc4766997807775510db8ed040293d9ac69f7430dbba7dade63ce982299e04b79d227873d1",
+            "5ecbe4d1a6330a44c8f7ef951d4bf165e6c6b721efada985fb41661bc6e7fd6c8734640c4998ff7e374b06ce1a64a2ecd82ab036384fb83d9a79b127a27d5032",
+            "8e533b6fa0bf7b4625bb30667c01fb607ef9f8b8a80fef5b300628703187b2a373eb1dbde03318366d069f83a6f5900053c73633cb041b21c55e1a86c1f400b4",
+        ]),
+        (&BN254, [
+            "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd315ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4",
+            "0769bf9ac56bea3ff40232bcb1b6bd159315d84715b8e679f2d355961915abf02ab799bee0489429554fdb7c8d086475319e63b40b9c5b57cdf1ff3dd9fe2261",
+            "17072b2ed3bb8d759a5325f477629386cb6fc6ecb801bd76983a6b86abffe078168ada6cd130dd52017bb54bfa19377aadfe3bf05d18f41b77809f7f60d4af9e",
+        ]),
+    ];
+
+    #[test]
+    fn small_multiples_of_the_generator() {
+        for (curve, [two, three, seven]) in MULTIPLES {
+            let g = curve.to_jacobian(&curve.generator).unwrap();
+            let (two, three, seven) = (point(two), point(three), point(seven));
+            assert!(curve.is_on_curve(&two) && curve.is_on_curve(&three) && curve.is_on_curve(&seven));
+
+            let doubled = curve.double(&g);
+            assert_eq!(curve.to_affine(&doubled), two);
+            assert_eq!(curve.to_affine(&curve.add(&g, &g)), two);
+            // Both operands of these have `Z != 1`.
+            let tripled = curve.add(&doubled, &g);
+            assert_eq!(curve.to_affine(&tripled), three);
+            assert_eq!(curve.to_affine(&curve.add(&g, &doubled)), three);
+            let six = curve.double(&tripled);
+            assert_eq!(curve.to_affine(&curve.add(&six, &g)), seven);
+            assert_eq!(curve.to_affine(&curve.add(&curve.add(&doubled, &doubled), &tripled)), seven);
+
+            for (k, expected) in [(0, AffinePoint::Infinity), (1, curve.generator), (2, two), (3, three), (7, seven)] {
+                assert_eq!(curve.to_affine(&curve.scalar_mul(&g, &U256::from_u64(k))), expected, "{k}G");
+            }
+        }
+    }
+
+    #[test]
+    fn multiples_wrap_at_the_group_order() {
+        for curve in [&SECP256K1, &P256, &BN254] {
+            let g = curve.to_jacobian(&curve.generator).unwrap();
+            let n = curve.scalar.modulus;
+            let AffinePoint::Coordinate { x, y } = curve.generator else { unreachable!() };
+            let minus_g = AffinePoint::Coordinate { x, y: curve.field.modulus.wrapping_sub(&y) };
+
+            let scalar_mul = |k: &U256| curve.to_affine(&curve.scalar_mul(&g, k));
+            assert_eq!(scalar_mul(&n.wrapping_sub(&U256::ONE)), minus_g);
+            assert_eq!(scalar_mul(&n), AffinePoint::Infinity);
+            assert_eq!(scalar_mul(&n.wrapping_add(&U256::ONE)), curve.generator);
+            assert_eq!(scalar_mul(&n.wrapping_add(&U256::from_u64(2))), curve.to_affine(&curve.double(&g)));
+
+            // P + (-P) is infinity, and infinity is the identity on both sides.
+            let minus_g = curve.to_jacobian(&minus_g).unwrap();
+            assert!(curve.add(&g, &minus_g).is_infinity());
+            assert_eq!(curve.to_affine(&curve.add(&g, &JacobianPoint::INFINITY)), curve.generator);
+            assert_eq!(curve.to_affine(&curve.add(&JacobianPoint::INFINITY, &g)), curve.generator);
+            assert!(curve.double(&JacobianPoint::INFINITY).is_infinity());
+            assert!(curve.scalar_mul(&JacobianPoint::INFINITY, &n.wrapping_sub(&U256::ONE)).is_infinity());
+        }
+    }
+
+    #[test]
+    fn double_scalar_mul_and_msm_agree_with_scalar_mul() {
+        let mut rng = crate::Xorshift64Star::new(32);
+        let mut random_scalar = || Uint([rng.next(), rng.next(), rng.next(), rng.next()]);
+        for curve in [&SECP256K1, &P256, &BN254] {
+            let g = curve.to_jacobian(&curve.generator).unwrap();
+            let n = &curve.scalar.modulus;
+            for _ in 0..4 {
+                let (a, b, c) = (random_scalar(), random_scalar(), random_scalar());
+                let q = curve.scalar_mul(&g, &c);
+                let expected = curve.add(&curve.scalar_mul(&g, &a), &curve.scalar_mul(&q, &b));
+                let expected = curve.to_affine(&expected);
+                assert_eq!(curve.to_affine(&curve.double_scalar_mul(&g, &a, &q, &b)), expected);
+                assert_eq!(curve.to_affine(&curve.msm(&[g, q], &[a, b])), expected);
+
+                // aG + bG = ((a + b) mod n)G.
+                let a = a.div_rem(n).unwrap().1;
+                let b = b.div_rem(n).unwrap().1;
+                let sum = a.add_mod(&b, n);
+                assert_eq!(
+                    curve.to_affine(&curve.double_scalar_mul(&g, &a, &g, &b)),
+                    curve.to_affine(&curve.scalar_mul(&g, &sum)),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn to_jacobian_rejects_points_off_the_curve() {
+        for curve in [&SECP256K1, &P256, &BN254] {
+            let AffinePoint::Coordinate { x, y } = curve.generator else { unreachable!() };
+            let p = curve.field.modulus;
+            assert!(curve.is_on_curve(&AffinePoint::Infinity));
+            assert!(!curve.is_on_curve(&AffinePoint::Coordinate { x, y: y.wrapping_add(&U256::ONE) }));
+            assert!(!curve.is_on_curve(&AffinePoint::Coordinate { x: U256::ZERO, y: U256::ZERO }));
+            // Coordinates must be reduced, even if they are congruent to a valid point.
+            assert!(!curve.is_on_curve(&AffinePoint::Coordinate { x, y: y.wrapping_add(&p) }));
+            if let Some(x) = x.checked_add(&p) {
+                assert!(!curve.is_on_curve(&AffinePoint::Coordinate { x, y }));
+            }
+        }
+    }
+
+    fn sec1(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    /// Compressed SEC1 encodings of `G`, `-G` and `3G` with both parity prefixes, from the
+    /// `k256` and `p256` crates; BN254's generator is `(1, 2)`.
+    const COMPRESSED: [(&Curve, [&str; 3]); 3] = [
+        (&SECP256K1, [
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "0379be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+        ]),
+        (&P256, [
+            "036b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+            "026b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+            "025ecbe4d1a6330a44c8f7ef951d4bf165e6c6b721efada985fb41661bc6e7fd6c",
+        ]),
+        (&BN254, [
+            "020000000000000000000000000000000000000000000000000000000000000001",
+            "030000000000000000000000000000000000000000000000000000000000000001",
+            "030769bf9ac56bea3ff40232bcb1b6bd159315d84715b8e679f2d355961915abf0",
+        ]),
+    ];
+
+    #[test]
+    fn sec1_compression_round_trips() {
+        for (curve, encodings) in COMPRESSED {
+            let g = curve.to_jacobian(&curve.generator).unwrap();
+            let minus_g = curve.scalar_mul(&g, &curve.scalar.modulus.wrapping_sub(&U256::ONE));
+            let three_g = curve.scalar_mul(&g, &U256::from_u64(3));
+            for (p, encoding) in [g, minus_g, three_g].iter().zip(encodings) {
+                let p = curve.to_affine(p);
+                let AffinePoint::Coordinate { x, y } = p else { unreachable!() };
+                assert_eq!(curve.encode_compressed(&p), Some(sec1(encoding)));
+                assert_eq!(curve.decode_sec1(&sec1(encoding)), Some(p));
+
+                let mut uncompressed = vec![0x04; 65];
+                x.write_be_bytes(&mut uncompressed[1..33]);
+                y.write_be_bytes(&mut uncompressed[33..]);
+                assert_eq!(curve.decode_sec1(&uncompressed), Some(p));
+                // A flipped `y` is still on the curve; a shifted one is not.
+                let flipped = curve.field.modulus.wrapping_sub(&y);
+                flipped.write_be_bytes(&mut uncompressed[33..]);
+                assert_ne!(curve.decode_sec1(&uncompressed), Some(p));
+                assert!(curve.decode_sec1(&uncompressed).is_some());
+                y.wrapping_add(&U256::ONE).write_be_bytes(&mut uncompressed[33..]);
+                assert_eq!(curve.decode_sec1(&uncompressed), None);
+            }
+            assert_eq!(curve.encode_compressed(&AffinePoint::Infinity), Some(vec![0x00]));
+            assert_eq!(curve.decode_sec1(&[0x00]), Some(AffinePoint::Infinity));
+        }
+    }
+
+    #[test]
+    fn sec1_decoding_rejects_malformed_points() {
+        for (curve, [encoding, ..]) in COMPRESSED {
+            let compressed = sec1(encoding);
+            let AffinePoint::Coordinate { x, y } = curve.generator else { unreachable!() };
+            let p = curve.field.modulus;
+
+            // Hybrid (0x06/0x07) and unknown prefixes, and lengths that do not match the prefix.
+            for prefix in [0x01, 0x05, 0x06, 0x07, 0xff] {
+                let mut bytes = compressed.clone();
+                bytes[0] = prefix;
+                assert_eq!(curve.decode_sec1(&bytes), None, "prefix {prefix:#04x}");
+            }
+            assert_eq!(curve.decode_sec1(&[]), None);
+            assert_eq!(curve.decode_sec1(&[0x00, 0x00]), None);
+            assert_eq!(curve.decode_sec1(&compressed[..32]), None);
+            let mut long = compressed.clone();
+            long.push(0);
+            assert_eq!(curve.decode_sec1(&long), None);
+            let mut short_uncompressed = compressed.clone();
+            short_uncompressed[0] = 0x04;
+            assert_eq!(curve.decode_sec1(&short_uncompressed), None);
+
+            // `x >= p`, including `x + p` where it fits, which is congruent to a valid `x`.
+            let mut bytes = compressed.clone();
+            for unreduced in [Some(p), p.checked_add(&U256::ONE), x.checked_add(&p)].into_iter().flatten() {
+                unreduced.write_be_bytes(&mut bytes[1..]);
+                assert_eq!(curve.decode_sec1(&bytes), None);
+                let mut uncompressed = vec![0x04; 65];
+                unreduced.write_be_bytes(&mut uncompressed[1..33]);
+                y.write_be_bytes(&mut uncompressed[33..]);
+                assert_eq!(curve.decode_sec1(&uncompressed), None);
+            }
+
+            // The first `x` whose `x^3 + ax + b` is a non-residue has no point with either prefix.
+            let f = &curve.field;
+            let half = p.wrapping_sub(&U256::ONE).shr(1);
+            let x = (0..).map(U256::from_u64)
+                .find(|x| f.pow(&curve.rhs(&f.element(x).unwrap()), &half) == f.neg(&f.one))
+                .unwrap();
+            for prefix in [0x02, 0x03] {
+                let mut bytes = vec![prefix; 33];
+                x.write_be_bytes(&mut bytes[1..]);
+                assert_eq!(curve.decode_sec1(&bytes), None);
+            }
+
+            assert_eq!(curve.encode_compressed(&AffinePoint::Coordinate { x, y }), None);
+            assert_eq!(curve.encode_compressed(&AffinePoint::Coordinate { x: p, y }), None);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! ECDSA verification and public key recovery (SEC1 section 4.1) over the `curve` module.
+
+use crate::curve::{AffinePoint, Curve};
+use crate::uint::U256;
+
+/// The message hash as a scalar: its leftmost `bits(n)` bits, reduced mod `n`.
+fn hash_to_scalar(curve: &Curve, hash: &[u8; 32]) -> U256 {
+    let n = &curve.scalar.modulus;
+    let z = U256::from_be_bytes(hash).shr(256 - n.bits());
+    if z >= *n { z.wrapping_sub(n) } else { z }
+}
+
+/// `r` and `s` as Montgomery scalars, or `None` unless both are in `[1, n - 1]`.
+fn signature_scalars(curve: &Curve, r: &U256, s: &U256) -> Option<(U256, U256)> {
+    if r.is_zero() || s.is_zero() { return None; }
+    Some((curve.scalar.element(r)?, curve.scalar.element(s)?))
+}
+
+/// Whether `s <= n / 2`, the canonical half that rules out signature malleability.
+fn is_low_s(curve: &Curve, s: &U256) -> bool {
+    *s <= curve.scalar.modulus.shr(1)
+}
+
+/// Checks `(r, s)` against `pubkey`. Both halves of `s` are accepted, since P-256 signers
+/// (WebAuthn passkeys in particular) do not normalize it.
+pub fn verify(curve: &Curve, hash: &[u8; 32], r: &U256, s: &U256, pubkey: &AffinePoint) -> bool {
+    let n = &curve.scalar;
+    let Some((r_mont, s_mont)) = signature_scalars(curve, r, s) else { return false };
+    let Some(q) = curve.to_jacobian(pubkey) else { return false };
+    if q.is_infinity() { return false; }
+    let g = curve.to_jacobian(&curve.generator).unwrap();
+    let Some(w) = n.inv(&s_mont) else { return false };
+    let z = n.element(&hash_to_scalar(curve, hash)).unwrap();
+    let u1 = n.canonical(&n.mul(&z, &w));
+    let u2 = n.canonical(&n.mul(&r_mont, &w));
+    match curve.to_affine(&curve.double_scalar_mul(&g, &u1, &q, &u2)) {
+        AffinePoint::Infinity => false,
+        AffinePoint::Coordinate { x, .. } => x.div_rem(&n.modulus).unwrap().1 == *r,
+    }
+}
+
+/// Recovers the public key from a signature with low `s`. Bit 0 of `recovery_id` is the
+/// parity of `R.y` and bit 1 selects `R.x = r + n`.
+pub fn recover(curve: &Curve, hash: &[u8; 32], recovery_id: u8, r: &U256, s: &U256) -> Option<AffinePoint> {
+    let n = &curve.scalar;
+    if recovery_id > 3 || !is_low_s(curve, s) { return None; }
+    let (r_mont, s_mont) = signature_scalars(curve, r, s)?;
+    let x = if recovery_id & 2 == 0 { *r } else { r.checked_add(&n.modulus)? };
+    let mut encoded = [0u8; 33];
+    encoded[0] = 0x02 | (recovery_id & 1);
+    x.write_be_bytes(&mut encoded[1..]);
+    let big_r = curve.to_jacobian(&curve.decode_sec1(&encoded)?)?;
+    let g = curve.to_jacobian(&curve.generator)?;
+    // Q = r^-1 * (s * R - z * G)
+    let r_inv = n.inv(&r_mont)?;
+    let z = n.element(&hash_to_scalar(curve, hash))?;
+    let u1 = n.canonical(&n.neg(&n.mul(&z, &r_inv)));
+    let u2 = n.canonical(&n.mul(&s_mont, &r_inv));
+    let q = curve.double_scalar_mul(&g, &u1, &big_r, &u2);
+    if q.is_infinity() { return None; }
+    Some(curve.to_affine(&q))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{P256, SECP256K1};
+    use crate::hash::{keccak256, sha256};
+
+    fn point(curve: &Curve, text: &str) -> AffinePoint {
+        curve.decode_sec1(&crate::hex::decode(text.as_bytes()).unwrap()).unwrap()
+    }
+
+    /// RFC 6979 signatures over the Keccak-256 of the message, from the `k256` crate:
+    /// `(private key, public key, message, r, s, recovery id)`.
+    const SECP256K1_VECTORS: [(u8, &str, &str, &str, &str, u8); 4] = [
+        (1, "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+            "hello",
+            "433ec3d37e4f1253df15e2dea412fed8e915737730f74b3dfb1353268f932ef5",
+            "557c9158e0b34bce39de28d11797b42e9b1acb2749230885fe075aedc3e491a4", 0),
+        (3, "04f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672",
+            "x",
+            "e6172c6649f6475e830fc626f00dc7081ad220f1832f3109b6417d15b0afdeec",
+            "7643e272623099ee8e29a2e113ba21154162d655a0ea99a20fb32b7d15cd9544", 1),
+        (7, "045cbdf0646e5db4eaa398f365f2ea7a0e3d419b7e0330e39ce92bddedcac4f9bc6aebca40ba255960a3178d6d861a54dba813d0b813fde7b5a5082628087264da",
+            "abc",
+            "49e5ca9bd394e0adac4a9325089aa388ce7a5182f6db32e4103d384ea3295d55",
+            "11302cfe1c9aeea078a86672829b7c6d1d588d5e91df177667e19aaee3827cf8", 1),
+        (42, "04fe8d1eb1bcb3432b1db5833ff5f2226d9cb5e65cee430558c18ed3a3c86ce1af07b158f244cd0de2134ac7c1d371cffbfae4db40801a2572e531c573cda9b5b4",
+            "polkavm",
+            "937c9eca1e7147b453866c619dd1d9d478f681af6ccd09013c3eb3bebef700a6",
+            "6719396dabc5e7d08d22aa1c9130ed6992dbdd0afd17361a5a4a1d7652e280e3", 1),
+    ];
+
+    /// RFC 6979 appendix A.2.5, P-256 with SHA-256: the key and the `(message, r, s)`
+    /// signatures. The "sample" `s` is in the high half.
+    const P256_PUBLIC_KEY: &str = "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299";
+    const P256_VECTORS: [(&str, &str, &str); 2] = [
+        ("sample",
+            "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716",
+            "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"),
+        ("test",
+            "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367",
+            "019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083"),
+    ];
+
+    #[test]
+    fn secp256k1_known_answers() {
+        for (key, pubkey, message, r, s, recovery_id) in SECP256K1_VECTORS {
+            let (q, hash) = (point(&SECP256K1, pubkey), keccak256(message.as_bytes()));
+            let (r, s) = (U256::from_be_hex(r), U256::from_be_hex(s));
+            let g = SECP256K1.to_jacobian(&SECP256K1.generator).unwrap();
+            assert_eq!(SECP256K1.to_affine(&SECP256K1.scalar_mul(&g, &U256::from_u64(key.into()))), q);
+
+            assert!(verify(&SECP256K1, &hash, &r, &s, &q), "{message}");
+            assert_eq!(recover(&SECP256K1, &hash, recovery_id, &r, &s), Some(q), "{message}");
+            // The other parity yields a different key, and `r + n` is past the field.
+            assert_ne!(recover(&SECP256K1, &hash, recovery_id ^ 1, &r, &s), Some(q));
+            assert_eq!(recover(&SECP256K1, &hash, recovery_id | 2, &r, &s), None);
+            assert_eq!(recover(&SECP256K1, &hash, 4, &r, &s), None);
+
+            // `n - s` verifies too, but recovery insists on the low half.
+            let high_s = SECP256K1.scalar.modulus.wrapping_sub(&s);
+            assert!(verify(&SECP256K1, &hash, &r, &high_s, &q));
+            assert_eq!(recover(&SECP256K1, &hash, recovery_id ^ 1, &r, &high_s), None);
+
+            let mut other = hash;
+            other[31] ^= 1;
+            assert!(!verify(&SECP256K1, &other, &r, &s, &q));
+            assert!(!verify(&SECP256K1, &hash, &s, &r, &q));
+            assert!(!verify(&SECP256K1, &hash, &U256::ZERO, &s, &q));
+            assert!(!verify(&SECP256K1, &hash, &r, &SECP256K1.scalar.modulus, &q));
+            assert!(!verify(&SECP256K1, &hash, &r, &s, &AffinePoint::Infinity));
+        }
+    }
+
+    #[test]
+    fn p256_known_answers() {
+        let q = point(&P256, P256_PUBLIC_KEY);
+        let n = &P256.scalar.modulus;
+        for ((message, r, s), recovery_id) in P256_VECTORS.into_iter().zip([1, 0]) {
+            let hash = sha256(message.as_bytes());
+            let (r, s) = (U256::from_be_hex(r), U256::from_be_hex(s));
+            assert!(verify(&P256, &hash, &r, &s, &q), "{message}");
+            assert!(verify(&P256, &hash, &r, &n.wrapping_sub(&s), &q), "{message}");
+            assert!(!verify(&P256, &sha256(b"other"), &r, &s, &q));
+            assert!(!verify(&SECP256K1, &hash, &r, &s, &point(&SECP256K1, SECP256K1_VECTORS[0].1)));
+
+            // Recovery ids are for the low-`s` form.
+            let low_s = if is_low_s(&P256, &s) { s } else { n.wrapping_sub(&s) };
+            assert_eq!(recover(&P256, &hash, recovery_id, &r, &low_s), Some(q), "{message}");
+            assert_ne!(recover(&P256, &hash, recovery_id ^ 1, &r, &low_s), Some(q));
+        }
+        let (_, r, s) = P256_VECTORS[0];
+        assert_eq!(recover(&P256, &sha256(b"sample"), 0, &U256::from_be_hex(r), &U256::from_be_hex(s)), None);
+    }
+}
@@ -0,0 +1,257 @@
+//! Edwards25519, `-x^2 + y^2 = 1 + d x^2 y^2` over `2^255 - 19`, and Ed25519 verification
+//! (RFC 8032).
+//!
+//! Points use extended coordinates `(X : Y : Z : T)` with `T = XY/Z`, for which the unified
+//! addition law below is complete. Coordinates are in Montgomery form over `FIELD`; scalars
+//! and encodings are little-endian, as the RFC specifies.
+
+use crate::field::PrimeField;
+use crate::hash::Sha512;
+use crate::uint::{Uint, U256};
+
+pub const FIELD: PrimeField<4> =
+    PrimeField::new(U256::from_be_hex("7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed"));
+
+/// The order `l` of the prime-order subgroup generated by the base point.
+pub const SCALAR: PrimeField<4> =
+    PrimeField::new(U256::from_be_hex("1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed"));
+
+pub const D: U256 =
+    FIELD.element_reduced(&U256::from_be_hex("52036cee2b6ffe738cc740797779e89800700a4d4141d8ab75eb4dca135978a3"));
+const D2: U256 = FIELD.add(&D, &D);
+
+/// A square root of -1.
+pub const SQRT_M1: U256 =
+    FIELD.element_reduced(&U256::from_be_hex("2b8324804fc1df0b2b4d00993dfbd7a72f431806ad2fe478c4ee1b274a0ea0b0"));
+
+/// `(p - 5) / 8`, the exponent of the combined inverse square root.
+const P_MINUS_5_DIV_8: U256 = U256::from_be_hex("0ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffd");
+
+pub const BASEPOINT: EdwardsPoint = {
+    let x = FIELD.element_reduced(&U256::from_be_hex("216936d3cd6e53fec0a4e231fdd6dc5c692cc7609525a7b2c9562d608f25d51a"));
+    let y = FIELD.element_reduced(&U256::from_be_hex("6666666666666666666666666666666666666666666666666666666666666658"));
+    EdwardsPoint { x, y, z: FIELD.one, t: FIELD.mul(&x, &y) }
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct EdwardsPoint {
+    pub x: U256,
+    pub y: U256,
+    pub z: U256,
+    pub t: U256,
+}
+
+impl EdwardsPoint {
+    pub const IDENTITY: Self = EdwardsPoint { x: U256::ZERO, y: FIELD.one, z: FIELD.one, t: U256::ZERO };
+
+    /// Unified addition ("add-2008-hwcd-3" with `a = -1`).
+    pub fn add(&self, other: &Self) -> Self {
+        let f = &FIELD;
+        let a = f.mul(&f.sub(&self.y, &self.x), &f.sub(&other.y, &other.x));
+        let b = f.mul(&f.add(&self.y, &self.x), &f.add(&other.y, &other.x));
+        let c = f.mul(&f.mul(&self.t, &D2), &other.t);
+        let d = f.double(&f.mul(&self.z, &other.z));
+        let (e, ff, g, h) = (f.sub(&b, &a), f.sub(&d, &c), f.add(&d, &c), f.add(&b, &a));
+        EdwardsPoint { x: f.mul(&e, &ff), y: f.mul(&g, &h), z: f.mul(&ff, &g), t: f.mul(&e, &h) }
+    }
+
+    /// Doubling ("dbl-2008-hwcd" with `a = -1`).
+    pub fn double(&self) -> Self {
+        let f = &FIELD;
+        let a = f.square(&self.x);
+        let b = f.square(&self.y);
+        let c = f.double(&f.square(&self.z));
+        let e = f.sub(&f.sub(&f.square(&f.add(&self.x, &self.y)), &a), &b);
+        let g = f.sub(&b, &a);
+        let ff = f.sub(&g, &c);
+        let h = f.neg(&f.add(&a, &b));
+        EdwardsPoint { x: f.mul(&e, &ff), y: f.mul(&g, &h), z: f.mul(&ff, &g), t: f.mul(&e, &h) }
+    }
+
+    pub fn neg(&self) -> Self {
+        EdwardsPoint { x: FIELD.neg(&self.x), y: self.y, z: self.z, t: FIELD.neg(&self.t) }
+    }
+
+    /// Canonical affine `(x, y)`.
+    pub fn to_affine(self) -> (U256, U256) {
+        let f = &FIELD;
+        let z_inv = f.inv(&self.z).unwrap();
+        (f.canonical(&f.mul(&self.x, &z_inv)), f.canonical(&f.mul(&self.y, &z_inv)))
+    }
+
+    /// RFC 8032 encoding: `y` little-endian with the sign of `x` in the top bit.
+    pub fn compress(&self) -> [u8; 32] {
+        let (x, y) = self.to_affine();
+        let mut bytes = u256_to_le_bytes(&y);
+        bytes[31] |= (x.is_odd() as u8) << 7;
+        bytes
+    }
+
+    /// RFC 8032 decoding; rejects `y >= p` and encodings with no matching `x`.
+    pub fn decompress(bytes: &[u8; 32]) -> Option<Self> {
+        let f = &FIELD;
+        let x_sign = bytes[31] >> 7 == 1;
+        let mut y_bytes = *bytes;
+        y_bytes[31] &= 0x7f;
+        let y = f.element(&u256_from_le_bytes(&y_bytes))?;
+        let yy = f.square(&y);
+        let (is_square, x) = sqrt_ratio_m1(&f.sub(&yy, &f.one), &f.add(&f.mul(&D, &yy), &f.one));
+        if !is_square || (x.is_zero() && x_sign) { return None; }
+        let x = if x_sign { f.neg(&x) } else { x };
+        Some(EdwardsPoint { x, y, z: f.one, t: f.mul(&x, &y) })
+    }
+
+    /// `[0, p, 2p, ..., 15p]` for 4-bit windows.
+    fn window_table(&self) -> [EdwardsPoint; 16] {
+        let mut table = [EdwardsPoint::IDENTITY; 16];
+        table[1] = *self;
+        for i in 2..16 {
+            table[i] = if i % 2 == 0 { table[i / 2].double() } else { table[i - 1].add(self) };
+        }
+        table
+    }
+}
+
+/// `k1 * p + k2 * q` for canonical scalars, sharing the doublings between two 4-bit windows.
+pub fn double_scalar_mul(p: &EdwardsPoint, k1: &U256, q: &EdwardsPoint, k2: &U256) -> EdwardsPoint {
+    let (p_table, q_table) = (p.window_table(), q.window_table());
+    let nibble = |k: &U256, window: usize| ((k.0[window / 16] >> (4 * (window % 16))) & 0xF) as usize;
+    let mut acc = EdwardsPoint::IDENTITY;
+    for window in (0..k1.bits().max(k2.bits()).div_ceil(4) as usize).rev() {
+        for _ in 0..4 {
+            acc = acc.double();
+        }
+        acc = acc.add(&p_table[nibble(k1, window)]);
+        acc = acc.add(&q_table[nibble(k2, window)]);
+    }
+    acc
+}
+
+/// A field element is "negative" when its canonical value is odd.
+pub fn is_negative(a: &U256) -> bool {
+    FIELD.canonical(a).is_odd()
+}
+
+/// `SQRT_RATIO_M1` from RFC 9496: the non-negative square root of `u / v` and whether it
+/// exists; otherwise the root of `SQRT_M1 * u / v`.
+pub fn sqrt_ratio_m1(u: &U256, v: &U256) -> (bool, U256) {
+    let f = &FIELD;
+    let v3 = f.mul(&f.square(v), v);
+    let v7 = f.mul(&f.square(&v3), v);
+    let r = f.mul(&f.mul(u, &v3), &f.pow(&f.mul(u, &v7), &P_MINUS_5_DIV_8));
+    let check = f.mul(v, &f.square(&r));
+    let neg_u = f.neg(u);
+    let correct_sign = check == *u;
+    let flipped_sign = check == neg_u;
+    let flipped_sign_i = check == f.mul(&neg_u, &SQRT_M1);
+    let r = if flipped_sign || flipped_sign_i { f.mul(&r, &SQRT_M1) } else { r };
+    let r = if is_negative(&r) { f.neg(&r) } else { r };
+    (correct_sign || flipped_sign, r)
+}
+
+pub fn u256_from_le_bytes(bytes: &[u8; 32]) -> U256 {
+    let mut be = *bytes;
+    be.reverse();
+    U256::from_be_bytes(&be)
+}
+
+pub fn u256_to_le_bytes(value: &U256) -> [u8; 32] {
+    let mut bytes = value.to_be_bytes();
+    bytes.reverse();
+    bytes
+}
+
+/// A scalar encoding, accepted only if it is canonical (below `l`).
+pub fn scalar_from_canonical_bytes(bytes: &[u8; 32]) -> Option<U256> {
+    let s = u256_from_le_bytes(bytes);
+    (s < SCALAR.modulus).then_some(s)
+}
+
+/// A 64-byte little-endian hash reduced mod `l`.
+pub fn scalar_from_wide_bytes(bytes: &[u8; 64]) -> U256 {
+    let mut be = *bytes;
+    be.reverse();
+    let wide = Uint::<8>::from_be_slice(&be).unwrap();
+    let mut l = [0u64; 8];
+    l[..4].copy_from_slice(&SCALAR.modulus.0);
+    let (_, rem) = wide.div_rem(&Uint(l)).unwrap();
+    Uint([rem.0[0], rem.0[1], rem.0[2], rem.0[3]])
+}
+
+/// Ed25519 verification: accepts when `[s]B = R + [k]A` with `k = SHA-512(R || A || M) mod l`,
+/// checked by re-encoding `[s]B - [k]A` and comparing it with `R` byte for byte.
+pub fn ed25519_verify(public_key: &[u8; 32], r: &[u8; 32], s: &[u8; 32], message: &[u8]) -> bool {
+    let Some(s) = scalar_from_canonical_bytes(s) else { return false };
+    let Some(a) = EdwardsPoint::decompress(public_key) else { return false };
+    let mut hasher = Sha512::new();
+    hasher.update(r);
+    hasher.update(public_key);
+    hasher.update(message);
+    let k = scalar_from_wide_bytes(&hasher.finalize());
+    double_scalar_mul(&BASEPOINT, &s, &a.neg(), &k).compress() == *r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    fn verify(public_key: &str, message: &str, signature: &str) -> bool {
+        let signature = bytes(signature);
+        ed25519_verify(&bytes(public_key).try_into().unwrap(), &signature[..32].try_into().unwrap(), &signature[32..].try_into().unwrap(), &bytes(message))
+    }
+
+    /// RFC 8032 §7.1: TEST 1, 2, 3 and SHA(abc).
+    const VECTORS: [(&str, &str, &str); 4] = [
+        (
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+        (
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+        ),
+        (
+            "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf",
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b58909351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704",
+        ),
+    ];
+
+    #[test]
+    fn rfc8032_vectors() {
+        for (public_key, message, signature) in VECTORS {
+            assert!(verify(public_key, message, signature));
+        }
+    }
+
+    #[test]
+    fn rejects_altered_signatures() {
+        let (public_key, _, signature) = VECTORS[1];
+        // Another message, a flipped bit of R and of s, and another key.
+        assert!(!verify(public_key, "73", signature));
+        let mut altered = bytes(signature);
+        altered[0] ^= 1;
+        assert!(!verify(public_key, "72", &crate::hex::encode(&altered)));
+        let mut altered = bytes(signature);
+        altered[40] ^= 1;
+        assert!(!verify(public_key, "72", &crate::hex::encode(&altered)));
+        assert!(!verify(VECTORS[0].0, "72", signature));
+        // s + l, the same scalar in non-canonical form.
+        let mut altered = bytes(signature);
+        let s = u256_from_le_bytes(&altered[32..].try_into().unwrap());
+        altered[32..].copy_from_slice(&u256_to_le_bytes(&s.wrapping_add(&SCALAR.modulus)));
+        assert!(!verify(public_key, "72", &crate::hex::encode(&altered)));
+    }
+}
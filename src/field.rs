@@ -0,0 +1,252 @@
+//! Prime field arithmetic in Montgomery form over `Uint<L>`.
+//!
+//! Field elements are plain `Uint<L>` values holding `a * R mod p` with `R = 2^(64L)`, always
+//! fully reduced, so equality and zero tests work directly on the representation. Parameters
+//! are derived by `const fn`, which lets curve and hash constants live in `const` items.
+
+use crate::uint::Uint;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PrimeField<const L: usize> {
+    pub modulus: Uint<L>,
+    /// `-p^-1 mod 2^64`.
+    inv: u64,
+    /// `R^2 mod p`, used to move values into Montgomery form.
+    r2: Uint<L>,
+    /// `R mod p`, the Montgomery form of one.
+    pub one: Uint<L>,
+}
+
+impl<const L: usize> PrimeField<L> {
+    /// Derives the Montgomery parameters for an odd prime `modulus`.
+    pub const fn new(modulus: Uint<L>) -> Self {
+        assert!(modulus.0[0] & 1 == 1, "modulus must be odd");
+        // Newton iteration doubles the number of correct low bits each round.
+        let mut inv = 1u64;
+        let mut i = 0;
+        while i < 6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(modulus.0[0].wrapping_mul(inv)));
+            i += 1;
+        }
+        // R mod p and R^2 mod p by repeated doubling of one.
+        let mut acc = Uint::<L>::ONE;
+        let mut i = 0;
+        while i < 64 * L {
+            acc = acc.add_mod(&acc, &modulus);
+            i += 1;
+        }
+        let one = acc;
+        while i < 128 * L {
+            acc = acc.add_mod(&acc, &modulus);
+            i += 1;
+        }
+        PrimeField { modulus, inv: inv.wrapping_neg(), r2: acc, one }
+    }
+
+    /// Montgomery product `a * b * R^-1 mod p` (CIOS).
+    pub const fn mul(&self, a: &Uint<L>, b: &Uint<L>) -> Uint<L> {
+        let p = &self.modulus.0;
+        let mut t = [0u64; L];
+        let mut t_hi = 0u64;
+        let mut i = 0;
+        while i < L {
+            let mut carry = 0u64;
+            let mut j = 0;
+            while j < L {
+                let v = t[j] as u128 + a.0[j] as u128 * b.0[i] as u128 + carry as u128;
+                t[j] = v as u64;
+                carry = (v >> 64) as u64;
+                j += 1;
+            }
+            let v = t_hi as u128 + carry as u128;
+            t_hi = v as u64;
+            let t_top = (v >> 64) as u64;
+
+            let m = t[0].wrapping_mul(self.inv);
+            let v = t[0] as u128 + m as u128 * p[0] as u128;
+            let mut carry = (v >> 64) as u64;
+            let mut j = 1;
+            while j < L {
+                let v = t[j] as u128 + m as u128 * p[j] as u128 + carry as u128;
+                t[j - 1] = v as u64;
+                carry = (v >> 64) as u64;
+                j += 1;
+            }
+            let v = t_hi as u128 + carry as u128;
+            t[L - 1] = v as u64;
+            t_hi = t_top + (v >> 64) as u64;
+            i += 1;
+        }
+        let t = Uint(t);
+        if t_hi != 0 || t.const_ge(&self.modulus) { t.wrapping_sub(&self.modulus) } else { t }
+    }
+
+    pub fn square(&self, a: &Uint<L>) -> Uint<L> { self.mul(a, a) }
+
+    pub const fn add(&self, a: &Uint<L>, b: &Uint<L>) -> Uint<L> { a.add_mod(b, &self.modulus) }
+    pub const fn sub(&self, a: &Uint<L>, b: &Uint<L>) -> Uint<L> { a.sub_mod(b, &self.modulus) }
+    pub fn double(&self, a: &Uint<L>) -> Uint<L> { self.add(a, a) }
+    pub fn neg(&self, a: &Uint<L>) -> Uint<L> { self.sub(&Uint::ZERO, a) }
+
+    /// Converts a canonical value into Montgomery form, or `None` if it is not below `p`.
+    pub fn element(&self, value: &Uint<L>) -> Option<Uint<L>> {
+        if *value >= self.modulus { return None; }
+        Some(self.mul(value, &self.r2))
+    }
+
+    /// Like `element` for constants known to be reduced.
+    pub const fn element_reduced(&self, value: &Uint<L>) -> Uint<L> { self.mul(value, &self.r2) }
+
+    /// Converts out of Montgomery form into the canonical value.
+    pub const fn canonical(&self, a: &Uint<L>) -> Uint<L> { self.mul(a, &Uint::ONE) }
+
+    /// Square-and-multiply with a canonical exponent.
+    pub fn pow(&self, base: &Uint<L>, exp: &Uint<L>) -> Uint<L> {
+        let mut result = self.one;
+        for i in (0..exp.bits()).rev() {
+            result = self.square(&result);
+            if exp.bit(i) { result = self.mul(&result, base); }
+        }
+        result
+    }
+
+    /// A square root of `a`, or `None` for a non-residue. Uses the single exponentiation
+    /// `a^((p+1)/4)` when `p = 3 mod 4`, and Tonelli-Shanks otherwise.
+    pub fn sqrt(&self, a: &Uint<L>) -> Option<Uint<L>> {
+        if a.is_zero() { return Some(Uint::ZERO); }
+        let p_minus_one = self.modulus.wrapping_sub(&Uint::ONE);
+        let s = p_minus_one.trailing_zeros();
+        if s == 1 {
+            let root = self.pow(a, &self.modulus.wrapping_add(&Uint::ONE).shr(2));
+            return (self.square(&root) == *a).then_some(root);
+        }
+        // p - 1 = q * 2^s with q odd; find any non-residue z by Euler's criterion.
+        let q = p_minus_one.shr(s);
+        let half = p_minus_one.shr(1);
+        let minus_one = self.neg(&self.one);
+        let mut z = self.add(&self.one, &self.one);
+        while self.pow(&z, &half) != minus_one {
+            z = self.add(&z, &self.one);
+        }
+        let mut m = s;
+        let mut c = self.pow(&z, &q);
+        let mut t = self.pow(a, &q);
+        let mut root = self.pow(a, &q.wrapping_add(&Uint::ONE).shr(1));
+        while t != self.one {
+            // Least i with t^(2^i) = 1; reaching m means `a` is a non-residue.
+            let mut i = 0;
+            let mut t_pow = t;
+            while t_pow != self.one {
+                t_pow = self.square(&t_pow);
+                i += 1;
+                if i == m { return None; }
+            }
+            let mut b = c;
+            for _ in 0..m - i - 1 {
+                b = self.square(&b);
+            }
+            m = i;
+            c = self.square(&b);
+            t = self.mul(&t, &c);
+            root = self.mul(&root, &b);
+        }
+        Some(root)
+    }
+
+    /// Multiplicative inverse via the binary extended GCD on the canonical value; `None` for zero.
+    pub fn inv(&self, a: &Uint<L>) -> Option<Uint<L>> {
+        let inverse = crate::modinv(self.canonical(a), self.modulus)?;
+        Some(self.mul(&inverse, &self.r2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{BN254, P256, SECP256K1};
+    use crate::uint::U256;
+    use alloc::vec::Vec;
+
+    /// The base and scalar fields of the three curves; BN254's scalar field has
+    /// `p - 1 = q * 2^28`, which takes Tonelli-Shanks the long way.
+    fn fields() -> [PrimeField<4>; 6] {
+        [SECP256K1.field, SECP256K1.scalar, P256.field, P256.scalar, BN254.field, BN254.scalar]
+    }
+
+    #[test]
+    fn montgomery_arithmetic_matches_plain_modular_arithmetic() {
+        let mut rng = crate::Xorshift64Star::new(0xf1e1d);
+        for f in fields() {
+            let p = &f.modulus;
+            let mut random = || Uint([rng.next(), rng.next(), rng.next(), rng.next()]).div_rem(p).unwrap().1;
+            assert_eq!(f.canonical(&f.one), U256::ONE);
+            assert_eq!(f.element(p), None);
+            assert_eq!(f.element(&p.wrapping_sub(&U256::ONE)), Some(f.neg(&f.one)));
+            for _ in 0..32 {
+                let (a, b) = (random(), random());
+                let (ma, mb) = (f.element(&a).unwrap(), f.element(&b).unwrap());
+                assert_eq!(f.canonical(&ma), a);
+                assert_eq!(f.canonical(&f.mul(&ma, &mb)), a.mul_mod(&b, p));
+                assert_eq!(f.canonical(&f.add(&ma, &mb)), a.add_mod(&b, p));
+                assert_eq!(f.canonical(&f.sub(&ma, &mb)), a.sub_mod(&b, p));
+                assert_eq!(f.canonical(&f.pow(&ma, &b)), a.pow_mod(&b, p));
+                assert_eq!(f.mul(&ma, &f.inv(&ma).unwrap()), f.one);
+                // Fermat: a^(p - 1) = 1.
+                assert_eq!(f.pow(&ma, &p.wrapping_sub(&U256::ONE)), f.one);
+            }
+            assert_eq!(f.inv(&U256::ZERO), None);
+        }
+    }
+
+    #[test]
+    fn sqrt_of_squares_and_non_residues() {
+        let mut rng = crate::Xorshift64Star::new(0x5a7);
+        for f in fields() {
+            let p = &f.modulus;
+            let half = p.wrapping_sub(&U256::ONE).shr(1);
+            let minus_one = f.neg(&f.one);
+            assert_eq!(f.sqrt(&U256::ZERO), Some(U256::ZERO));
+            for _ in 0..16 {
+                let a = f.element(&Uint([rng.next(), rng.next(), rng.next(), rng.next()]).div_rem(p).unwrap().1).unwrap();
+                let square = f.square(&a);
+                let root = f.sqrt(&square).unwrap();
+                assert!(root == a || root == f.neg(&a));
+                // Euler's criterion decides whether `a` itself has a root.
+                match f.sqrt(&a) {
+                    Some(root) => assert_eq!(f.square(&root), a),
+                    None => assert_eq!(f.pow(&a, &half), minus_one),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sqrt_on_every_element_of_small_fields() {
+        // p = 3 mod 4, then 2-adicities 2, 5 and 8.
+        for p in [103u64, 101, 97, 257] {
+            let f = PrimeField::new(Uint([p]));
+            let squares: Vec<u64> = (0..p).map(|x| x * x % p).collect();
+            for a in 0..p {
+                let element = f.element(&Uint([a])).unwrap();
+                match f.sqrt(&element) {
+                    Some(root) => assert_eq!(squares[f.canonical(&root).0[0] as usize], a, "sqrt({a}) mod {p}"),
+                    None => assert!(!squares.contains(&a), "sqrt({a}) mod {p}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_limb_field_matches_u128_arithmetic() {
+        let p = u64::MAX - 58; // 2^64 - 59
+        let f = PrimeField::new(Uint([p]));
+        let mut rng = crate::Xorshift64Star::new(59);
+        for _ in 0..256 {
+            let (a, b) = (rng.next() % p, rng.next() % p);
+            let (ma, mb) = (f.element(&Uint([a])).unwrap(), f.element(&Uint([b])).unwrap());
+            assert_eq!(f.canonical(&f.mul(&ma, &mb)).0[0] as u128, a as u128 * b as u128 % p as u128);
+            assert_eq!(f.canonical(&f.add(&ma, &mb)).0[0] as u128, (a as u128 + b as u128) % p as u128);
+            assert_eq!(f.canonical(&f.sub(&ma, &mb)).0[0] as u128, (a as u128 + p as u128 - b as u128) % p as u128);
+        }
+    }
+}
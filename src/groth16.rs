@@ -0,0 +1,45 @@
+//! Groth16 verification over BN254, with the pairing equation snarkjs and the Solidity
+//! verifiers use: `e(A, B) = e(alpha, beta) * e(L, gamma) * e(C, delta)`, where `L` is the
+//! linear combination of the `IC` points with `1, x_1, ..., x_n`.
+
+use alloc::vec::Vec;
+
+use crate::bn254;
+use crate::curve::{self, AffinePoint, JacobianPoint};
+use crate::pairing::G2Affine;
+use crate::uint::U256;
+
+pub struct VerifyingKey {
+    pub alpha: AffinePoint,
+    pub beta: G2Affine<4>,
+    pub gamma: G2Affine<4>,
+    pub delta: G2Affine<4>,
+    /// One point per public input, plus the constant term first.
+    pub ic: Vec<AffinePoint>,
+}
+
+pub struct Proof {
+    pub a: AffinePoint,
+    pub b: G2Affine<4>,
+    pub c: AffinePoint,
+}
+
+/// Returns `None` for malformed input: a point off its curve or subgroup, a public input not
+/// below `r`, or a number of inputs that does not match the key.
+pub fn verify(vk: &VerifyingKey, proof: &Proof, public_inputs: &[U256]) -> Option<bool> {
+    let g1 = &curve::BN254;
+    if vk.ic.len() != public_inputs.len() + 1 { return None; }
+    let mut l = g1.to_jacobian(&vk.ic[0])?;
+    for (input, point) in public_inputs.iter().zip(&vk.ic[1..]) {
+        if *input >= g1.scalar.modulus { return None; }
+        l = g1.add(&l, &g1.scalar_mul(&g1.to_jacobian(point)?, input));
+    }
+    let a = g1.to_jacobian(&proof.a)?;
+    let neg_a = g1.to_affine(&JacobianPoint { y: g1.field.neg(&a.y), ..a });
+    bn254::pairing_check(&[
+        (neg_a, proof.b),
+        (vk.alpha, vk.beta),
+        (g1.to_affine(&l), vk.gamma),
+        (proof.c, vk.delta),
+    ])
+}
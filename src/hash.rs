@@ -0,0 +1,495 @@
+//! Hash primitives the host does not expose. `keccak256`, `sha256` and BLAKE2b-256 come from
+//! the runtime (`host::hash_*`); everything here is computed in-contract. Host-side tests have
+//! no runtime, so there the first two are computed here as well.
+
+const SHA512_K: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+const SHA512_IV: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+/// Incremental SHA-512 (FIPS 180-4), so callers can hash several pieces without
+/// concatenating them on the bump heap.
+pub struct Sha512 {
+    state: [u64; 8],
+    block: [u8; 128],
+    block_len: usize,
+    total_len: u128,
+}
+
+impl Sha512 {
+    pub fn new() -> Self {
+        Sha512 { state: SHA512_IV, block: [0; 128], block_len: 0, total_len: 0 }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u128;
+        while !data.is_empty() {
+            let take = (128 - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + take].copy_from_slice(&data[..take]);
+            self.block_len += take;
+            data = &data[take..];
+            if self.block_len == 128 {
+                sha512_compress(&mut self.state, &self.block);
+                self.block_len = 0;
+            }
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; 64] {
+        let bit_len = self.total_len << 3;
+        self.block[self.block_len] = 0x80;
+        self.block[self.block_len + 1..].fill(0);
+        if self.block_len >= 112 {
+            sha512_compress(&mut self.state, &self.block);
+            self.block = [0; 128];
+        }
+        self.block[112..].copy_from_slice(&bit_len.to_be_bytes());
+        sha512_compress(&mut self.state, &self.block);
+        let mut out = [0u8; 64];
+        for (chunk, word) in out.chunks_exact_mut(8).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+}
+
+fn sha512_compress(state: &mut [u64; 8], block: &[u8; 128]) {
+    let mut w = [0u64; 80];
+    for (i, chunk) in block.chunks_exact(8).enumerate() {
+        w[i] = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for i in 16..80 {
+        let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+        let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+        w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (k, w) in SHA512_K.iter().zip(w) {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(*k).wrapping_add(w);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+const KECCAK_ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// Rotation offsets and lane order for the combined rho and pi steps.
+const KECCAK_RHO: [u32; 24] = [1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44];
+const KECCAK_PI: [usize; 24] = [10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1];
+
+/// The Keccak-f[1600] permutation on 25 little-endian lanes.
+pub fn keccak_f1600(state: &mut [u64; 25]) {
+    for round_constant in KECCAK_ROUND_CONSTANTS {
+        // theta
+        let mut c = [0u64; 5];
+        for (x, column) in c.iter_mut().enumerate() {
+            *column = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+        // rho and pi
+        let mut carry = state[1];
+        for (&rotation, &target) in KECCAK_RHO.iter().zip(&KECCAK_PI) {
+            let next = state[target];
+            state[target] = carry.rotate_left(rotation);
+            carry = next;
+        }
+        // chi
+        for y in 0..5 {
+            let row = [state[5 * y], state[5 * y + 1], state[5 * y + 2], state[5 * y + 3], state[5 * y + 4]];
+            for x in 0..5 {
+                state[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+        // iota
+        state[0] ^= round_constant;
+    }
+}
+
+/// SHA3-256 (FIPS 202): the Keccak sponge with a 136-byte rate and the `0x06` domain padding.
+pub fn sha3_256(data: &[u8]) -> [u8; 32] {
+    keccak_sponge_256(data, 0x06)
+}
+
+/// Ethereum's `keccak256`, the same sponge with the original `0x01` padding.
+#[cfg(test)]
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    keccak_sponge_256(data, 0x01)
+}
+
+fn keccak_sponge_256(data: &[u8], domain: u8) -> [u8; 32] {
+    const RATE: usize = 136;
+    let mut state = [0u64; 25];
+    let mut absorb = |block: &[u8; RATE]| {
+        for (lane, chunk) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        keccak_f1600(&mut state);
+    };
+    let mut chunks = data.chunks_exact(RATE);
+    for chunk in &mut chunks {
+        absorb(chunk.try_into().unwrap());
+    }
+    let rest = chunks.remainder();
+    let mut last = [0u8; RATE];
+    last[..rest.len()].copy_from_slice(rest);
+    last[rest.len()] ^= domain;
+    last[RATE - 1] ^= 0x80;
+    absorb(&last);
+    let mut out = [0u8; 32];
+    for (chunk, lane) in out.chunks_exact_mut(8).zip(state) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+    out
+}
+
+/// Message word order for each BLAKE2 round; BLAKE2b's rounds 10 and 11 reuse rows 0 and 1.
+const BLAKE2_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// BLAKE2b shares SHA-512's initial values; BLAKE2s takes SHA-256's, their high halves.
+const BLAKE2S_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Defines an unkeyed BLAKE2 variant (RFC 7693) over `$word` lanes: the compression function
+/// and a one-shot hash with an `N`-byte digest.
+macro_rules! blake2 {
+    ($name:ident, $compress:ident, $word:ty, $iv:expr, $rounds:expr, $rotations:expr) => {
+        fn $compress(h: &mut [$word; 8], block: &[u8], counter: u128, last: bool) {
+            const BYTES: usize = <$word>::BITS as usize / 8;
+            let mut m = [0 as $word; 16];
+            for (word, chunk) in m.iter_mut().zip(block.chunks_exact(BYTES)) {
+                *word = <$word>::from_le_bytes(chunk.try_into().unwrap());
+            }
+            let mut v = [0 as $word; 16];
+            v[..8].copy_from_slice(h);
+            v[8..].copy_from_slice(&$iv);
+            v[12] ^= counter as $word;
+            v[13] ^= (counter >> <$word>::BITS) as $word;
+            if last { v[14] = !v[14]; }
+            let [r1, r2, r3, r4]: [u32; 4] = $rotations;
+            let mut g = |a: usize, b: usize, c: usize, d: usize, x: $word, y: $word| {
+                v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+                v[d] = (v[d] ^ v[a]).rotate_right(r1);
+                v[c] = v[c].wrapping_add(v[d]);
+                v[b] = (v[b] ^ v[c]).rotate_right(r2);
+                v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+                v[d] = (v[d] ^ v[a]).rotate_right(r3);
+                v[c] = v[c].wrapping_add(v[d]);
+                v[b] = (v[b] ^ v[c]).rotate_right(r4);
+            };
+            for round in 0..$rounds {
+                let s = &BLAKE2_SIGMA[round % 10];
+                g(0, 4, 8, 12, m[s[0]], m[s[1]]);
+                g(1, 5, 9, 13, m[s[2]], m[s[3]]);
+                g(2, 6, 10, 14, m[s[4]], m[s[5]]);
+                g(3, 7, 11, 15, m[s[6]], m[s[7]]);
+                g(0, 5, 10, 15, m[s[8]], m[s[9]]);
+                g(1, 6, 11, 12, m[s[10]], m[s[11]]);
+                g(2, 7, 8, 13, m[s[12]], m[s[13]]);
+                g(3, 4, 9, 14, m[s[14]], m[s[15]]);
+            }
+            for i in 0..8 {
+                h[i] ^= v[i] ^ v[i + 8];
+            }
+        }
+
+        pub fn $name<const N: usize>(data: &[u8]) -> [u8; N] {
+            const BYTES: usize = <$word>::BITS as usize / 8;
+            const BLOCK: usize = 16 * BYTES;
+            let mut h = $iv;
+            h[0] ^= 0x01010000 ^ N as $word;
+            // The final block is compressed with the `last` flag even when full, and the empty
+            // message still takes one (zero) block.
+            let full_blocks = data.len().saturating_sub(1) / BLOCK;
+            for (i, block) in data.chunks_exact(BLOCK).take(full_blocks).enumerate() {
+                $compress(&mut h, block, ((i + 1) * BLOCK) as u128, false);
+            }
+            let rest = &data[full_blocks * BLOCK..];
+            let mut last = [0u8; BLOCK];
+            last[..rest.len()].copy_from_slice(rest);
+            $compress(&mut h, &last, data.len() as u128, true);
+            let mut out = [0u8; N];
+            for (chunk, word) in out.chunks_mut(BYTES).zip(h) {
+                chunk.copy_from_slice(&word.to_le_bytes()[..chunk.len()]);
+            }
+            out
+        }
+    };
+}
+
+blake2!(blake2b, blake2b_compress, u64, SHA512_IV, 12, [32, 24, 16, 63]);
+blake2!(blake2s, blake2s_compress, u32, BLAKE2S_IV, 10, [16, 12, 8, 7]);
+
+#[cfg(test)]
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 (FIPS 180-4), standing in for the host's in tests.
+#[cfg(test)]
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut state = BLAKE2S_IV;
+    let mut message = data.to_vec();
+    message.push(0x80);
+    message.resize(message.len().div_ceil(64) * 64, 0);
+    if message.len() - data.len() < 9 {
+        message.resize(message.len() + 64, 0);
+    }
+    let len = message.len();
+    message[len - 8..].copy_from_slice(&((data.len() as u64) << 3).to_be_bytes());
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, chunk) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for (k, w) in SHA256_K.iter().zip(w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(*k).wrapping_add(w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+    let mut out = [0u8; 32];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn sha512(data: &[u8]) -> [u8; 64] {
+        let mut hasher = Sha512::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    fn check(digest: &[u8], expected: &str) {
+        assert_eq!(crate::hex::encode(digest), expected);
+    }
+
+    const FIPS_448: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const FIPS_896: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    #[test]
+    fn sha512_fips_180_4() {
+        check(&sha512(b""), "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e");
+        check(&sha512(b"abc"), "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f");
+        check(&sha512(FIPS_448), "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c33596fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445");
+        check(&sha512(FIPS_896), "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909");
+    }
+
+    #[test]
+    fn sha3_256_fips_202() {
+        check(&sha3_256(b""), "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a");
+        check(&sha3_256(b"abc"), "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532");
+        check(&sha3_256(FIPS_448), "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376");
+        check(&sha3_256(FIPS_896), "916f6061fe879741ca6469b43971dfdb28b1a32dc36cb3254e812be27aad1d18");
+    }
+
+    /// The stand-ins for the host's hashes in tests.
+    #[test]
+    fn keccak256_and_sha256() {
+        check(&keccak256(b""), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        check(&keccak256(b"abc"), "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+        check(&sha256(b""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        check(&sha256(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        check(&sha256(FIPS_448), "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+        // Either side of the length field's boundary in one and two blocks.
+        let message: Vec<u8> = (0..=255).collect();
+        for (len, digest) in [
+            (55, "463eb28e72f82e0a96c0a4cc53690c571281131f672aa229e0d45ae59b598b59"),
+            (56, "da2ae4d6b36748f2a318f23e7ab1dfdf45acdc9d049bd80e59de82a60895f562"),
+            (63, "29af2686fd53374a36b0846694cc342177e428d1647515f078784d69cdb9e488"),
+            (64, "fdeab9acf3710362bd2658cdc9a29e8f9c757fcf9811603a8c447cd1d9151108"),
+            (119, "da18797ed7c3a777f0847f429724a2d8cd5138e6ed2895c3fa1a6d39d18f7ec6"),
+            (120, "f52b23db1fbb6ded89ef42a23ce0c8922c45f25c50b568a93bf1c075420bbb7c"),
+            (256, "40aff2e9d2d8922e47afd4648e6967497158785fbd1da870e7110266bf944880"),
+        ] {
+            check(&sha256(&message[..len]), digest);
+        }
+    }
+
+    /// RFC 7693 Appendix A and B give `"abc"`; the empty message is from the reference code.
+    #[test]
+    fn blake2_rfc_7693() {
+        check(&blake2b::<64>(b"abc"), "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923");
+        check(&blake2s::<32>(b"abc"), "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982");
+        check(&blake2b::<64>(b""), "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce");
+        check(&blake2s::<32>(b""), "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9");
+    }
+
+    /// The message `0, 1, 2, ...` at lengths either side of the padding and block boundaries:
+    /// 55/56 and 111/112 for the SHA-2 length field, 64 and 128 for the BLAKE2 blocks, and
+    /// 135/136 for the SHA3-256 rate. Digests from the RustCrypto `sha2`, `sha3` and `blake2`
+    /// crates.
+    const BOUNDARIES: [(usize, &str, &str, &str, &str); 8] = [
+        (
+            55,
+            "6856647f269c2ee3d8128f0b25427659d880641ef343300dd3cd4679168f58d6527fda70b4ebc854e2065e172b7d58c1536992c0810599259ba84a2b40c65414",
+            "a91a138e3374d2d8fa4791b83a93a311a06a2926ef70153428cf6e1b239c10d4",
+            "659858f0b5c9edab5b94fd732f6e6b17c51cc096104f09beb3afc3aa467c2ecf885c4c6541effa9023d3b5738ae5a14d867e15db06fe1f9d1127b77e1aabb516",
+            "f4495470f226c8c214be08fdfad4bc4a2a9dbea9136a210df0d4b64929e6fc14",
+        ),
+        (
+            56,
+            "8b12b2f6fe400a51d29656e2b8c42a1bbfe6fcf3e425da430db05d1a2dda14790dee20fa8b22d8762afffe4988a5c98a4430d22a17e41e23d90fa61ab75671a9",
+            "d192f5964dc70118fcac64bf0eb838009b816d344f67b04e8e78d5bde783e54a",
+            "26cca0126f5d1a813c62e5c71001c046f9c92095704550be5873a495a999ad010a4f79491f24f286500adce1a137bc2084e4949f5b7294cefe51ecaff8e95cba",
+            "e290dd270b467f34ab1c002d340fa016257ff19e5833fdbbf2cb401c3b2817de",
+        ),
+        (
+            64,
+            "ee4320ebaf3fdb4f2c832b137200c08e235e0fa7bbd0eb1740c7063ba8a0d151da77e003398e1714a955d475b05e3e950b639503b452ec185de4229bc4873949",
+            "c8ad478f4e1dd9d47dfc3b985708d92db1f8db48fe9cddd459e63c321f490402",
+            "2fc6e69fa26a89a5ed269092cb9b2a449a4409a7a44011eecad13d7c4b0456602d402fa5844f1a7a758136ce3d5d8d0e8b86921ffff4f692dd95bdc8e5ff0052",
+            "56f34e8b96557e90c1f24b52d0c89d51086acf1b00f634cf1dde9233b8eaaa3e",
+        ),
+        (
+            111,
+            "a1a111449b198d9b1f538bad7f3fc1022b3a5b1a5e90a0bc860de8512746cbc31599e6c834de3a3235327af0b51ff57bf7acf1974a73014d9c3953812edc7c8d",
+            "b08646567d09c477939ea7f417fa307ec0d522a41d4f8e7aab4d9a889ec67fef",
+            "bf23c0c25c8060e4f6995f1623a3bebecaa96e308680000a8aa3cd56bb1a6da099e10d9231b37f4519b2efd2c24de72f31a5f19535241b4a59fa3c03ceb790e7",
+            "0a4049d57e833b5695fac93dd1fbef3166b44b12ad11248662383ae051e15827",
+        ),
+        (
+            112,
+            "c5fbd731d19d2ae1180f001be72c2c1aaba1d7b094b3748880e24593b8e117a750e11c1bd867cc2f96dace8c8b74abd2d5c4f236be444e77d30d1916174070b9",
+            "575f18078b5874147ecd662f4260cdb3548756081ec3d2e7bed2397f67888622",
+            "877fd652c05281009c0a5250e7a3a671f8b18c108817fe4a874de22da8e45db11958a600c5f62e67d36cbf84474cf244a9c2b03a9fb9dc711cd1a2cab6f3fae0",
+            "81dcc0678bb6a765e48c3209654fe90089ce44ff5618477e39ab286476df052b",
+        ),
+        (
+            128,
+            "1dffd5e3adb71d45d2245939665521ae001a317a03720a45732ba1900ca3b8351fc5c9b4ca513eba6f80bc7b1d1fdad4abd13491cb824d61b08d8c0e1561b3f7",
+            "bec3ebfba06834f224543cca2a427cb9329147be93e19aeb0e33a7119c7f63ef",
+            "2319e3789c47e2daa5fe807f61bec2a1a6537fa03f19ff32e87eecbfd64b7e0e8ccff439ac333b040f19b0c4ddd11a61e24ac1fe0f10a039806c5dcc0da3d115",
+            "1fa877de67259d19863a2a34bcc6962a2b25fcbf5cbecd7ede8f1fa36688a796",
+        ),
+        (
+            135,
+            "9f0ddab7986da54e65ef6b536bb4f7bff468e0f310803de28d3908492343e4caa855b8cac7409e3a8928e63b9c5d1caea7a408ed061809dbae1ab1a67ba1b926",
+            "fded8fd9d6551c601eeb3b7c6bc5e5cfd8aad1d015b7e9aaa9c9b9475231d5e2",
+            "0602185d8c3a78738b99164b8bc6ffb21c7debebbf806372e0da44d121545597b9c662a255dc31542cf995ecbe6a50fb5e6e0ee4ef240fe557eded1188087e86",
+            "70ea3b0176927d9096a18508cd123a290325920a9d00a89b5de04273fbc76b85",
+        ),
+        (
+            136,
+            "c58867d309ca48af74b4d7e49eced514c89fd433f9dd842f9b50ffaa6c7810bef35348d00d26dcbe28122ba1ce33d4cd00d09ba76f982a598b8f65790368ae59",
+            "cf3ccff92480a29160c2d38317c430e14749bfee1788106957dfe73f8c4930e5",
+            "c08afa5b927bf08097afc5fff9ca4e7800125c1f52f2af3553fa2b89e1e3015c4f87d5e0a48956ad31450b083dad147ffb5ec03434a26830cf37d103ab50c5da",
+            "67de25c02a4aaba23bdc973c8bb0b5796d47cc0659d43dff1f97de174963b68e",
+        ),
+    ];
+
+    #[test]
+    fn block_boundaries() {
+        for (len, sha512_digest, sha3_digest, blake2b_digest, blake2s_digest) in BOUNDARIES {
+            let message: Vec<u8> = (0..len as u8).collect();
+            check(&sha512(&message), sha512_digest);
+            check(&sha3_256(&message), sha3_digest);
+            check(&blake2b::<64>(&message), blake2b_digest);
+            check(&blake2s::<32>(&message), blake2s_digest);
+        }
+    }
+
+    #[test]
+    fn sha512_in_pieces() {
+        let message: Vec<u8> = (0..=255).collect();
+        for split in [0, 1, 111, 112, 128, 200, 256] {
+            let mut hasher = Sha512::new();
+            hasher.update(&message[..split]);
+            hasher.update(&message[split..]);
+            assert_eq!(hasher.finalize(), sha512(&message));
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Hexadecimal text: two lowercase digits per byte on output, either case and an optional `0x`
+//! prefix on input.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+pub fn encode(data: &[u8]) -> String {
+    data.iter().flat_map(|&b| [DIGITS[(b >> 4) as usize] as char, DIGITS[(b & 0x0F) as usize] as char]).collect()
+}
+
+/// `None` on an odd number of digits or a character that is not one.
+pub fn decode(text: &[u8]) -> Option<Vec<u8>> {
+    let digits = text.strip_prefix(b"0x").unwrap_or(text);
+    if digits.len() % 2 != 0 { return None; }
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    digits.chunks_exact(2).map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_byte() {
+        let data: Vec<u8> = (0..=255).collect();
+        let text = encode(&data);
+        assert_eq!(&text[..8], "00010203");
+        assert_eq!(&text[text.len() - 8..], "fcfdfeff");
+        assert_eq!(decode(text.as_bytes()), Some(data.clone()));
+        assert_eq!(decode(text.to_uppercase().as_bytes()), Some(data.clone()));
+        assert_eq!(decode(alloc::format!("0x{text}").as_bytes()), Some(data));
+    }
+
+    #[test]
+    fn vectors() {
+        assert_eq!(encode(&[]), "");
+        assert_eq!(encode(&[0xde, 0xad, 0xbe, 0xef]), "deadbeef");
+        assert_eq!(decode(b""), Some(Vec::new()));
+        assert_eq!(decode(b"0x"), Some(Vec::new()));
+        assert_eq!(decode(b"DeadBeef"), Some(Vec::from([0xde, 0xad, 0xbe, 0xef])));
+        assert_eq!(decode(b"0x00ff"), Some(Vec::from([0x00, 0xff])));
+    }
+
+    #[test]
+    fn rejects_what_it_would_not_produce() {
+        for text in [
+            &b"0"[..], b"0x0", b"abc", b"0g", b"zz", b" 00", b"00 ", b"0x 00",
+            // Only a lowercase prefix, and only once.
+            b"0X00", b"0x0x00",
+            // Signs and non-ASCII bytes are not digits.
+            b"+1", b"-1", b"\xff\xff", "\u{ff10}0".as_bytes(),
+        ] {
+            assert_eq!(decode(text), None, "{text:?}");
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! The runtime host functions the library calls: calldata access and the hashes the runtime
+//! provides. `uapi` only implements them when compiled for PolkaVM, so host-side tests get
+//! in-crate equivalents instead, with the calldata held in a per-thread buffer that a test sets
+//! through `set_call_data`. Tests also count what each thread allocates, since the contract's
+//! bump heap never frees.
+
+#[cfg(not(test))]
+use uapi::{HostFn, HostFnImpl as api};
+
+#[cfg(not(test))]
+pub fn call_data_size() -> u64 {
+    api::call_data_size()
+}
+
+#[cfg(not(test))]
+pub fn call_data_copy(output: &mut [u8], offset: u32) {
+    api::call_data_copy(output, offset);
+}
+
+#[cfg(not(test))]
+pub fn hash_keccak_256(input: &[u8], output: &mut [u8; 32]) {
+    api::hash_keccak_256(input, output);
+}
+
+#[cfg(not(test))]
+pub fn hash_sha2_256(input: &[u8], output: &mut [u8; 32]) {
+    api::hash_sha2_256(input, output);
+}
+
+#[cfg(not(test))]
+pub fn hash_blake2_256(input: &[u8], output: &mut [u8; 32]) {
+    api::hash_blake2_256(input, output);
+}
+
+#[cfg(test)]
+pub use self::test_host::*;
+
+#[cfg(test)]
+mod test_host {
+    extern crate std;
+
+    use alloc::vec::Vec;
+    use core::alloc::{GlobalAlloc, Layout};
+    use core::cell::{Cell, RefCell};
+    use std::alloc::System;
+
+    std::thread_local! {
+        static CALL_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+        static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// The system allocator, adding up the bytes each thread asks for as the bump heap would
+    /// hand them out: nothing is given back, and a reallocation takes a fresh block.
+    struct CountingAllocator;
+
+    fn count(bytes: usize) {
+        let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get() + bytes));
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count(layout.size());
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count(new_size);
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// The bytes this thread has allocated so far.
+    pub fn allocated_bytes() -> usize {
+        ALLOCATED.with(Cell::get)
+    }
+
+    /// Replaces this thread's calldata, selector included.
+    pub fn set_call_data(data: &[u8]) {
+        CALL_DATA.with(|call_data| *call_data.borrow_mut() = data.to_vec());
+    }
+
+    pub fn call_data_size() -> u64 {
+        CALL_DATA.with(|call_data| call_data.borrow().len() as u64)
+    }
+
+    /// Like the runtime, fills whatever lies past the end of the calldata with zeros.
+    pub fn call_data_copy(output: &mut [u8], offset: u32) {
+        CALL_DATA.with(|call_data| {
+            let call_data = call_data.borrow();
+            let start = (offset as usize).min(call_data.len());
+            let available = (call_data.len() - start).min(output.len());
+            output[..available].copy_from_slice(&call_data[start..start + available]);
+            output[available..].fill(0);
+        });
+    }
+
+    pub fn hash_keccak_256(input: &[u8], output: &mut [u8; 32]) {
+        *output = crate::hash::keccak256(input);
+    }
+
+    pub fn hash_sha2_256(input: &[u8], output: &mut [u8; 32]) {
+        *output = crate::hash::sha256(input);
+    }
+
+    pub fn hash_blake2_256(input: &[u8], output: &mut [u8; 32]) {
+        *output = crate::hash::blake2b(input);
+    }
+}
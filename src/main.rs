@@ -1,17 +1,70 @@
 
-#![no_main]
-#![no_std]
+// Host-side tests build against std with the test harness's own `main`; only the contract
+// itself is `no_std`.
+#![cfg_attr(not(test), no_main)]
+#![cfg_attr(not(test), no_std)]
+// The handlers are only reachable through `call`, which tests do not build.
+#![cfg_attr(test, allow(dead_code))]
 
 extern crate alloc;
+#[cfg(not(test))]
 use static_alloc::Bump;
 
+/// The contract's whole heap. The bump allocator never frees, so it bounds everything one
+/// call allocates.
+const HEAP_SIZE: usize = 128 * 1024;
+
+#[cfg(not(test))]
 #[global_allocator]
-static ALLOCATOR: Bump<[u8; 128 * 1024]> = Bump::uninit(); // Increased memory
+static ALLOCATOR: Bump<[u8; HEAP_SIZE]> = Bump::uninit(); // Increased memory
 
+#[cfg(not(test))]
 use uapi::{HostFn, HostFnImpl as api, ReturnFlags};
+use alloc::string::String;
 use alloc::vec::Vec;
 use alloc::vec;
 
+// The codec covers the whole ABI; each handler uses the parts it needs.
+#[allow(dead_code)]
+mod abi;
+mod host;
+mod uint;
+mod field;
+mod curve;
+mod ecdsa;
+mod hash;
+mod merlin;
+mod edwards;
+mod ristretto;
+mod tower;
+mod pairing;
+mod bn254;
+mod groth16;
+mod bls12_381;
+mod bls;
+mod msm;
+mod pedersen;
+mod schnorr;
+mod poseidon;
+mod mimc;
+mod merkle;
+mod smt;
+mod mmr;
+mod scale;
+mod base58;
+mod account;
+mod rlp;
+mod mpt;
+mod hex;
+mod base64;
+mod bech32;
+use uint::{Uint, U256, Unsigned};
+use abi::AbiEncode;
+use curve::{AffinePoint, Curve};
+use pairing::G2Affine;
+use tower::Fp2;
+
+#[cfg(not(test))]
 #[panic_handler]
 fn panic(_info: &core::panic::PanicInfo) -> ! {
     unsafe {
@@ -20,11 +73,13 @@ fn panic(_info: &core::panic::PanicInfo) -> ! {
     }
 }
 
+#[cfg(not(test))]
 #[no_mangle]
 #[polkavm_derive::polkavm_export]
 pub extern "C" fn deploy() {}
 
 /// Main entry point. Decodes a function selector and dispatches to the correct handler.
+#[cfg(not(test))]
 #[no_mangle]
 #[polkavm_derive::polkavm_export]
 pub extern "C" fn call() {
@@ -78,7 +133,150 @@ pub extern "C" fn call() {
         0x29 => api::return_value(ReturnFlags::empty(), &point_add_handler()),
         0x2A => api::return_value(ReturnFlags::empty(), &point_double_handler()),
         0x2b => api::return_value(ReturnFlags::empty(), &get_projectile_trajectory_coefficients_handler()),
-        
+
+        // --- Combinatorics ---
+        0x2C => api::return_value(ReturnFlags::empty(), &n_choose_k_u256_handler()),
+        0x2D => api::return_value(ReturnFlags::empty(), &n_choose_k_mod_handler()),
+        0x2E => api::return_value(ReturnFlags::empty(), &factorial_mod_handler()),
+
+        // --- Wide Modular Inversion ---
+        0x2F => api::return_value(ReturnFlags::empty(), &modinv_u256_handler()),
+        0x30 => api::return_value(ReturnFlags::empty(), &extended_gcd_handler()),
+        0x31 => api::return_value(ReturnFlags::empty(), &batch_modinv_handler()),
+
+        // --- Integer Roots & Logarithms ---
+        0x32 => api::return_value(ReturnFlags::empty(), &nth_root_floor_handler()),
+        0x33 => api::return_value(ReturnFlags::empty(), &nth_root_ceil_handler()),
+        0x34 => api::return_value(ReturnFlags::empty(), &is_perfect_power_handler()),
+        0x35 => api::return_value(ReturnFlags::empty(), &ilog_floor_handler()),
+        0x36 => api::return_value(ReturnFlags::empty(), &ilog_ceil_handler()),
+
+        // --- Sequences & Recurrences ---
+        0x37 => api::return_value(ReturnFlags::empty(), &fibonacci_handler()),
+        0x38 => api::return_value(ReturnFlags::empty(), &lucas_handler()),
+        0x39 => api::return_value(ReturnFlags::empty(), &fibonacci_mod_handler()),
+        0x3A => api::return_value(ReturnFlags::empty(), &lucas_mod_handler()),
+        0x3B => api::return_value(ReturnFlags::empty(), &linear_recurrence_mod_handler()),
+
+        // --- Continued Fractions & Rationals ---
+        0x3C => api::return_value(ReturnFlags::empty(), &to_continued_fraction_handler()),
+        0x3D => api::return_value(ReturnFlags::empty(), &convergents_handler()),
+        0x3E => api::return_value(ReturnFlags::empty(), &best_rational_approximation_handler()),
+        0x3F => api::return_value(ReturnFlags::empty(), &rational_add_handler()),
+        0x40 => api::return_value(ReturnFlags::empty(), &rational_mul_handler()),
+        0x41 => api::return_value(ReturnFlags::empty(), &rational_cmp_handler()),
+
+        // --- Elliptic Curve Scalar Multiplication ---
+        0x42 => api::return_value(ReturnFlags::empty(), &scalar_mul_ladder_handler()),
+        0x43 => api::return_value(ReturnFlags::empty(), &scalar_mul_wnaf_handler()),
+
+        // --- 256-bit Curves (secp256k1, P-256, BN254 G1) ---
+        0x44 => api::return_value(ReturnFlags::empty(), &ec_add_handler()),
+        0x45 => api::return_value(ReturnFlags::empty(), &ec_double_handler()),
+        0x46 => api::return_value(ReturnFlags::empty(), &ec_scalar_mul_handler()),
+
+        // --- Curve Validation & SEC1 Encoding ---
+        0x47 => api::return_value(ReturnFlags::empty(), &is_on_curve_handler()),
+        0x48 => api::return_value(ReturnFlags::empty(), &ec_is_on_curve_handler()),
+        0x49 => api::return_value(ReturnFlags::empty(), &ec_compress_handler()),
+        0x4A => api::return_value(ReturnFlags::empty(), &ec_decompress_handler()),
+
+        // --- ECDSA ---
+        0x4B => api::return_value(ReturnFlags::empty(), &ecdsa_verify_handler()),
+        0x4C => api::return_value(ReturnFlags::empty(), &ecdsa_recover_handler()),
+        0x4D => api::return_value(ReturnFlags::empty(), &ecrecover_handler()),
+
+        // --- Substrate Signatures ---
+        0x4E => api::return_value(ReturnFlags::empty(), &ed25519_verify_handler()),
+        0x4F => api::return_value(ReturnFlags::empty(), &sr25519_verify_handler()),
+
+        // --- BN254 Pairing & Groth16 ---
+        0x50 => api::return_value(ReturnFlags::empty(), &bn254_g2_add_handler()),
+        0x51 => api::return_value(ReturnFlags::empty(), &bn254_g2_scalar_mul_handler()),
+        0x52 => api::return_value(ReturnFlags::empty(), &bn254_pairing_check_handler()),
+        0x53 => api::return_value(ReturnFlags::empty(), &groth16_verify_handler()),
+
+        // --- BLS12-381 & BLS Signatures ---
+        0x54 => api::return_value(ReturnFlags::empty(), &bls_verify_handler()),
+        0x55 => api::return_value(ReturnFlags::empty(), &bls_fast_aggregate_verify_handler()),
+        0x56 => api::return_value(ReturnFlags::empty(), &bls_aggregate_verify_handler()),
+        0x57 => api::return_value(ReturnFlags::empty(), &bls12_381_g1_add_handler()),
+        0x58 => api::return_value(ReturnFlags::empty(), &bls12_381_g1_scalar_mul_handler()),
+        0x59 => api::return_value(ReturnFlags::empty(), &bls12_381_g2_add_handler()),
+        0x5A => api::return_value(ReturnFlags::empty(), &bls12_381_g2_scalar_mul_handler()),
+        0x5B => api::return_value(ReturnFlags::empty(), &bls12_381_hash_to_g2_handler()),
+
+        // --- Multi-Scalar Multiplication ---
+        0x5C => api::return_value(ReturnFlags::empty(), &msm_handler()),
+        0x5D => api::return_value(ReturnFlags::empty(), &ec_msm_handler()),
+
+        // --- Pedersen Commitments ---
+        0x5E => api::return_value(ReturnFlags::empty(), &pedersen_commit_handler()),
+        0x5F => api::return_value(ReturnFlags::empty(), &pedersen_verify_handler()),
+        0x60 => api::return_value(ReturnFlags::empty(), &pedersen_add_handler()),
+        0x61 => api::return_value(ReturnFlags::empty(), &pedersen_sub_handler()),
+        0x62 => api::return_value(ReturnFlags::empty(), &pedersen_generators_handler()),
+
+        // --- BIP-340 Schnorr ---
+        0x63 => api::return_value(ReturnFlags::empty(), &bip340_verify_handler()),
+        0x64 => api::return_value(ReturnFlags::empty(), &bip340_batch_verify_handler()),
+
+        // --- Hash Functions ---
+        0x65 => api::return_value(ReturnFlags::empty(), &keccak256_handler()),
+        0x66 => api::return_value(ReturnFlags::empty(), &sha256_handler()),
+        0x67 => api::return_value(ReturnFlags::empty(), &sha512_handler()),
+        0x68 => api::return_value(ReturnFlags::empty(), &sha3_256_handler()),
+        0x69 => api::return_value(ReturnFlags::empty(), &blake2b_256_handler()),
+        0x6A => api::return_value(ReturnFlags::empty(), &blake2b_512_handler()),
+        0x6B => api::return_value(ReturnFlags::empty(), &blake2s_256_handler()),
+
+        // --- ZK-Friendly Hashes ---
+        0x6C => api::return_value(ReturnFlags::empty(), &poseidon_handler()),
+        0x6D => api::return_value(ReturnFlags::empty(), &poseidon2_handler()),
+        0x6E => api::return_value(ReturnFlags::empty(), &mimc_sponge_handler()),
+
+        // --- Merkle Proofs ---
+        0x6F => api::return_value(ReturnFlags::empty(), &merkle_verify_handler()),
+        0x70 => api::return_value(ReturnFlags::empty(), &merkle_root_handler()),
+        0x71 => api::return_value(ReturnFlags::empty(), &merkle_multiproof_verify_handler()),
+
+        // --- Sparse Merkle Trees and Merkle Mountain Ranges ---
+        0x72 => api::return_value(ReturnFlags::empty(), &smt_verify_inclusion_handler()),
+        0x73 => api::return_value(ReturnFlags::empty(), &smt_verify_non_inclusion_handler()),
+        0x74 => api::return_value(ReturnFlags::empty(), &mmr_verify_leaf_handler()),
+
+        // --- SCALE Codec ---
+        0x75 => api::return_value(ReturnFlags::empty(), &scale_decode_compact_handler()),
+        0x76 => api::return_value(ReturnFlags::empty(), &scale_encode_compact_handler()),
+        0x77 => api::return_value(ReturnFlags::empty(), &scale_decode_bytes_handler()),
+        0x78 => api::return_value(ReturnFlags::empty(), &scale_encode_bytes_handler()),
+        0x79 => api::return_value(ReturnFlags::empty(), &scale_decode_struct_handler()),
+        0x7A => api::return_value(ReturnFlags::empty(), &scale_encode_struct_handler()),
+
+        // --- Substrate Accounts ---
+        0x7B => api::return_value(ReturnFlags::empty(), &ss58_encode_handler()),
+        0x7C => api::return_value(ReturnFlags::empty(), &ss58_decode_handler()),
+        0x7D => api::return_value(ReturnFlags::empty(), &account_id_to_address_handler()),
+        0x7E => api::return_value(ReturnFlags::empty(), &address_to_account_id_handler()),
+
+        // --- RLP and Patricia Tries ---
+        0x7F => api::return_value(ReturnFlags::empty(), &rlp_decode_list_handler()),
+        0x80 => api::return_value(ReturnFlags::empty(), &rlp_encode_list_handler()),
+        0x81 => api::return_value(ReturnFlags::empty(), &rlp_encode_bytes_handler()),
+        0x82 => api::return_value(ReturnFlags::empty(), &mpt_verify_proof_handler()),
+
+        // --- Text Encodings ---
+        0x83 => api::return_value(ReturnFlags::empty(), &hex_encode_handler()),
+        0x84 => api::return_value(ReturnFlags::empty(), &hex_decode_handler()),
+        0x85 => api::return_value(ReturnFlags::empty(), &base64_encode_handler()),
+        0x86 => api::return_value(ReturnFlags::empty(), &base64_decode_handler()),
+        0x87 => api::return_value(ReturnFlags::empty(), &base58_encode_handler()),
+        0x88 => api::return_value(ReturnFlags::empty(), &base58_decode_handler()),
+        0x89 => api::return_value(ReturnFlags::empty(), &bech32_encode_handler()),
+        0x8A => api::return_value(ReturnFlags::empty(), &bech32_decode_handler()),
+        0x8B => api::return_value(ReturnFlags::empty(), &segwit_encode_handler()),
+        0x8C => api::return_value(ReturnFlags::empty(), &segwit_decode_handler()),
+
         _ => {
             // Default case for an unknown selector
             api::return_value(ReturnFlags::empty(), &[0u8; 32]);
@@ -133,18 +331,16 @@ fn div_handler() -> [u8; 32] {
     u64_to_abi_word(result)
 }
 
+/// Returns `(bool ok, int64 c1, int64 c2)`, with `ok` false and zero coefficients for a
+/// vertical launch or a zero velocity.
 fn get_projectile_trajectory_coefficients_handler() -> Vec<u8> {
     let inputs = read_inputs(3);
     let angle_deg_times_10 = u32_from_abi_word(&inputs[0]);
     let initial_velocity = u64_from_abi_word(&inputs[1]);
     let gravity = u64_from_abi_word(&inputs[2]);
     
-    let (c1, c2) = get_projectile_trajectory_coefficients(angle_deg_times_10, initial_velocity, gravity);
-    
-    let mut output = Vec::with_capacity(64);
-    output.extend_from_slice(&i64_to_abi_word(c1));
-    output.extend_from_slice(&i64_to_abi_word(c2));
-    output
+    let result = get_projectile_trajectory_coefficients(angle_deg_times_10, initial_velocity, gravity);
+    abi::encode(&result)
 }
 
 fn lerp_handler() -> [u8; 32] {
@@ -232,11 +428,8 @@ fn clamp_vector_magnitude_handler() -> Vec<u8> {
     let vx = u64_from_abi_word(&inputs[0]);
     let vy = u64_from_abi_word(&inputs[1]);
     let max_length = u64_from_abi_word(&inputs[2]);
-    let (new_vx, new_vy) = clamp_vector_magnitude(vx, vy, max_length);
-    let mut output = vec![0u8; 64];
-    output[24..32].copy_from_slice(&new_vx.to_be_bytes());
-    output[56..64].copy_from_slice(&new_vy.to_be_bytes());
-    output
+    let result = clamp_vector_magnitude(vx, vy, max_length);
+    abi::encode(&result)
 }
 
 fn is_point_in_rect_handler() -> [u8; 32] {
@@ -268,11 +461,8 @@ fn add_vectors_handler() -> Vec<u8> {
     let vy1 = u64_from_abi_word(&inputs[1]);
     let vx2 = u64_from_abi_word(&inputs[2]);
     let vy2 = u64_from_abi_word(&inputs[3]);
-    let (new_vx, new_vy) = add_vectors(vx1, vy1, vx2, vy2);
-    let mut output = vec![0u8; 64];
-    output[24..32].copy_from_slice(&new_vx.to_be_bytes());
-    output[56..64].copy_from_slice(&new_vy.to_be_bytes());
-    output
+    let result = add_vectors(vx1, vy1, vx2, vy2);
+    abi::encode(&result)
 }
 
 fn subtract_vectors_handler() -> Vec<u8> {
@@ -281,11 +471,8 @@ fn subtract_vectors_handler() -> Vec<u8> {
     let vy1 = u64_from_abi_word(&inputs[1]);
     let vx2 = u64_from_abi_word(&inputs[2]);
     let vy2 = u64_from_abi_word(&inputs[3]);
-    let (new_vx, new_vy) = subtract_vectors(vx1, vy1, vx2, vy2);
-    let mut output = vec![0u8; 64];
-    output[24..32].copy_from_slice(&new_vx.to_be_bytes());
-    output[56..64].copy_from_slice(&new_vy.to_be_bytes());
-    output
+    let result = subtract_vectors(vx1, vy1, vx2, vy2);
+    abi::encode(&result)
 }
 
 fn scale_vector_handler() -> Vec<u8> {
@@ -293,22 +480,16 @@ fn scale_vector_handler() -> Vec<u8> {
     let vx = u64_from_abi_word(&inputs[0]);
     let vy = u64_from_abi_word(&inputs[1]);
     let scalar = u64_from_abi_word(&inputs[2]);
-    let (new_vx, new_vy) = scale_vector(vx, vy, scalar);
-    let mut output = vec![0u8; 64];
-    output[24..32].copy_from_slice(&new_vx.to_be_bytes());
-    output[56..64].copy_from_slice(&new_vy.to_be_bytes());
-    output
+    let result = scale_vector(vx, vy, scalar);
+    abi::encode(&result)
 }
 
 fn normalize_vector_handler() -> Vec<u8> {
     let inputs = read_inputs(2);
     let vx = u64_from_abi_word(&inputs[0]);
     let vy = u64_from_abi_word(&inputs[1]);
-    let (new_vx, new_vy) = normalize_vector(vx, vy);
-    let mut output = vec![0u8; 64];
-    output[24..32].copy_from_slice(&new_vx.to_be_bytes());
-    output[56..64].copy_from_slice(&new_vy.to_be_bytes());
-    output
+    let result = normalize_vector(vx, vy);
+    abi::encode(&result)
 }
 
 fn rotate_vector_handler() -> Vec<u8> {
@@ -316,11 +497,8 @@ fn rotate_vector_handler() -> Vec<u8> {
     let vx = u64_from_abi_word(&inputs[0]);
     let vy = u64_from_abi_word(&inputs[1]);
     let angle = u32_from_abi_word(&inputs[2]);
-    let (new_x, new_y) = rotate_vector(vx, vy, angle);
-    let mut output = vec![0u8; 64];
-    output[24..32].copy_from_slice(&new_x.to_be_bytes());
-    output[56..64].copy_from_slice(&new_y.to_be_bytes());
-    output
+    let result = rotate_vector(vx, vy, angle);
+    abi::encode(&result)
 }
 
 fn reflect_vector_handler() -> Vec<u8> {
@@ -329,11 +507,8 @@ fn reflect_vector_handler() -> Vec<u8> {
     let vy = u64_from_abi_word(&inputs[1]);
     let normal_x = u64_from_abi_word(&inputs[2]);
     let normal_y = u64_from_abi_word(&inputs[3]);
-    let (reflected_vx, reflected_vy) = reflect_vector(vx, vy, normal_x, normal_y);
-    let mut output = vec![0u8; 64];
-    output[24..32].copy_from_slice(&reflected_vx.to_be_bytes());
-    output[56..64].copy_from_slice(&reflected_vy.to_be_bytes());
-    output
+    let result = reflect_vector(vx, vy, normal_x, normal_y);
+    abi::encode(&result)
 }
 
 fn is_point_in_triangle_handler() -> [u8; 32] {
@@ -355,10 +530,35 @@ fn is_point_in_triangle_handler() -> [u8; 32] {
 
 fn modinv_handler() -> Vec<u8> {
     let inputs = read_inputs(2);
-    let a = i64_from_abi_word(&inputs[0]);
-    let m = i64_from_abi_word(&inputs[1]);
+    let a = u64_from_abi_word(&inputs[0]);
+    let m = u64_from_abi_word(&inputs[1]);
+    let result = modinv(a, m);
+    abi::encode(&result)
+}
+
+fn modinv_u256_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let a = u256_from_abi_word(&inputs[0]);
+    let m = u256_from_abi_word(&inputs[1]);
     let result = modinv(a, m);
-    encode_option_i64(result)
+    abi::encode(&result)
+}
+
+fn extended_gcd_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let a = u256_from_abi_word(&inputs[0]);
+    let b = u256_from_abi_word(&inputs[1]);
+    let result = extended_gcd(a, b);
+    abi::encode(&result)
+}
+
+fn batch_modinv_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.array_of(abi::Decoder::uint64), args.uint64()) {
+        (Some(values), Some(m)) => batch_modinv(&values, m),
+        _ => None,
+    };
+    abi::encode(&result)
 }
 
 fn is_prime_handler() -> [u8; 32] {
@@ -384,26 +584,148 @@ fn lcm_handler() -> [u8; 32] {
     u64_to_abi_word(result)
 }
 
+fn to_continued_fraction_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let num = u64_from_abi_word(&inputs[0]);
+    let den = u64_from_abi_word(&inputs[1]);
+    let result = to_continued_fraction(num, den);
+    abi::encode(&result)
+}
+
+fn convergents_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_u64_array(&inputs[0]).and_then(|terms| convergents(&terms));
+    let result = result.map(|fractions| {
+        let nums: Vec<u64> = fractions.iter().map(|r| r.num).collect();
+        let dens: Vec<u64> = fractions.iter().map(|r| r.den).collect();
+        (nums, dens)
+    });
+    abi::encode(&result)
+}
+
+fn best_rational_approximation_handler() -> Vec<u8> {
+    let inputs = read_inputs(3);
+    let num = u64_from_abi_word(&inputs[0]);
+    let den = u64_from_abi_word(&inputs[1]);
+    let max_denominator = u64_from_abi_word(&inputs[2]);
+    let result = Rational::new(num, den).and_then(|x| best_rational_approximation(x, max_denominator));
+    abi::encode(&result)
+}
+
+fn rational_add_handler() -> Vec<u8> {
+    let inputs = read_inputs(4);
+    let a = Rational::new(u64_from_abi_word(&inputs[0]), u64_from_abi_word(&inputs[1]));
+    let b = Rational::new(u64_from_abi_word(&inputs[2]), u64_from_abi_word(&inputs[3]));
+    let result = a.zip(b).and_then(|(a, b)| a.checked_add(b));
+    abi::encode(&result)
+}
+
+fn rational_mul_handler() -> Vec<u8> {
+    let inputs = read_inputs(4);
+    let a = Rational::new(u64_from_abi_word(&inputs[0]), u64_from_abi_word(&inputs[1]));
+    let b = Rational::new(u64_from_abi_word(&inputs[2]), u64_from_abi_word(&inputs[3]));
+    let result = a.zip(b).and_then(|(a, b)| a.checked_mul(b));
+    abi::encode(&result)
+}
+
+fn rational_cmp_handler() -> Vec<u8> {
+    let inputs = read_inputs(4);
+    let a = Rational::new(u64_from_abi_word(&inputs[0]), u64_from_abi_word(&inputs[1]));
+    let b = Rational::new(u64_from_abi_word(&inputs[2]), u64_from_abi_word(&inputs[3]));
+    let result = a.zip(b).map(|(a, b)| a.cmp(&b));
+    abi::encode(&result)
+}
+
 fn factorial_handler() -> Vec<u8> {
     let inputs = read_inputs(1);
     let n = u64_from_abi_word(&inputs[0]);
     let result = factorial(n);
-    encode_option_u64(result)
+    abi::encode(&result)
 }
 
-fn n_choose_k_handler() -> [u8; 32] {
+fn n_choose_k_handler() -> Vec<u8> {
     let inputs = read_inputs(2);
     let n = u64_from_abi_word(&inputs[0]);
     let k = u64_from_abi_word(&inputs[1]);
     let result = n_choose_k(n, k);
-    u64_to_abi_word(result)
+    abi::encode(&result)
+}
+
+fn n_choose_k_u256_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let n = u64_from_abi_word(&inputs[0]);
+    let k = u64_from_abi_word(&inputs[1]);
+    let result = n_choose_k_u256(n, k);
+    abi::encode(&result)
+}
+
+/// Arguments: `(uint64 n, uint64 k, uint64 p)`. Returns `(bool ok, uint64)`; not ok if `p` is
+/// not prime or, for `p` above 1024, the base-`p` digits need more than `MAX_MODULAR_PRODUCT_TERMS`
+/// multiplications in all.
+fn n_choose_k_mod_handler() -> Vec<u8> {
+    let inputs = read_inputs(3);
+    let n = u64_from_abi_word(&inputs[0]);
+    let k = u64_from_abi_word(&inputs[1]);
+    let p = u64_from_abi_word(&inputs[2]);
+    let result = n_choose_k_mod(n, k, p);
+    abi::encode(&result)
+}
+
+/// Arguments: `(uint64 n, uint64 m)`. Returns `(bool ok, uint64)`; not ok if `m` is zero or the
+/// product would take more than `MAX_MODULAR_PRODUCT_TERMS` terms (`n` of them, or `m - 1 - n`
+/// past `m / 2` for a prime `m`).
+fn factorial_mod_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let n = u64_from_abi_word(&inputs[0]);
+    let m = u64_from_abi_word(&inputs[1]);
+    let result = factorial_mod(n, m);
+    abi::encode(&result)
 }
 
 fn log2_floor_handler() -> Vec<u8> {
     let inputs = read_inputs(1);
     let n = u64_from_abi_word(&inputs[0]);
     let result = log2_floor(n);
-    encode_option_u32(result)
+    abi::encode(&result)
+}
+
+fn nth_root_floor_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let n = u256_from_abi_word(&inputs[0]);
+    let k = u32_from_abi_word(&inputs[1]);
+    let result = nth_root_floor(n, k);
+    abi::encode(&result)
+}
+
+fn nth_root_ceil_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let n = u256_from_abi_word(&inputs[0]);
+    let k = u32_from_abi_word(&inputs[1]);
+    let result = nth_root_ceil(n, k);
+    abi::encode(&result)
+}
+
+fn is_perfect_power_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let n = u256_from_abi_word(&inputs[0]);
+    let result = is_perfect_power(n);
+    abi::encode(&result)
+}
+
+fn ilog_floor_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let n = u256_from_abi_word(&inputs[0]);
+    let base = u256_from_abi_word(&inputs[1]);
+    let result = ilog_floor(n, base);
+    abi::encode(&result)
+}
+
+fn ilog_ceil_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let n = u256_from_abi_word(&inputs[0]);
+    let base = u256_from_abi_word(&inputs[1]);
+    let result = ilog_ceil(n, base);
+    abi::encode(&result)
 }
 
 fn log10_floor_handler() -> [u8; 32] {
@@ -413,6 +735,46 @@ fn log10_floor_handler() -> [u8; 32] {
     u32_to_abi_word(result)
 }
 
+fn fibonacci_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let n = u64_from_abi_word(&inputs[0]);
+    let result = fibonacci(n);
+    abi::encode(&result)
+}
+
+fn lucas_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let n = u64_from_abi_word(&inputs[0]);
+    let result = lucas(n);
+    abi::encode(&result)
+}
+
+fn fibonacci_mod_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let n = u64_from_abi_word(&inputs[0]);
+    let m = u64_from_abi_word(&inputs[1]);
+    let result = fibonacci_mod(n, m);
+    abi::encode(&result)
+}
+
+fn lucas_mod_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let n = u64_from_abi_word(&inputs[0]);
+    let m = u64_from_abi_word(&inputs[1]);
+    let result = lucas_mod(n, m);
+    abi::encode(&result)
+}
+
+fn linear_recurrence_mod_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let (coefficients, initial) = (args.array_of(abi::Decoder::uint64), args.array_of(abi::Decoder::uint64));
+    let result = match (coefficients, initial, args.uint64(), args.uint64()) {
+        (Some(coefficients), Some(initial), Some(n), Some(m)) => linear_recurrence_mod(&coefficients, &initial, n, m),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
 fn popcount_handler() -> [u8; 32] {
     let inputs = read_inputs(1);
     let n = u64_from_abi_word(&inputs[0]);
@@ -461,13 +823,7 @@ fn clmul_handler() -> Vec<u8> {
     let a = u64_from_abi_word(&inputs[0]);
     let b = u64_from_abi_word(&inputs[1]);
     let result = clmul(a, b);
-    
-    let mut output = Vec::with_capacity(64);
-    let high = (result >> 64) as u64;
-    let low = result as u64;
-    output.extend_from_slice(&u64_to_abi_word(high));
-    output.extend_from_slice(&u64_to_abi_word(low));
-    output
+    abi::encode(&((result >> 64) as u64, result as u64))
 }
 
 fn xorshift_next_handler() -> [u8; 32] {
@@ -479,220 +835,1090 @@ fn xorshift_next_handler() -> [u8; 32] {
 }
 
 fn point_add_handler() -> Vec<u8> {
-    let inputs = read_inputs(6);
+    let inputs = read_inputs(7);
     let p1 = point_from_abi_words(&inputs[0], &inputs[1]);
     let p2 = point_from_abi_words(&inputs[2], &inputs[3]);
     let a = u64_from_abi_word(&inputs[4]);
-    let modulus = u64_from_abi_word(&inputs[5]);
-    let result = point_add(p1, p2, a, modulus); 
-    encode_option_point(result)
+    let b = u64_from_abi_word(&inputs[5]);
+    let modulus = u64_from_abi_word(&inputs[6]);
+    let result = if is_on_curve(p1, a, b, modulus) && is_on_curve(p2, a, b, modulus) {
+        point_add(p1, p2, a, modulus)
+    } else {
+        None
+    };
+    abi::encode(&result)
 }
 
 fn point_double_handler() -> Vec<u8> {
-    let inputs = read_inputs(4);
+    let inputs = read_inputs(5);
     let p = point_from_abi_words(&inputs[0], &inputs[1]);
     let a = u64_from_abi_word(&inputs[2]);
-    let modulus = u64_from_abi_word(&inputs[3]);
-    let result = point_double(p, a, modulus);
-    encode_option_point(result)
+    let b = u64_from_abi_word(&inputs[3]);
+    let modulus = u64_from_abi_word(&inputs[4]);
+    let result = if is_on_curve(p, a, b, modulus) { point_double(p, a, modulus) } else { None };
+    abi::encode(&result)
 }
 
+fn scalar_mul_ladder_handler() -> Vec<u8> {
+    let inputs = read_inputs(6);
+    let p = point_from_abi_words(&inputs[0], &inputs[1]);
+    let k = u64_from_abi_word(&inputs[2]);
+    let a = u64_from_abi_word(&inputs[3]);
+    let b = u64_from_abi_word(&inputs[4]);
+    let modulus = u64_from_abi_word(&inputs[5]);
+    let result = if is_on_curve(p, a, b, modulus) { scalar_mul_ladder(p, k, a, modulus) } else { None };
+    abi::encode(&result)
+}
 
-// ==========================================================================================
-//                                 ABI ENCODING/DECODING HELPERS
-// ==========================================================================================
-
-fn read_inputs(num_args: usize) -> Vec<[u8; 32]> {
-    let mut inputs = Vec::with_capacity(num_args);
-    for i in 0..num_args {
-        let mut buf = [0u8; 32];
-        api::call_data_copy(&mut buf, (4 + i * 32).try_into().unwrap());
-        inputs.push(buf);
-    }
-    inputs
+fn scalar_mul_wnaf_handler() -> Vec<u8> {
+    let inputs = read_inputs(6);
+    let p = point_from_abi_words(&inputs[0], &inputs[1]);
+    let k = u64_from_abi_word(&inputs[2]);
+    let a = u64_from_abi_word(&inputs[3]);
+    let b = u64_from_abi_word(&inputs[4]);
+    let modulus = u64_from_abi_word(&inputs[5]);
+    let result = if is_on_curve(p, a, b, modulus) { scalar_mul_wnaf(p, k, a, modulus) } else { None };
+    abi::encode(&result)
 }
 
-fn u64_from_abi_word(word: &[u8; 32]) -> u64 {
-    u64::from_be_bytes(word[24..].try_into().unwrap())
+fn is_on_curve_handler() -> [u8; 32] {
+    let inputs = read_inputs(5);
+    let p = point_from_abi_words(&inputs[0], &inputs[1]);
+    let a = u64_from_abi_word(&inputs[2]);
+    let b = u64_from_abi_word(&inputs[3]);
+    let modulus = u64_from_abi_word(&inputs[4]);
+    bool_to_abi_word(is_on_curve(p, a, b, modulus))
 }
 
-fn i64_from_abi_word(word: &[u8; 32]) -> i64 {
-    i64::from_be_bytes(word[24..].try_into().unwrap())
+fn ec_add_handler() -> Vec<u8> {
+    let inputs = read_inputs(5);
+    let curve_id = u64_from_abi_word(&inputs[0]);
+    let p1 = affine_point_from_abi_words(&inputs[1], &inputs[2]);
+    let p2 = affine_point_from_abi_words(&inputs[3], &inputs[4]);
+    let result = curve::curve_by_id(curve_id).and_then(|curve| ec_add(curve, &p1, &p2));
+    abi::encode(&result)
 }
 
-fn u32_from_abi_word(word: &[u8; 32]) -> u32 {
-    u32::from_be_bytes(word[28..].try_into().unwrap())
+fn ec_double_handler() -> Vec<u8> {
+    let inputs = read_inputs(3);
+    let curve_id = u64_from_abi_word(&inputs[0]);
+    let p = affine_point_from_abi_words(&inputs[1], &inputs[2]);
+    let result = curve::curve_by_id(curve_id).and_then(|curve| ec_double(curve, &p));
+    abi::encode(&result)
 }
 
-const fn u64_to_abi_word(value: u64) -> [u8; 32] {
-    let mut output = [0u8; 32];
-    let value_bytes = value.to_be_bytes();
-    output[24] = value_bytes[0];
-    output[25] = value_bytes[1];
-    output[26] = value_bytes[2];
-    output[27] = value_bytes[3];
-    output[28] = value_bytes[4];
-    output[29] = value_bytes[5];
-    output[30] = value_bytes[6];
-    output[31] = value_bytes[7];
-    output
+fn ec_scalar_mul_handler() -> Vec<u8> {
+    let inputs = read_inputs(4);
+    let curve_id = u64_from_abi_word(&inputs[0]);
+    let p = affine_point_from_abi_words(&inputs[1], &inputs[2]);
+    let k = u256_from_abi_word(&inputs[3]);
+    let result = curve::curve_by_id(curve_id).and_then(|curve| ec_scalar_mul(curve, &p, &k));
+    abi::encode(&result)
 }
 
-fn i64_to_abi_word(value: i64) -> [u8; 32] {
-    let mut output = [0u8; 32];
-    output[24..].copy_from_slice(&value.to_be_bytes());
-    output
+fn ec_is_on_curve_handler() -> [u8; 32] {
+    let inputs = read_inputs(3);
+    let curve_id = u64_from_abi_word(&inputs[0]);
+    let p = affine_point_from_abi_words(&inputs[1], &inputs[2]);
+    let result = curve::curve_by_id(curve_id).is_some_and(|curve| curve.is_on_curve(&p));
+    bool_to_abi_word(result)
 }
 
-fn u32_to_abi_word(value: u32) -> [u8; 32] {
-    let mut output = [0u8; 32];
-    output[28..].copy_from_slice(&value.to_be_bytes());
-    output
+fn ec_compress_handler() -> Vec<u8> {
+    let inputs = read_inputs(3);
+    let curve_id = u64_from_abi_word(&inputs[0]);
+    let p = affine_point_from_abi_words(&inputs[1], &inputs[2]);
+    let result = curve::curve_by_id(curve_id).and_then(|curve| curve.encode_compressed(&p));
+    abi::encode(&result)
 }
 
-fn bool_to_abi_word(value: bool) -> [u8; 32] {
-    let mut output = [0u8; 32];
-    if value { output[31] = 1; }
-    output
+fn ec_decompress_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let curve_id = u64_from_abi_word(&inputs[0]);
+    let result = read_bytes(&inputs[1])
+        .zip(curve::curve_by_id(curve_id))
+        .and_then(|(bytes, curve)| curve.decode_sec1(&bytes));
+    abi::encode(&result)
 }
 
-fn extract_selector() -> [u8; 4] {
-    let mut selector = [0u8; 4];
-    api::call_data_copy(&mut selector, 0);
-    selector
+fn ecdsa_verify_handler() -> [u8; 32] {
+    let inputs = read_inputs(6);
+    let r = u256_from_abi_word(&inputs[1]);
+    let s = u256_from_abi_word(&inputs[2]);
+    let pubkey = affine_point_from_abi_words(&inputs[3], &inputs[4]);
+    let curve_id = u64_from_abi_word(&inputs[5]);
+    let result = curve::curve_by_id(curve_id).is_some_and(|curve| ecdsa::verify(curve, &inputs[0], &r, &s, &pubkey));
+    bool_to_abi_word(result)
 }
 
-fn encode_option_u64(opt: Option<u64>) -> Vec<u8> {
-    let mut output = Vec::with_capacity(64);
-    match opt {
-        Some(val) => {
-            output.extend_from_slice(&bool_to_abi_word(true));
-            output.extend_from_slice(&u64_to_abi_word(val));
-        },
-        None => {
-            output.extend_from_slice(&bool_to_abi_word(false));
-            output.extend_from_slice(&[0; 32]);
+fn ecdsa_recover_handler() -> Vec<u8> {
+    let inputs = read_inputs(5);
+    let recovery_id = u64_from_abi_word(&inputs[1]);
+    let r = u256_from_abi_word(&inputs[2]);
+    let s = u256_from_abi_word(&inputs[3]);
+    let curve_id = u64_from_abi_word(&inputs[4]);
+    let result = curve::curve_by_id(curve_id)
+        .zip(u8::try_from(recovery_id).ok())
+        .and_then(|(curve, recovery_id)| ecdsa::recover(curve, &inputs[0], recovery_id, &r, &s));
+    abi::encode(&result)
+}
+
+/// Same argument order as the Ethereum precompile: `(hash, v, r, s)` with `v` in {27, 28};
+/// like the precompile, a `v` word with any other high byte set fails.
+fn ecrecover_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.word(), args.uint64(), args.uint256(), args.uint256()) {
+        (Some(hash), Some(v @ (27 | 28)), Some(r), Some(s)) => {
+            ecdsa::recover(&curve::SECP256K1, &hash, (v - 27) as u8, &r, &s)
         }
-    }
-    output
+        _ => None,
+    };
+    abi::encode(&result.map(|pubkey| ethereum_address(&pubkey)))
 }
 
-fn encode_option_i64(opt: Option<i64>) -> Vec<u8> {
-    let mut output = Vec::with_capacity(64);
-    match opt {
-        Some(val) => {
-            let mut word = [0u8; 32];
-            word[24..].copy_from_slice(&val.to_be_bytes());
-            output.extend_from_slice(&bool_to_abi_word(true));
-            output.extend_from_slice(&word);
-        },
-        None => {
-            output.extend_from_slice(&bool_to_abi_word(false));
-            output.extend_from_slice(&[0; 32]);
-        }
-    }
-    output
+/// Arguments: `(bytes32 publicKey, bytes32 r, bytes32 s, bytes message)`, the signature
+/// split into its two 32-byte halves.
+fn ed25519_verify_handler() -> [u8; 32] {
+    let inputs = read_inputs(4);
+    let result = read_bytes(&inputs[3])
+        .is_some_and(|message| edwards::ed25519_verify(&inputs[0], &inputs[1], &inputs[2], &message));
+    bool_to_abi_word(result)
+}
+
+/// Same layout as `ed25519_verify_handler`; the signing context is Substrate's `"substrate"`.
+fn sr25519_verify_handler() -> [u8; 32] {
+    let inputs = read_inputs(4);
+    let result = read_bytes(&inputs[3])
+        .is_some_and(|message| ristretto::sr25519_verify(&inputs[0], &inputs[1], &inputs[2], &message));
+    bool_to_abi_word(result)
+}
+
+/// G2 points are four words in EIP-197 order: `(x_im, x_re, y_im, y_re)`.
+fn bn254_g2_add_handler() -> Vec<u8> {
+    let inputs = read_inputs(8);
+    let p1 = g2_point_from_abi_words(&inputs[..4]);
+    let p2 = g2_point_from_abi_words(&inputs[4..]);
+    abi::encode(&bn254_g2_add(&p1, &p2))
+}
+
+fn bn254_g2_scalar_mul_handler() -> Vec<u8> {
+    let inputs = read_inputs(5);
+    let p = g2_point_from_abi_words(&inputs[..4]);
+    let k = u256_from_abi_word(&inputs[4]);
+    abi::encode(&bn254_g2_scalar_mul(&p, &k))
 }
 
-fn encode_option_u32(opt: Option<u32>) -> Vec<u8> {
-    let mut output = Vec::with_capacity(64);
-    match opt {
-        Some(val) => {
-            output.extend_from_slice(&bool_to_abi_word(true));
-            output.extend_from_slice(&u32_to_abi_word(val));
-        },
-        None => {
-            output.extend_from_slice(&bool_to_abi_word(false));
-            output.extend_from_slice(&[0; 32]);
+/// Takes the EIP-197 precompile input as `bytes`: 192-byte `(G1, G2)` records. Returns
+/// `(bool valid, bool result)`.
+fn bn254_pairing_check_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_bytes(&inputs[0]).and_then(|input| bn254_pairing_check(&input));
+    abi::encode(&result)
+}
+
+/// Arguments: `(uint256[] vk, uint256[] proof, uint256[] publicInputs)`, with the key flattened
+/// as `[alpha, beta, gamma, delta, IC_0, ..., IC_n]` and the proof as `[A, B, C]`. Returns
+/// `(bool valid, bool result)`.
+fn groth16_verify_handler() -> Vec<u8> {
+    let inputs = read_inputs(3);
+    let vk = read_word_array(&inputs[0]).and_then(|words| groth16_vk_from_abi_words(&words));
+    let proof = read_word_array(&inputs[1]).and_then(|words| groth16_proof_from_abi_words(&words));
+    let public_inputs = read_word_array(&inputs[2]);
+    let result = match (vk, proof, public_inputs) {
+        (Some(vk), Some(proof), Some(words)) => {
+            let public_inputs: Vec<U256> = words.iter().map(u256_from_abi_word).collect();
+            groth16::verify(&vk, &proof, &public_inputs)
         }
-    }
-    output
+        _ => None,
+    };
+    abi::encode(&result)
 }
 
-const U64_MAX_WORD: [u8; 32] = u64_to_abi_word(u64::MAX);
+/// Arguments: `(bytes publicKey, bytes message, bytes signature)`, with the key (48 bytes)
+/// and signature (96 bytes) compressed. Malformed keys or signatures verify as `false`.
+fn bls_verify_handler() -> [u8; 32] {
+    let inputs = read_inputs(3);
+    let result = match (read_bytes(&inputs[0]), read_bytes(&inputs[1]), read_bytes(&inputs[2])) {
+        (Some(public_key), Some(message), Some(signature)) => bls::verify(&public_key, &message, &signature),
+        _ => false,
+    };
+    bool_to_abi_word(result)
+}
 
-fn point_from_abi_words(x_word: &[u8; 32], y_word: &[u8; 32]) -> Point {
-    if x_word == &U64_MAX_WORD && y_word == &U64_MAX_WORD {
-        Point::Infinity
-    } else {
-        Point::Coordinate { x: u64_from_abi_word(x_word), y: u64_from_abi_word(y_word) }
-    }
+/// Arguments: `(bytes publicKeys, bytes message, bytes signature)`, with the keys
+/// concatenated.
+fn bls_fast_aggregate_verify_handler() -> [u8; 32] {
+    let inputs = read_inputs(3);
+    let result = match (read_bytes(&inputs[0]), read_bytes(&inputs[1]), read_bytes(&inputs[2])) {
+        (Some(public_keys), Some(message), Some(signature)) => {
+            bls::fast_aggregate_verify(&public_keys, &message, &signature)
+        }
+        _ => false,
+    };
+    bool_to_abi_word(result)
 }
 
-fn encode_option_point(opt: Option<Point>) -> Vec<u8> {
-    let mut output = Vec::with_capacity(96);
-    match opt {
-        Some(Point::Coordinate{x, y}) => {
-            output.extend_from_slice(&bool_to_abi_word(true));
-            output.extend_from_slice(&u64_to_abi_word(x));
-            output.extend_from_slice(&u64_to_abi_word(y));
-        },
-        Some(Point::Infinity) => {
-            output.extend_from_slice(&bool_to_abi_word(true));
-            output.extend_from_slice(&U64_MAX_WORD);
-            output.extend_from_slice(&U64_MAX_WORD);
-        },
-        None => {
-            output.extend_from_slice(&bool_to_abi_word(false));
-            output.extend_from_slice(&[0; 32]);
-            output.extend_from_slice(&[0; 32]);
+/// Arguments: `(bytes publicKeys, bytes[] messages, bytes signature)`, one message per key.
+fn bls_aggregate_verify_handler() -> [u8; 32] {
+    let inputs = read_inputs(3);
+    let result = match (read_bytes(&inputs[0]), read_bytes_array(&inputs[1]), read_bytes(&inputs[2])) {
+        (Some(public_keys), Some(messages), Some(signature)) => {
+            bls::aggregate_verify(&public_keys, &messages, &signature)
         }
-    }
-    output
+        _ => false,
+    };
+    bool_to_abi_word(result)
 }
 
+/// Arguments: `(bytes p1, bytes p2)`, 48-byte compressed points. Returns `(bool, bytes)`.
+fn bls12_381_g1_add_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let result = match (read_bytes(&inputs[0]), read_bytes(&inputs[1])) {
+        (Some(p1), Some(p2)) => bls12_381_g1_add(&p1, &p2),
+        _ => None,
+    };
+    abi::encode(&result)
+}
 
-// ==========================================================================================
-//                                 CORE LOGIC FUNCTIONS
-// ==========================================================================================
+/// Arguments: `(bytes p, uint256 k)`.
+fn bls12_381_g1_scalar_mul_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let k = u256_from_abi_word(&inputs[1]);
+    abi::encode(&read_bytes(&inputs[0]).and_then(|p| bls12_381_g1_scalar_mul(&p, &k)))
+}
 
-pub fn modexp(base: u64, mut exp: u64, modulus: u64) -> u64 {
-    if modulus == 1 { return 0; }
-    let mut result: u128 = 1;
-    let mut base_128 = (base % modulus) as u128;
-    let modulus_128 = modulus as u128;
-    while exp > 0 {
-        if exp % 2 == 1 { result = (result * base_128) % modulus_128; }
-        exp >>= 1;
-        base_128 = (base_128 * base_128) % modulus_128;
-    }
-    result as u64
+/// Arguments: `(bytes p1, bytes p2)`, 96-byte compressed points. Returns `(bool, bytes)`.
+fn bls12_381_g2_add_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let result = match (read_bytes(&inputs[0]), read_bytes(&inputs[1])) {
+        (Some(p1), Some(p2)) => bls12_381_g2_add(&p1, &p2),
+        _ => None,
+    };
+    abi::encode(&result)
 }
 
-pub fn square(n: u64) -> u64 {
-    n.saturating_mul(n) / 100
+/// Arguments: `(bytes p, uint256 k)`.
+fn bls12_381_g2_scalar_mul_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let k = u256_from_abi_word(&inputs[1]);
+    abi::encode(&read_bytes(&inputs[0]).and_then(|p| bls12_381_g2_scalar_mul(&p, &k)))
 }
 
-pub fn square_root(n: u64) -> u64 {
-    let scaled_n = n.saturating_mul(100);
-    if scaled_n == 0 { return 0; }
-    let mut x = scaled_n;
-    let mut y = (x + 1) / 2;
-    while y < x {
-        x = y;
-        y = (x + scaled_n / x) / 2;
-    }
-    x
+/// Arguments: `(bytes message, bytes dst)`. Returns `(bool, bytes)` with the compressed G2
+/// point; invalid only if the DST is longer than 255 bytes.
+fn bls12_381_hash_to_g2_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let result = match (read_bytes(&inputs[0]), read_bytes(&inputs[1])) {
+        (Some(message), Some(dst)) => bls12_381::hash_to_g2(&message, &dst).map(|p| bls12_381::encode_g2(&p)),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(uint64[] points, uint64[] scalars, uint64 a, uint64 b, uint64 modulus)`, with
+/// the points flattened as `[x_1, y_1, ..., x_n, y_n]` and one scalar per point.
+fn msm_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let (coordinates, scalars) = (args.array_of(abi::Decoder::uint64), args.array_of(abi::Decoder::uint64));
+    let result = match (coordinates, scalars, args.uint64(), args.uint64(), args.uint64()) {
+        (Some(coordinates), Some(scalars), Some(a), Some(b), Some(modulus)) if coordinates.len() == 2 * scalars.len() => {
+            let points: Vec<Point> = coordinates.chunks_exact(2).map(|xy| point_from_coordinates(xy[0], xy[1])).collect();
+            if points.iter().all(|&p| is_on_curve(p, a, b, modulus)) { msm(&points, &scalars, a, modulus) } else { None }
+        }
+        _ => None,
+    };
+    abi::encode(&result)
 }
 
-pub fn mul(a: u64, b: u64) -> u64 {
-    a.saturating_mul(b) / 100
+/// Arguments: `(uint256 curveId, uint256[] points, uint256[] scalars)`, flattened the same way.
+fn ec_msm_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let curve = args.uint64().and_then(curve::curve_by_id);
+    let result = match (curve, args.array_of(abi::Decoder::word), args.array_of(abi::Decoder::uint256)) {
+        (Some(curve), Some(words), Some(scalars)) if words.len() == 2 * scalars.len() => {
+            let points: Vec<AffinePoint> = words.chunks_exact(2).map(|xy| affine_point_from_abi_words(&xy[0], &xy[1])).collect();
+            ec_msm(curve, &points, &scalars)
+        }
+        _ => None,
+    };
+    abi::encode(&result)
 }
 
-pub fn div(a: u64, b: u64) -> u64 {
-    if b == 0 { return 0; }
-    a.saturating_mul(100) / b
+/// Arguments: `(uint256 value, uint256 blinding)`. Returns `(bool, uint256 x, uint256 y)`.
+fn pedersen_commit_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let value = u256_from_abi_word(&inputs[0]);
+    let blinding = u256_from_abi_word(&inputs[1]);
+    abi::encode(&pedersen::commit(&value, &blinding))
 }
 
-pub fn lerp(start: u64, end: u64, t: u64) -> u64 {
-    let t_clamped = if t > 100 { 100 } else { t };
-    if start < end {
-        let delta = end - start;
-        start + delta.saturating_mul(t_clamped) / 100
-    } else {
-        let delta = start - end;
-        start - delta.saturating_mul(t_clamped) / 100
-    }
+/// Arguments: `(uint256 x, uint256 y, uint256 value, uint256 blinding)`. Returns
+/// `(bool valid, bool result)`.
+fn pedersen_verify_handler() -> Vec<u8> {
+    let inputs = read_inputs(4);
+    let commitment = affine_point_from_abi_words(&inputs[0], &inputs[1]);
+    let value = u256_from_abi_word(&inputs[2]);
+    let blinding = u256_from_abi_word(&inputs[3]);
+    abi::encode(&pedersen::verify(&commitment, &value, &blinding))
+}
+
+fn pedersen_add_handler() -> Vec<u8> {
+    let inputs = read_inputs(4);
+    let c1 = affine_point_from_abi_words(&inputs[0], &inputs[1]);
+    let c2 = affine_point_from_abi_words(&inputs[2], &inputs[3]);
+    abi::encode(&pedersen::add(&c1, &c2))
+}
+
+fn pedersen_sub_handler() -> Vec<u8> {
+    let inputs = read_inputs(4);
+    let c1 = affine_point_from_abi_words(&inputs[0], &inputs[1]);
+    let c2 = affine_point_from_abi_words(&inputs[2], &inputs[3]);
+    abi::encode(&pedersen::sub(&c1, &c2))
+}
+
+/// Returns `(uint256 gx, uint256 gy, uint256 hx, uint256 hy)`.
+fn pedersen_generators_handler() -> Vec<u8> {
+    abi::encode(&pedersen::generators())
+}
+
+/// Arguments: `(bytes32 publicKey, bytes32 r, bytes32 s, bytes message)`, with the x-only key
+/// and the signature split into its two halves as for Ed25519.
+fn bip340_verify_handler() -> [u8; 32] {
+    let inputs = read_inputs(4);
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&inputs[1]);
+    signature[32..].copy_from_slice(&inputs[2]);
+    let result = read_bytes(&inputs[3]).is_some_and(|message| schnorr::verify(&inputs[0], &message, &signature));
+    bool_to_abi_word(result)
+}
+
+/// Arguments: `(bytes32[] publicKeys, bytes[] messages, bytes signatures)`, with the 64-byte
+/// signatures concatenated in key order.
+fn bip340_batch_verify_handler() -> [u8; 32] {
+    let inputs = read_inputs(3);
+    let result = match (read_word_array(&inputs[0]), read_bytes_array(&inputs[1]), read_bytes(&inputs[2])) {
+        (Some(public_keys), Some(messages), Some(signatures)) if signatures.len() % 64 == 0 => {
+            let signatures: Vec<[u8; 64]> = signatures.chunks_exact(64).map(|chunk| chunk.try_into().unwrap()).collect();
+            schnorr::batch_verify(&public_keys, &messages, &signatures)
+        }
+        _ => false,
+    };
+    bool_to_abi_word(result)
+}
+
+// The hash handlers take `(bytes data)` and return `(bool, bytes32)`, or `(bool, bytes32, bytes32)`
+// with the high half first for 64-byte digests; `false` means the calldata was malformed.
+
+/// A 64-byte digest as the two words it is returned in.
+fn split_digest(digest: [u8; 64]) -> ([u8; 32], [u8; 32]) {
+    let (mut high, mut low) = ([0u8; 32], [0u8; 32]);
+    high.copy_from_slice(&digest[..32]);
+    low.copy_from_slice(&digest[32..]);
+    (high, low)
+}
+
+fn keccak256_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_bytes(&inputs[0]).map(|data| {
+        let mut hash = [0u8; 32];
+        host::hash_keccak_256(&data, &mut hash);
+        hash
+    });
+    abi::encode(&result)
+}
+
+fn sha256_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_bytes(&inputs[0]).map(|data| {
+        let mut hash = [0u8; 32];
+        host::hash_sha2_256(&data, &mut hash);
+        hash
+    });
+    abi::encode(&result)
+}
+
+fn sha512_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_bytes(&inputs[0]).map(|data| {
+        let mut hasher = hash::Sha512::new();
+        hasher.update(&data);
+        split_digest(hasher.finalize())
+    });
+    abi::encode(&result)
+}
+
+fn sha3_256_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    abi::encode(&read_bytes(&inputs[0]).map(|data| hash::sha3_256(&data)))
+}
+
+fn blake2b_256_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_bytes(&inputs[0]).map(|data| {
+        let mut hash = [0u8; 32];
+        host::hash_blake2_256(&data, &mut hash);
+        hash
+    });
+    abi::encode(&result)
+}
+
+fn blake2b_512_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    abi::encode(&read_bytes(&inputs[0]).map(|data| split_digest(hash::blake2b(&data))))
+}
+
+fn blake2s_256_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    abi::encode(&read_bytes(&inputs[0]).map(|data| hash::blake2s::<32>(&data)))
+}
+
+/// Arguments: `(uint256[] inputs)`, 1 to 16 elements of the BN254 scalar field. Returns
+/// `(bool, uint256)`.
+fn poseidon_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_word_array(&inputs[0])
+        .and_then(|words| poseidon::poseidon(&words.iter().map(u256_from_abi_word).collect::<Vec<_>>()));
+    abi::encode(&result)
+}
+
+/// Arguments: `(uint256[] inputs)`, at least one element of the BN254 scalar field. Returns
+/// `(bool, uint256)`.
+fn poseidon2_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_word_array(&inputs[0])
+        .and_then(|words| poseidon::poseidon2(&words.iter().map(u256_from_abi_word).collect::<Vec<_>>()));
+    abi::encode(&result)
+}
+
+/// Arguments: `(uint256[] inputs, uint256 key, uint64 numOutputs)`. Returns `(bool, uint256[])`.
+fn mimc_sponge_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.array_of(abi::Decoder::uint256), args.uint256(), args.uint64()) {
+        (Some(inputs), Some(key), Some(outputs)) => mimc::sponge(&inputs, &key, outputs),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+// The Merkle handlers take a trailing `uint8 hash`: 0 = keccak256 of the sorted pair
+// (OpenZeppelin), 1 = SHA-256, 2 = Poseidon.
+
+/// Arguments: `(bytes32 leaf, bytes32[] proof, uint256 index, bytes32 root, uint8 hash)`.
+/// Returns `(bool valid, bool result)`.
+fn merkle_verify_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let (leaf, proof, index, root) = (args.word(), args.array_of(abi::Decoder::word), args.uint64(), args.word());
+    let result = match (leaf, proof, index, root, args.uint64().and_then(merkle::Hasher::from_id)) {
+        (Some(leaf), Some(proof), Some(index), Some(root), Some(hasher)) => merkle::verify(hasher, &leaf, &proof, index, &root),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes32[] leaves, uint8 hash)`. Returns `(bool, bytes32)`.
+fn merkle_root_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.array_of(abi::Decoder::word), args.uint64().and_then(merkle::Hasher::from_id)) {
+        (Some(leaves), Some(hasher)) => merkle::root(hasher, leaves),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes32[] leaves, bytes32[] proof, bool[] proofFlags, bytes32 root, uint8 hash)`.
+/// Returns `(bool valid, bool result)`.
+fn merkle_multiproof_verify_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let (leaves, proof, flags, root) = (args.array_of(abi::Decoder::word), args.array_of(abi::Decoder::word), args.array_of(abi::Decoder::bool), args.word());
+    let result = match (leaves, proof, flags, root, args.uint64().and_then(merkle::Hasher::from_id)) {
+        (Some(leaves), Some(proof), Some(flags), Some(root), Some(hasher)) => merkle::multiproof_verify(hasher, &leaves, &proof, &flags, &root),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+// The SMT and MMR handlers take a trailing `uint8 hash`: 0 = keccak256, 1 = BLAKE2b-256.
+
+/// Arguments: `(bytes32 root, bytes32 key, bytes32 valueHash, uint256 bitmap, bytes32[] siblings,
+/// uint8 hash)`. Returns `(bool valid, bool result)`.
+fn smt_verify_inclusion_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let (root, key, value_hash, bitmap) = (args.word(), args.word(), args.word(), args.uint256());
+    let result = match (root, key, value_hash, bitmap, args.array_of(abi::Decoder::word), args.uint64().and_then(merkle::NodeHash::from_id)) {
+        (Some(root), Some(key), Some(value_hash), Some(bitmap), Some(siblings), Some(hash)) => {
+            smt::verify_inclusion(hash, &root, &key, &value_hash, &bitmap, &siblings)
+        }
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes32 root, bytes32 key, uint256 bitmap, bytes32[] siblings, uint8 hash)`.
+/// Returns `(bool valid, bool result)`.
+fn smt_verify_non_inclusion_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let (root, key, bitmap) = (args.word(), args.word(), args.uint256());
+    let result = match (root, key, bitmap, args.array_of(abi::Decoder::word), args.uint64().and_then(merkle::NodeHash::from_id)) {
+        (Some(root), Some(key), Some(bitmap), Some(siblings), Some(hash)) => smt::verify_non_inclusion(hash, &root, &key, &bitmap, &siblings),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes32 root, bytes32 leafHash, uint64 leafIndex, uint64 leafCount, bytes32[] proof,
+/// uint8 hash)`. Returns `(bool valid, bool result)`.
+fn mmr_verify_leaf_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let (root, leaf_hash, leaf_index, leaf_count) = (args.word(), args.word(), args.uint64(), args.uint64());
+    let result = match (root, leaf_hash, leaf_index, leaf_count, args.array_of(abi::Decoder::word), args.uint64().and_then(merkle::NodeHash::from_id)) {
+        (Some(root), Some(leaf_hash), Some(leaf_index), Some(leaf_count), Some(proof), Some(hash)) => {
+            mmr::verify_leaf(hash, &root, &leaf_hash, leaf_index, leaf_count, &proof)
+        }
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes data)`. Returns `(bool ok, uint256 value, uint64 length)`: the compact
+/// integer at the start of `data` and the bytes it takes.
+fn scale_decode_compact_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_bytes(&inputs[0]).and_then(|data| {
+        let mut input = &data[..];
+        let value = scale::decode_compact(&mut input)?;
+        Some((value, (data.len() - input.len()) as u64))
+    });
+    abi::encode(&result)
+}
+
+/// Arguments: `(uint256 value)`. Returns `bytes`.
+fn scale_encode_compact_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let mut output = Vec::new();
+    scale::encode_compact(&u256_from_abi_word(&inputs[0]), &mut output);
+    abi::encode(&output)
+}
+
+/// Arguments: `(bytes data)`. Returns `(bool ok, bytes value, uint64 length)`: the `Vec<u8>`
+/// at the start of `data` and the bytes it takes.
+fn scale_decode_bytes_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_bytes(&inputs[0]).and_then(|data| {
+        let mut input = &data[..];
+        let value = scale::decode_bytes(&mut input)?;
+        Some((value, (data.len() - input.len()) as u64))
+    });
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes value)`. Returns `(bool ok, bytes data)`, the SCALE `Vec<u8>` of `value`.
+fn scale_encode_bytes_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    abi::encode(&read_bytes(&inputs[0]).map(|value| {
+        let mut output = Vec::new();
+        scale::encode_bytes(&value, &mut output);
+        output
+    }))
+}
+
+/// Arguments: `(bytes descriptor, bytes data)`. Returns `(bool ok, bytes fields, uint64 length)`:
+/// the struct at the start of `data`, its fields ABI-encoded for `abi.decode`, and the bytes it
+/// takes.
+fn scale_decode_struct_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let result = read_bytes(&inputs[0]).zip(read_bytes(&inputs[1])).and_then(|(descriptor, data)| {
+        let mut input = &data[..];
+        let fields = scale::decode_struct(&descriptor, &mut input)?;
+        Some((fields, (data.len() - input.len()) as u64))
+    });
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes descriptor, bytes fields)`, the fields as `abi.encode` gives them. Returns
+/// `(bool ok, bytes data)`.
+fn scale_encode_struct_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let fields = abi::Decoder::arguments().follow(&inputs[1]).and_then(abi::Decoder::into_embedded);
+    let result = read_bytes(&inputs[0]).zip(fields).and_then(|(descriptor, fields)| scale::encode_struct(&descriptor, fields));
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes32 accountId, uint16 prefix)`. Returns `(bool ok, bytes address)`, the
+/// address as its base58 text.
+fn ss58_encode_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.word(), args.uint64().and_then(|prefix| u16::try_from(prefix).ok())) {
+        (Some(account), Some(prefix)) => account::ss58_encode(&account, prefix).map(String::into_bytes),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes address)`. Returns `(bool ok, uint16 prefix, bytes32 accountId)`.
+fn ss58_decode_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let result = read_bytes(&inputs[0]).and_then(|address| account::ss58_decode(&address));
+    abi::encode(&result.map(|(prefix, account)| (prefix as u32, account)))
+}
+
+/// Arguments: `(bytes32 accountId)`. Returns the `address` pallet-revive maps it to.
+fn account_id_to_address_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    abi::encode(&account::to_address(&inputs[0]))
+}
+
+/// Arguments: `(address addr)`. Returns `(bool, bytes32)`: the fallback account id
+/// pallet-revive gives an address with no mapped account, `false` if `addr` is not an address.
+fn address_to_account_id_handler() -> Vec<u8> {
+    let result = abi::Decoder::arguments().address().map(|address| account::to_fallback_account_id(&address));
+    abi::encode(&result)
+}
+
+// Lists cross the ABI as `(bytes[] items, bool[] isList)`: a string item as its bytes, and a
+// nested list as its whole RLP encoding, to be decoded or passed on as it is.
+
+/// Arguments: `(bytes data)`, one RLP list such as a block header or the body of a typed
+/// receipt. Returns `(bool ok, bytes[] items, bool[] isList)`.
+fn rlp_decode_list_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    let data = read_bytes(&inputs[0]);
+    let result = data.as_deref().and_then(rlp::decode_list).map(|items| {
+        items.iter().map(|item| match *item {
+            rlp::Item::String(bytes) => (bytes.to_vec(), false),
+            rlp::Item::List(encoding) => (encoding.to_vec(), true),
+        }).unzip::<_, _, Vec<Vec<u8>>, Vec<bool>>()
+    });
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes[] items, bool[] isList)`. Returns `(bool ok, bytes data)`; `ok` is false
+/// if the arrays differ in length or an item marked as a list is not exactly one RLP list.
+fn rlp_encode_list_handler() -> Vec<u8> {
+    let inputs = read_inputs(2);
+    let result = read_bytes_array(&inputs[0]).zip(read_bool_array(&inputs[1])).and_then(|(items, is_list)| {
+        if items.len() != is_list.len() { return None; }
+        let mut payload = Vec::new();
+        for (item, is_list) in items.iter().zip(is_list) {
+            if is_list {
+                let mut input = &item[..];
+                if !matches!(rlp::next_item(&mut input)?, rlp::Item::List(_)) || !input.is_empty() { return None; }
+                payload.extend_from_slice(item);
+            } else {
+                rlp::encode_string(item, &mut payload);
+            }
+        }
+        let mut output = Vec::new();
+        rlp::encode_list(&payload, &mut output);
+        Some(output)
+    });
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes data)`. Returns `(bool ok, bytes encoded)`, the RLP string holding `data`.
+fn rlp_encode_bytes_handler() -> Vec<u8> {
+    let inputs = read_inputs(1);
+    abi::encode(&read_bytes(&inputs[0]).map(|data| {
+        let mut output = Vec::new();
+        rlp::encode_string(&data, &mut output);
+        output
+    }))
+}
+
+/// Arguments: `(bytes32 root, bytes key, bytes[] proof)`. Returns `(bool valid, bool exists,
+/// bytes value)`. The key is the trie path itself: `keccak256(address)` or `keccak256(slot)` in
+/// the state and storage tries, `rlp(index)` in the receipt and transaction tries.
+fn mpt_verify_proof_handler() -> Vec<u8> {
+    let inputs = read_inputs(3);
+    let result = read_bytes(&inputs[1]).zip(read_bytes_array(&inputs[2])).and_then(|(key, proof)| mpt::verify_proof(&inputs[0], &key, &proof));
+    abi::encode(&result)
+}
+
+// Text arguments are `string`s, read as their bytes; a decoder rejects any text it would not
+// produce itself, apart from letter case where the encoding ignores it.
+
+/// Arguments: `(bytes data)`. Returns `(bool ok, string text)`, lowercase without a `0x` prefix.
+fn hex_encode_handler() -> Vec<u8> {
+    let result = abi::Decoder::arguments().bytes().map(|data| hex::encode(&data));
+    abi::encode(&result)
+}
+
+/// Arguments: `(string text)`, with or without a `0x` prefix. Returns `(bool ok, bytes data)`.
+fn hex_decode_handler() -> Vec<u8> {
+    let result = abi::Decoder::arguments().bytes().and_then(|text| hex::decode(&text));
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes data, bool urlSafe)`. Returns `(bool ok, string text)`: padded standard
+/// base64, or unpadded URL-safe base64.
+fn base64_encode_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.bytes(), args.bool()) {
+        (Some(data), Some(url_safe)) => Some(base64::encode(&data, url_safe)),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(string text, bool urlSafe)`. Returns `(bool ok, bytes data)`.
+fn base64_decode_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.bytes(), args.bool()) {
+        (Some(text), Some(url_safe)) => base64::decode(&text, url_safe),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(bytes data, bool check)`. Returns `(bool ok, string text)`, with a Base58Check
+/// checksum if `check`. A Bitcoin address is the version byte and hash, checked.
+fn base58_encode_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.bytes(), args.bool()) {
+        (Some(data), Some(check)) => Some(if check { base58::encode_check(&data) } else { base58::encode(&data) }),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(string text, bool check)`. Returns `(bool ok, bytes data)`, without the
+/// checksum if `check`.
+fn base58_decode_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.bytes(), args.bool()) {
+        (Some(text), Some(check)) => if check { base58::decode_check(&text) } else { base58::decode(&text) },
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+// Bech32 variants: 0 = bech32 (BIP-173), 1 = bech32m (BIP-350).
+
+/// Arguments: `(string hrp, bytes data, uint8 variant)`, with `data` regrouped into 5-bit
+/// digits. Returns `(bool ok, string text)`.
+fn bech32_encode_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.bytes(), args.bytes(), args.uint64().and_then(bech32::Variant::from_id)) {
+        (Some(hrp), Some(data), Some(variant)) => bech32::convert_bits(&data, 8, 5, true).and_then(|digits| bech32::encode(&hrp, &digits, variant)),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(string text)`. Returns `(bool ok, string hrp, bytes data, uint8 variant)`, with
+/// the digits regrouped into bytes; `ok` is false if they do not fill whole bytes.
+fn bech32_decode_handler() -> Vec<u8> {
+    let result = abi::Decoder::arguments().bytes().and_then(|text| bech32::decode(&text)).and_then(|(hrp, digits, variant)| {
+        Some((hrp, bech32::convert_bits(&digits, 5, 8, false)?, variant.id()))
+    });
+    abi::encode(&result)
+}
+
+/// Arguments: `(string hrp, uint8 version, bytes program)`. Returns `(bool ok, string address)`,
+/// a segwit address such as `bc1...`.
+fn segwit_encode_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let (hrp, version) = (args.bytes(), args.uint64().and_then(|version| u8::try_from(version).ok()));
+    let result = match (hrp, version, args.bytes()) {
+        (Some(hrp), Some(version), Some(program)) => bech32::segwit_encode(&hrp, version, &program),
+        _ => None,
+    };
+    abi::encode(&result)
+}
+
+/// Arguments: `(string hrp, string address)`. Returns `(bool ok, uint8 version, bytes program)`.
+fn segwit_decode_handler() -> Vec<u8> {
+    let mut args = abi::Decoder::arguments();
+    let result = match (args.bytes(), args.bytes()) {
+        (Some(hrp), Some(address)) => bech32::segwit_decode(&hrp, &address),
+        _ => None,
+    };
+    abi::encode(&result.map(|(version, program)| (version as u32, program)))
+}
+
+
+// ==========================================================================================
+//                                 ABI ENCODING/DECODING HELPERS
+// ==========================================================================================
+
+fn read_inputs(num_args: usize) -> Vec<[u8; 32]> {
+    let mut inputs = Vec::with_capacity(num_args);
+    for i in 0..num_args {
+        let mut buf = [0u8; 32];
+        host::call_data_copy(&mut buf, (4 + i * 32).try_into().unwrap());
+        inputs.push(buf);
+    }
+    inputs
+}
+
+/// Reads a dynamic array of static 32-byte elements given its head word (the offset of its
+/// tail). Returns `None` if the array does not lie within the calldata.
+fn read_word_array(head: &[u8; 32]) -> Option<Vec<[u8; 32]>> {
+    abi::Decoder::arguments().follow(head)?.into_array_of(abi::Decoder::word)
+}
+
+/// Reads a dynamic `uint64[]` argument given its head word; `None` if an element does not fit
+/// 64 bits.
+fn read_u64_array(head: &[u8; 32]) -> Option<Vec<u64>> {
+    abi::Decoder::arguments().follow(head)?.into_array_of(abi::Decoder::uint64)
+}
+
+/// Reads a dynamic `bool[]`; `None` if an element is not 0 or 1.
+fn read_bool_array(head: &[u8; 32]) -> Option<Vec<bool>> {
+    abi::Decoder::arguments().follow(head)?.into_array_of(abi::Decoder::bool)
+}
+
+/// Reads a dynamic `bytes` argument whose head word holds the tail offset.
+fn read_bytes(head: &[u8; 32]) -> Option<Vec<u8>> {
+    abi::Decoder::arguments().follow(head)?.into_bytes()
+}
+
+/// Reads a dynamic `bytes[]` argument: an array of offsets, relative to the first of them,
+/// each pointing at a `bytes` tail.
+fn read_bytes_array(head: &[u8; 32]) -> Option<Vec<Vec<u8>>> {
+    abi::Decoder::arguments().follow(head)?.into_array_of(abi::Decoder::bytes)
+}
+
+fn u64_from_abi_word(word: &[u8; 32]) -> u64 {
+    u64::from_be_bytes(word[24..].try_into().unwrap())
+}
+
+fn u32_from_abi_word(word: &[u8; 32]) -> u32 {
+    u32::from_be_bytes(word[28..].try_into().unwrap())
+}
+
+const fn u64_to_abi_word(value: u64) -> [u8; 32] {
+    let mut output = [0u8; 32];
+    let value_bytes = value.to_be_bytes();
+    output[24] = value_bytes[0];
+    output[25] = value_bytes[1];
+    output[26] = value_bytes[2];
+    output[27] = value_bytes[3];
+    output[28] = value_bytes[4];
+    output[29] = value_bytes[5];
+    output[30] = value_bytes[6];
+    output[31] = value_bytes[7];
+    output
+}
+
+/// Sign-extended, as `int64` requires.
+fn i64_to_abi_word(value: i64) -> [u8; 32] {
+    let mut output = if value < 0 { [0xFF; 32] } else { [0; 32] };
+    output[24..].copy_from_slice(&value.to_be_bytes());
+    output
+}
+
+fn u32_to_abi_word(value: u32) -> [u8; 32] {
+    let mut output = [0u8; 32];
+    output[28..].copy_from_slice(&value.to_be_bytes());
+    output
+}
+
+fn bool_to_abi_word(value: bool) -> [u8; 32] {
+    let mut output = [0u8; 32];
+    if value { output[31] = 1; }
+    output
+}
+
+fn u256_from_abi_word(word: &[u8; 32]) -> U256 {
+    U256::from_be_bytes(word)
+}
+
+
+/// `int8`: -1, 0 or 1.
+impl AbiEncode for core::cmp::Ordering {
+    fn encode(&self, encoder: &mut abi::Encoder) {
+        encoder.int64(*self as i64);
+    }
+
+    fn encode_absent(encoder: &mut abi::Encoder) {
+        i64::encode_absent(encoder);
+    }
+}
+
+fn extract_selector() -> [u8; 4] {
+    let mut selector = [0u8; 4];
+    host::call_data_copy(&mut selector, 0);
+    selector
+}
+
+const U64_MAX_WORD: [u8; 32] = u64_to_abi_word(u64::MAX);
+
+fn point_from_abi_words(x_word: &[u8; 32], y_word: &[u8; 32]) -> Point {
+    if x_word == &U64_MAX_WORD && y_word == &U64_MAX_WORD {
+        Point::Infinity
+    } else {
+        Point::Coordinate { x: u64_from_abi_word(x_word), y: u64_from_abi_word(y_word) }
+    }
+}
+
+fn point_from_coordinates(x: u64, y: u64) -> Point {
+    if x == u64::MAX && y == u64::MAX { Point::Infinity } else { Point::Coordinate { x, y } }
+}
+
+/// `(uint64 x, uint64 y)`, with infinity as `(2^64 - 1, 2^64 - 1)`: coordinates are reduced
+/// below a 64-bit modulus, so they never take that value.
+impl AbiEncode for Point {
+    fn encode(&self, encoder: &mut abi::Encoder) {
+        let (x, y) = match *self {
+            Point::Coordinate { x, y } => (x, y),
+            Point::Infinity => (u64::MAX, u64::MAX),
+        };
+        encoder.uint64(x).uint64(y);
+    }
+
+    fn encode_absent(encoder: &mut abi::Encoder) {
+        <(u64, u64)>::encode_absent(encoder);
+    }
+}
+
+/// Full-width points use `(0, 0)` for infinity, as the EIP-196 precompiles do; it is never on
+/// a supported curve since all of them have `b != 0`.
+fn affine_point_from_abi_words(x_word: &[u8; 32], y_word: &[u8; 32]) -> AffinePoint {
+    if x_word == &[0; 32] && y_word == &[0; 32] {
+        AffinePoint::Infinity
+    } else {
+        AffinePoint::Coordinate { x: u256_from_abi_word(x_word), y: u256_from_abi_word(y_word) }
+    }
+}
+
+/// `(uint256 x, uint256 y)`, with infinity as `(0, 0)`.
+impl AbiEncode for AffinePoint {
+    fn encode(&self, encoder: &mut abi::Encoder) {
+        match self {
+            AffinePoint::Coordinate { x, y } => (*x, *y).encode(encoder),
+            AffinePoint::Infinity => Self::encode_absent(encoder),
+        }
+    }
+
+    fn encode_absent(encoder: &mut abi::Encoder) {
+        <(U256, U256)>::encode_absent(encoder);
+    }
+}
+
+/// Reads a G2 point from four words `(x_im, x_re, y_im, y_re)`; all zeros is infinity.
+fn g2_point_from_abi_words(words: &[[u8; 32]]) -> G2Affine<4> {
+    if words[..4].iter().all(|word| word == &[0; 32]) {
+        return G2Affine::Infinity;
+    }
+    let fp2 = |im: &[u8; 32], re: &[u8; 32]| Fp2 { c0: u256_from_abi_word(re), c1: u256_from_abi_word(im) };
+    G2Affine::Coordinate { x: fp2(&words[0], &words[1]), y: fp2(&words[2], &words[3]) }
+}
+
+/// `(x_im, x_re, y_im, y_re)`, with infinity as all zeros.
+impl AbiEncode for G2Affine<4> {
+    fn encode(&self, encoder: &mut abi::Encoder) {
+        match self {
+            G2Affine::Coordinate { x, y } => (x.c1, x.c0, y.c1, y.c0).encode(encoder),
+            G2Affine::Infinity => Self::encode_absent(encoder),
+        }
+    }
+
+    fn encode_absent(encoder: &mut abi::Encoder) {
+        <(U256, U256, U256, U256)>::encode_absent(encoder);
+    }
+}
+
+/// `(uint64 num, uint64 den)`.
+impl AbiEncode for Rational {
+    fn encode(&self, encoder: &mut abi::Encoder) {
+        (self.num, self.den).encode(encoder);
+    }
+
+    fn encode_absent(encoder: &mut abi::Encoder) {
+        <(u64, u64)>::encode_absent(encoder);
+    }
+}
+
+/// Splits `[alpha(2), beta(4), gamma(4), delta(4), IC_0(2), ..., IC_n(2)]` into a key.
+fn groth16_vk_from_abi_words(words: &[[u8; 32]]) -> Option<groth16::VerifyingKey> {
+    if words.len() < 16 || words.len() % 2 != 0 { return None; }
+    let ic = words[14..].chunks_exact(2).map(|point| affine_point_from_abi_words(&point[0], &point[1])).collect();
+    Some(groth16::VerifyingKey {
+        alpha: affine_point_from_abi_words(&words[0], &words[1]),
+        beta: g2_point_from_abi_words(&words[2..6]),
+        gamma: g2_point_from_abi_words(&words[6..10]),
+        delta: g2_point_from_abi_words(&words[10..14]),
+        ic,
+    })
+}
+
+/// Splits `[A(2), B(4), C(2)]` into a proof.
+fn groth16_proof_from_abi_words(words: &[[u8; 32]]) -> Option<groth16::Proof> {
+    if words.len() != 8 { return None; }
+    Some(groth16::Proof {
+        a: affine_point_from_abi_words(&words[0], &words[1]),
+        b: g2_point_from_abi_words(&words[2..6]),
+        c: affine_point_from_abi_words(&words[6], &words[7]),
+    })
+}
+
+
+// ==========================================================================================
+//                                 CORE LOGIC FUNCTIONS
+// ==========================================================================================
+
+/// `(a * b) % modulus` without overflow, via a 128-bit intermediate.
+pub fn mulmod(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+
+/// `(a + b) % modulus` for operands already reduced below `modulus`.
+pub fn addmod(a: u64, b: u64, modulus: u64) -> u64 {
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= modulus { sum.wrapping_sub(modulus) } else { sum }
+}
+
+/// `(a - b) % modulus` for operands already reduced below `modulus`.
+pub fn submod(a: u64, b: u64, modulus: u64) -> u64 {
+    if a >= b { a - b } else { a + (modulus - b) }
+}
+
+pub fn modexp(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    if modulus == 1 { return 0; }
+    let mut result = 1;
+    let mut base = base % modulus;
+    while exp > 0 {
+        if exp % 2 == 1 { result = mulmod(result, base, modulus); }
+        exp >>= 1;
+        base = mulmod(base, base, modulus);
+    }
+    result
+}
+
+pub fn square(n: u64) -> u64 {
+    n.saturating_mul(n) / 100
+}
+
+pub fn square_root(n: u64) -> u64 {
+    let scaled_n = n.saturating_mul(100);
+    if scaled_n == 0 { return 0; }
+    let mut x = scaled_n;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + scaled_n / x) / 2;
+    }
+    x
+}
+
+pub fn mul(a: u64, b: u64) -> u64 {
+    a.saturating_mul(b) / 100
+}
+
+pub fn div(a: u64, b: u64) -> u64 {
+    if b == 0 { return 0; }
+    a.saturating_mul(100) / b
+}
+
+pub fn lerp(start: u64, end: u64, t: u64) -> u64 {
+    let t_clamped = if t > 100 { 100 } else { t };
+    if start < end {
+        let delta = end - start;
+        start + delta.saturating_mul(t_clamped) / 100
+    } else {
+        let delta = start - end;
+        start - delta.saturating_mul(t_clamped) / 100
+    }
 }
 
 const SINE_LUT_QUADRANT: [i64; 91] = [
@@ -723,8 +1949,8 @@ pub fn cos(angle_deg_times_10: u32) -> i64 {
 }
 
 pub fn squared_distance(x1: u64, y1: u64, x2: u64, y2: u64) -> u64 {
-    let dx = if x1 > x2 { x1 - x2 } else { x2 - x1 };
-    let dy = if y1 > y2 { y1 - y2 } else { y2 - y1 };
+    let dx = x1.abs_diff(x2);
+    let dy = y1.abs_diff(y2);
     let sq_dx = (dx.saturating_mul(dx)) / 100;
     let sq_dy = (dy.saturating_mul(dy)) / 100;
     sq_dx.saturating_add(sq_dy)
@@ -773,8 +1999,8 @@ pub fn is_point_in_rect(px: u64, py: u64, rect_x: u64, rect_y: u64, rect_width:
 }
 
 pub fn is_point_in_circle(px: u64, py: u64, circle_cx: u64, circle_cy: u64, circle_radius: u64) -> bool {
-    let dx = if px > circle_cx { px - circle_cx } else { circle_cx - px };
-    let dy = if py > circle_cy { py - circle_cy } else { circle_cy - py };
+    let dx = px.abs_diff(circle_cx);
+    let dy = py.abs_diff(circle_cy);
     
     let dx_sq = dx.saturating_mul(dx);
     let dy_sq = dy.saturating_mul(dy);
@@ -819,6 +2045,7 @@ pub fn reflect_vector(vx: u64, vy: u64, normal_x: u64, normal_y: u64) -> (i64, i
     (reflected_vx, reflected_vy)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn is_point_in_triangle(
     px: u64, py: u64,
     ax: u64, ay: u64,
@@ -858,17 +2085,104 @@ pub fn is_point_in_triangle(
     all_non_negative || all_non_positive
 }
 
-fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
-    if a == 0 { (b, 0, 1) } else {
-        let (g, x, y) = extended_gcd(b % a, a);
-        (g, y - (b / a) * x, x)
+/// Stein's binary GCD, which needs only shifts and subtractions at any width.
+pub fn binary_gcd<T: Unsigned>(mut a: T, mut b: T) -> T {
+    if a.is_zero() { return b; }
+    if b.is_zero() { return a; }
+    let shift = a.trailing_zeros().min(b.trailing_zeros());
+    a = a.shr(a.trailing_zeros());
+    loop {
+        b = b.shr(b.trailing_zeros());
+        if a > b { core::mem::swap(&mut a, &mut b); }
+        b = b.wrapping_sub(&a);
+        if b.is_zero() { return a.shl(shift); }
     }
 }
 
-pub fn modinv(a: i64, m: i64) -> Option<i64> {
-    if m == 0 { return None; }
-    let (g, x, _) = extended_gcd(a, m);
-    if g != 1 { None } else { Some((x % m + m) % m) }
+/// Binary extended GCD: returns `(g, x)` with `g = gcd(a, b)` and `a * x ≡ g (mod b)`, where
+/// `x < b / g`. The matching Bezout coefficient is `y = (a * x - g) / b`.
+pub fn extended_gcd<T: Unsigned>(a: T, b: T) -> (T, T) {
+    if b.is_zero() { return (a, T::ONE); }
+    if a.is_zero() { return (b, T::ZERO); }
+    let g = binary_gcd(a, b);
+    let (a_reduced, _) = a.div_mod(&g);
+    let (b_reduced, _) = b.div_mod(&g);
+    // a / g and b / g are coprime, so the inverse always exists.
+    (g, modinv(a_reduced, b_reduced).unwrap_or(T::ZERO))
+}
+
+/// Modular inverse for any unsigned modulus up to the full width of `T`.
+pub fn modinv<T: Unsigned>(a: T, m: T) -> Option<T> {
+    if m.is_zero() { return None; }
+    let (_, a) = a.div_mod(&m);
+    if m.is_odd() { return modinv_odd(a, m); }
+    if !a.is_odd() { return None; }
+    // m = 2^s * q with q odd: invert modulo q and modulo 2^s separately, then recombine (CRT).
+    let s = m.trailing_zeros();
+    let q = m.shr(s);
+    let (_, a_mod_q) = a.div_mod(&q);
+    let x_q = modinv_odd(a_mod_q, q)?;
+    let x_2 = modinv_pow2(a);
+    let mask_shift = T::BITS - s;
+    let t = x_2.wrapping_sub(&x_q).wrapping_mul(&modinv_pow2(q)).shl(mask_shift).shr(mask_shift);
+    // x_q < q and t < 2^s, so x_q + q * t < m and nothing wraps.
+    Some(x_q.wrapping_add(&q.wrapping_mul(&t)))
+}
+
+/// Binary inversion modulo an odd `m`, for `a < m`. Keeps `u ≡ a * x1` and `v ≡ a * x2 (mod m)`
+/// while running Stein's algorithm on `(u, v)`; all coefficients stay reduced below `m`.
+fn modinv_odd<T: Unsigned>(a: T, m: T) -> Option<T> {
+    if m == T::ONE { return Some(T::ZERO); }
+    let (mut u, mut v) = (a, m);
+    let (mut x1, mut x2) = (T::ONE, T::ZERO);
+    let half_m_ceil = m.shr(1).wrapping_add(&T::ONE);
+    while !u.is_zero() {
+        while !u.is_odd() {
+            u = u.shr(1);
+            // x1 / 2 mod m; for odd x1 this is (x1 + m) / 2, computed without overflow.
+            x1 = if x1.is_odd() { x1.shr(1).wrapping_add(&half_m_ceil) } else { x1.shr(1) };
+        }
+        if u < v {
+            core::mem::swap(&mut u, &mut v);
+            core::mem::swap(&mut x1, &mut x2);
+        }
+        u = u.wrapping_sub(&v);
+        x1 = if x1 >= x2 { x1.wrapping_sub(&x2) } else { x1.wrapping_add(&m.wrapping_sub(&x2)) };
+    }
+    if v == T::ONE { Some(x2) } else { None }
+}
+
+/// Inverse of an odd `a` modulo `2^BITS` by Newton iteration; each step doubles the correct bits.
+fn modinv_pow2<T: Unsigned>(a: T) -> T {
+    let two = T::ONE.wrapping_add(&T::ONE);
+    // Every odd a satisfies a * a ≡ 1 (mod 8), so a starts with three correct bits.
+    let mut x = a;
+    let mut correct_bits = 3;
+    while correct_bits < T::BITS {
+        x = x.wrapping_mul(&two.wrapping_sub(&a.wrapping_mul(&x)));
+        correct_bits *= 2;
+    }
+    x
+}
+
+/// Inverts every value modulo `m` with a single modular inversion (Montgomery's trick).
+/// Returns `None` if any value shares a factor with `m`.
+pub fn batch_modinv<T: Unsigned>(values: &[T], m: T) -> Option<Vec<T>> {
+    if m.is_zero() { return None; }
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = T::ONE;
+    for value in values {
+        prefix.push(acc);
+        acc = acc.mul_mod(value, &m);
+    }
+    let mut inv = modinv(acc, m)?;
+    let mut result = vec![T::ZERO; values.len()];
+    for i in (0..values.len()).rev() {
+        // inv is the inverse of values[0] * ... * values[i].
+        result[i] = inv.mul_mod(&prefix[i], &m);
+        inv = inv.mul_mod(&values[i], &m);
+    }
+    Some(result)
 }
 
 pub fn is_prime(n: u64) -> bool {
@@ -879,64 +2193,426 @@ pub fn is_prime(n: u64) -> bool {
     let mut d = n - 1;
     while d % 2 == 0 { d /= 2; }
 
-    for &a in &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
-        if n == a { return true; }
-        if modexp(a, d, n) != 1 {
-            let mut t = modexp(a, d, n);
-            let mut dt = d;
-            let mut is_composite = true;
-            while dt < n - 1 {
-                if t == n - 1 { is_composite = false; break; }
-                t = (t as u128 * t as u128 % n as u128) as u64;
-                dt = dt.saturating_mul(2);
-            }
-            if is_composite { return false; }
-        }
+    for &a in &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if n == a { return true; }
+        if modexp(a, d, n) != 1 {
+            let mut t = modexp(a, d, n);
+            let mut dt = d;
+            let mut is_composite = true;
+            while dt < n - 1 {
+                if t == n - 1 { is_composite = false; break; }
+                t = mulmod(t, t, n);
+                dt = dt.saturating_mul(2);
+            }
+            if is_composite { return false; }
+        }
+    }
+    true
+}
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let temp = b;
+        b = a % b;
+        a = temp;
+    }
+    a
+}
+
+pub fn lcm(a: u64, b: u64) -> u64 {
+    if a == 0 || b == 0 { return 0; }
+    (a / gcd(a, b)).saturating_mul(b)
+}
+
+/// A non-negative fraction kept in lowest terms with a non-zero denominator.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rational { pub num: u64, pub den: u64 }
+
+impl Rational {
+    /// Builds `num / den` in lowest terms, `None` if `den` is zero.
+    pub fn new(num: u64, den: u64) -> Option<Self> {
+        if den == 0 { return None; }
+        let g = gcd(num, den);
+        Some(Rational { num: num / g, den: den / g })
+    }
+
+    /// Sum in lowest terms, `None` if the reduced numerator or denominator overflows.
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        // Cancel gcd(b, d) up front so the intermediates stay as small as possible.
+        let g = gcd(self.den, other.den);
+        let (b, d) = (self.den / g, other.den / g);
+        let num = (self.num as u128 * d as u128).checked_add(other.num as u128 * b as u128)?;
+        let g2 = gcd((num % g as u128) as u64, g);
+        let num = u64::try_from(num / g2 as u128).ok()?;
+        let den = b.checked_mul(other.den / g2)?;
+        Some(Rational { num, den })
+    }
+
+    /// Product in lowest terms, `None` if the reduced numerator or denominator overflows.
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        if self.num == 0 || other.num == 0 { return Some(Rational { num: 0, den: 1 }); }
+        let g1 = gcd(self.num, other.den);
+        let g2 = gcd(other.num, self.den);
+        let num = (self.num / g1).checked_mul(other.num / g2)?;
+        let den = (self.den / g2).checked_mul(other.den / g1)?;
+        Some(Rational { num, den })
+    }
+
+    /// `|self - other|` as an unreduced `(numerator, denominator)` pair.
+    fn abs_diff_unreduced(self, other: Rational) -> (u128, u128) {
+        let lhs = self.num as u128 * other.den as u128;
+        let rhs = other.num as u128 * self.den as u128;
+        (lhs.abs_diff(rhs), self.den as u128 * other.den as u128)
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.num as u128 * other.den as u128).cmp(&(other.num as u128 * self.den as u128))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> { Some(self.cmp(other)) }
+}
+
+/// Continued fraction terms `[a0; a1, a2, ...]` of `num / den`, `None` if `den` is zero.
+pub fn to_continued_fraction(mut num: u64, mut den: u64) -> Option<Vec<u64>> {
+    if den == 0 { return None; }
+    let mut terms = Vec::new();
+    while den != 0 {
+        terms.push(num / den);
+        (num, den) = (den, num % den);
+    }
+    Some(terms)
+}
+
+/// Successive convergents `h_i / k_i` of a continued fraction, `None` if one overflows.
+pub fn convergents(terms: &[u64]) -> Option<Vec<Rational>> {
+    let mut result = Vec::with_capacity(terms.len());
+    let (mut h_prev, mut h) = (0u64, 1u64);
+    let (mut k_prev, mut k) = (1u64, 0u64);
+    for &a in terms {
+        let h_next = a.checked_mul(h)?.checked_add(h_prev)?;
+        let k_next = a.checked_mul(k)?.checked_add(k_prev)?;
+        (h_prev, h) = (h, h_next);
+        (k_prev, k) = (k, k_next);
+        // Only a zero partial quotient after a0 can produce k = 0; such input is malformed.
+        if k == 0 { return None; }
+        // Convergents of a continued fraction are always in lowest terms.
+        result.push(Rational { num: h, den: k });
+    }
+    Some(result)
+}
+
+/// Closest fraction to `x` with denominator at most `max_denominator`, taken from the
+/// convergents and semiconvergents of its continued fraction; ties go to the convergent.
+/// A fixed-point value `v` with scale `s` is passed as `x = v / s`.
+pub fn best_rational_approximation(x: Rational, max_denominator: u64) -> Option<Rational> {
+    if max_denominator == 0 { return None; }
+    if x.den <= max_denominator { return Some(x); }
+    let (mut p0, mut q0, mut p1, mut q1) = (0u64, 1u64, 1u64, 0u64);
+    let (mut n, mut d) = (x.num, x.den);
+    // Convergent denominators grow towards x.den > max_denominator, so this always stops
+    // before d reaches zero, and p, q never exceed x.num, x.den.
+    loop {
+        let a = n / d;
+        let q2 = q0 + a * q1;
+        if q2 > max_denominator { break; }
+        (p0, q0, p1, q1) = (p1, q1, p0 + a * p1, q2);
+        (n, d) = (d, n - a * d);
+    }
+    let k = (max_denominator - q0) / q1;
+    let semiconvergent = Rational::new(p0 + k * p1, q0 + k * q1)?;
+    let convergent = Rational::new(p1, q1)?;
+    let (sn, sd) = semiconvergent.abs_diff_unreduced(x);
+    let (cn, cd) = convergent.abs_diff_unreduced(x);
+    // Compare sn / sd with cn / cd by cross-multiplying at 256 bits.
+    let semi_error = U256::from_u128(sn).widening_mul(&U256::from_u128(cd)).0;
+    let conv_error = U256::from_u128(cn).widening_mul(&U256::from_u128(sd)).0;
+    if conv_error <= semi_error { Some(convergent) } else { Some(semiconvergent) }
+}
+
+pub fn factorial(n: u64) -> Option<u64> {
+    if n > 20 { return None; }
+    let mut result = 1u64;
+    for i in 2..=n { result = result.saturating_mul(i); }
+    Some(result)
+}
+
+/// Exact binomial coefficient, `None` if it does not fit in a `u64`.
+pub fn n_choose_k(n: u64, k: u64) -> Option<u64> {
+    n_choose_k_u256(n, k)?.to_u64()
+}
+
+/// Exact binomial coefficient, `None` if it does not fit in 256 bits.
+pub fn n_choose_k_u256(n: u64, mut k: u64) -> Option<U256> {
+    if k > n { return Some(U256::ZERO); }
+    if k > n / 2 { k = n - k; }
+    let mut res = U256::ONE;
+    for i in 0..k {
+        // C(n, i) * (n - i) is divisible by (i + 1). Cancelling their common factor first keeps
+        // every intermediate equal to C(n, i + 1), so overflow here means the result overflows.
+        let d = i + 1;
+        let (_, rem) = res.div_rem_u64(d)?;
+        let g = gcd(rem, d);
+        let (reduced, _) = res.div_rem_u64(g)?;
+        res = reduced.checked_mul_u64((n - i) / (d / g))?;
+    }
+    Some(res)
+}
+
+/// Primes up to this size get a factorial lookup table in `n_choose_k_mod`.
+const FACTORIAL_TABLE_LIMIT: u64 = 1024;
+
+/// Most multiplications `factorial_mod` and `n_choose_k_mod` will spend on running products, so
+/// that a call fails instead of running out of gas on a 64-bit input.
+const MAX_MODULAR_PRODUCT_TERMS: u64 = 1 << 16;
+
+/// `C(n, k) mod p` for prime `p` via Lucas' theorem, `None` if `p` is not prime or the digits
+/// past the factorial table need more than `MAX_MODULAR_PRODUCT_TERMS` multiplications.
+pub fn n_choose_k_mod(mut n: u64, mut k: u64, p: u64) -> Option<u64> {
+    if !is_prime(p) { return None; }
+    if k > n { return Some(0); }
+    let table = if p <= FACTORIAL_TABLE_LIMIT { Some(factorial_table_mod(p)) } else { None };
+    let mut budget = MAX_MODULAR_PRODUCT_TERMS;
+    let mut result = 1;
+    while k > 0 {
+        let (n_digit, k_digit) = (n % p, k % p);
+        if k_digit > n_digit { return Some(0); }
+        let digit = match &table {
+            Some(fact) => {
+                let denom = mulmod(fact[k_digit as usize], fact[(n_digit - k_digit) as usize], p);
+                mulmod(fact[n_digit as usize], modexp(denom, p - 2, p), p)
+            }
+            None => {
+                budget = budget.checked_sub(k_digit.min(n_digit - k_digit))?;
+                small_n_choose_k_mod(n_digit, k_digit, p)
+            }
+        };
+        result = mulmod(result, digit, p);
+        n /= p;
+        k /= p;
+    }
+    Some(result)
+}
+
+/// `[0!, 1!, ..., (p-1)!] mod p`.
+fn factorial_table_mod(p: u64) -> Vec<u64> {
+    let mut fact = Vec::with_capacity(p as usize);
+    fact.push(1);
+    for i in 1..p {
+        let prev = fact[(i - 1) as usize];
+        fact.push(mulmod(prev, i, p));
+    }
+    fact
+}
+
+/// `C(n, k) mod p` for prime `p` and `k <= n < p`, using a single modular inverse.
+fn small_n_choose_k_mod(n: u64, mut k: u64, p: u64) -> u64 {
+    if k > n - k { k = n - k; }
+    let mut num = 1;
+    let mut den = 1;
+    for i in 0..k {
+        num = mulmod(num, n - i, p);
+        den = mulmod(den, i + 1, p);
+    }
+    mulmod(num, modexp(den, p - 2, p), p)
+}
+
+/// `n! mod m`, `None` if `m` is zero or the product takes more than `MAX_MODULAR_PRODUCT_TERMS`
+/// terms.
+pub fn factorial_mod(n: u64, m: u64) -> Option<u64> {
+    if m == 0 { return None; }
+    // m itself is one of the factors once n >= m.
+    if n >= m { return Some(0); }
+    let wilson = n > m / 2 && is_prime(m);
+    if (if wilson { m - 1 - n } else { n }) > MAX_MODULAR_PRODUCT_TERMS { return None; }
+    if wilson {
+        // Wilson's theorem: (m-1)! = -1 mod m, so n! = -1 / ((n+1) * ... * (m-1)).
+        let mut tail = 1;
+        for i in n + 1..m { tail = mulmod(tail, i, m); }
+        return Some(mulmod(m - 1, modexp(tail, m - 2, m), m));
+    }
+    let mut result = 1 % m;
+    for i in 2..=n { result = mulmod(result, i, m); }
+    Some(result)
+}
+
+pub fn log2_floor(n: u64) -> Option<u32> {
+    if n == 0 { None } else { Some(63 - n.leading_zeros()) }
+}
+
+pub fn log10_floor(mut n: u64) -> u32 {
+    if n == 0 { return 0; }
+    let mut count = 0;
+    while n >= 10 { n /= 10; count += 1; }
+    count
+}
+
+/// `base^exp`, or `None` on overflow.
+pub fn checked_pow<T: Unsigned>(base: T, mut exp: u32) -> Option<T> {
+    let mut result = T::ONE;
+    let mut base = base;
+    while exp > 0 {
+        if exp & 1 == 1 { result = result.checked_mul(&base)?; }
+        exp >>= 1;
+        if exp > 0 { base = base.checked_mul(&base)?; }
+    }
+    Some(result)
+}
+
+/// Largest `r` with `r^k <= n`, `None` if `k` is zero.
+pub fn nth_root_floor<T: Unsigned>(n: T, k: u32) -> Option<T> {
+    if k == 0 { return None; }
+    if k == 1 || n.is_zero() { return Some(n); }
+    let bits = n.bits();
+    if k >= bits { return Some(T::ONE); }
+    // The root has at most ceil(bits / k) bits; fix them from the top down.
+    let mut root = T::ZERO;
+    for i in (0..bits.div_ceil(k)).rev() {
+        let candidate = root.wrapping_add(&T::ONE.shl(i));
+        if checked_pow(candidate, k).is_some_and(|power| power <= n) { root = candidate; }
+    }
+    Some(root)
+}
+
+/// Smallest `r` with `r^k >= n`, `None` if `k` is zero.
+pub fn nth_root_ceil<T: Unsigned>(n: T, k: u32) -> Option<T> {
+    let root = nth_root_floor(n, k)?;
+    if checked_pow(root, k) == Some(n) { Some(root) } else { Some(root.wrapping_add(&T::ONE)) }
+}
+
+/// Writes `n` as `base^exponent` with `base >= 2` and the largest possible `exponent >= 2`.
+pub fn is_perfect_power<T: Unsigned>(n: T) -> Option<(T, u32)> {
+    let bits = n.bits();
+    // base >= 2 means exponent <= log2(n).
+    for k in (2..bits).rev() {
+        let root = nth_root_floor(n, k)?;
+        if root > T::ONE && checked_pow(root, k) == Some(n) { return Some((root, k)); }
     }
-    true
+    None
 }
 
-pub fn gcd(mut a: u64, mut b: u64) -> u64 {
-    while b != 0 {
-        let temp = b;
-        b = a % b;
-        a = temp;
+/// `floor(log_base(n))`, `None` if `n` is zero or `base < 2`.
+pub fn ilog_floor<T: Unsigned>(n: T, base: T) -> Option<u32> {
+    if n.is_zero() || base <= T::ONE { return None; }
+    let mut power = base;
+    let mut count = 0;
+    while power <= n {
+        count += 1;
+        power = match power.checked_mul(&base) { Some(next) => next, None => break };
     }
-    a
+    Some(count)
 }
 
-pub fn lcm(a: u64, b: u64) -> u64 {
-    if a == 0 || b == 0 { return 0; }
-    (a / gcd(a, b)).saturating_mul(b)
+/// `ceil(log_base(n))`, `None` if `n` is zero or `base < 2`.
+pub fn ilog_ceil<T: Unsigned>(n: T, base: T) -> Option<u32> {
+    let floor = ilog_floor(n, base)?;
+    if checked_pow(base, floor) == Some(n) { Some(floor) } else { Some(floor + 1) }
 }
 
-pub fn factorial(n: u64) -> Option<u64> {
-    if n > 20 { return None; }
-    let mut result = 1u64;
-    for i in 2..=n { result = result.saturating_mul(i); }
-    Some(result)
+/// `(F(n), F(n+1))` by fast doubling, `None` if either overflows 256 bits.
+fn fibonacci_pair(n: u64) -> Option<(U256, U256)> {
+    let (mut a, mut b) = (U256::ZERO, U256::ONE);
+    for i in (0..64 - n.leading_zeros()).rev() {
+        // F(2k) = F(k) * (2F(k+1) - F(k)), F(2k+1) = F(k)^2 + F(k+1)^2
+        let two_b = b.checked_add(&b)?;
+        let c = a.checked_mul(&two_b.checked_sub(&a)?)?;
+        let d = a.checked_mul(&a)?.checked_add(&b.checked_mul(&b)?)?;
+        (a, b) = if (n >> i) & 1 == 1 { (d, c.checked_add(&d)?) } else { (c, d) };
+    }
+    Some((a, b))
 }
 
-pub fn n_choose_k(n: u64, mut k: u64) -> u64 {
-    if k > n { return 0; }
-    if k == 0 || k == n { return 1; }
-    if k > n / 2 { k = n - k; }
-    let mut res: u64 = 1;
-    for i in 0..k {
-        res = res.saturating_mul(n - i) / (i + 1);
+/// Exact n-th Fibonacci number, `None` if it does not fit in 256 bits.
+pub fn fibonacci(n: u64) -> Option<U256> {
+    if n == 0 { return Some(U256::ZERO); }
+    // Ask for (F(n-1), F(n)) so the pair never holds a value larger than the result.
+    fibonacci_pair(n - 1).map(|(_, f_n)| f_n)
+}
+
+/// Exact n-th Lucas number, `None` if it does not fit in 256 bits.
+pub fn lucas(n: u64) -> Option<U256> {
+    if n == 0 { return Some(U256::from_u64(2)); }
+    // L(n) = F(n) + 2F(n-1); both terms are at most L(n), so overflow here means L(n) overflows.
+    let (f_prev, f_n) = fibonacci_pair(n - 1)?;
+    f_n.checked_add(&f_prev)?.checked_add(&f_prev)
+}
+
+/// `(F(n) mod m, F(n+1) mod m)` by fast doubling, for a non-zero `m`.
+fn fibonacci_pair_mod(n: u64, m: u64) -> (u64, u64) {
+    let (mut a, mut b) = (0, 1 % m);
+    for i in (0..64 - n.leading_zeros()).rev() {
+        let c = mulmod(a, submod(addmod(b, b, m), a, m), m);
+        let d = addmod(mulmod(a, a, m), mulmod(b, b, m), m);
+        (a, b) = if (n >> i) & 1 == 1 { (d, addmod(c, d, m)) } else { (c, d) };
     }
-    res
+    (a, b)
 }
 
-pub fn log2_floor(n: u64) -> Option<u32> {
-    if n == 0 { None } else { Some(63 - n.leading_zeros()) }
+/// `F(n) mod m`, `None` if `m` is zero.
+pub fn fibonacci_mod(n: u64, m: u64) -> Option<u64> {
+    if m == 0 { return None; }
+    Some(fibonacci_pair_mod(n, m).0)
 }
 
-pub fn log10_floor(mut n: u64) -> u32 {
-    if n == 0 { return 0; }
-    let mut count = 0;
-    while n >= 10 { n /= 10; count += 1; }
-    count
+/// `L(n) mod m`, `None` if `m` is zero.
+pub fn lucas_mod(n: u64, m: u64) -> Option<u64> {
+    if m == 0 { return None; }
+    // L(n) = 2F(n+1) - F(n)
+    let (f_n, f_next) = fibonacci_pair_mod(n, m);
+    Some(submod(addmod(f_next, f_next, m), f_n, m))
+}
+
+/// Largest recurrence order accepted by `linear_recurrence_mod`, which keeps three
+/// order x order matrices live on the bump heap.
+const MAX_RECURRENCE_ORDER: usize = 32;
+
+/// n-th term mod `m` of `a(k) = coefficients[0] * a(k-1) + ... + coefficients[d-1] * a(k-d)`,
+/// given `initial = [a(0), ..., a(d-1)]`, by exponentiating the companion matrix.
+pub fn linear_recurrence_mod(coefficients: &[u64], initial: &[u64], n: u64, m: u64) -> Option<u64> {
+    let d = coefficients.len();
+    if m == 0 || d == 0 || d > MAX_RECURRENCE_ORDER || initial.len() != d { return None; }
+    if n < d as u64 { return Some(initial[n as usize] % m); }
+    // The companion matrix maps [a(k+d-1), ..., a(k)] to [a(k+d), ..., a(k+1)].
+    let mut base = vec![0u64; d * d];
+    for (j, &c) in coefficients.iter().enumerate() { base[j] = c % m; }
+    for i in 1..d { base[i * d + i - 1] = 1 % m; }
+    let mut power = vec![0u64; d * d];
+    for i in 0..d { power[i * d + i] = 1 % m; }
+    let mut scratch = vec![0u64; d * d];
+    let mut exp = n - (d as u64 - 1);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            mat_mul_mod(&power, &base, &mut scratch, d, m);
+            core::mem::swap(&mut power, &mut scratch);
+        }
+        exp >>= 1;
+        if exp > 0 {
+            mat_mul_mod(&base, &base, &mut scratch, d, m);
+            core::mem::swap(&mut base, &mut scratch);
+        }
+    }
+    // a(n) is the first entry of power * [a(d-1), ..., a(0)].
+    let mut term = 0;
+    for j in 0..d {
+        term = addmod(term, mulmod(power[j], initial[d - 1 - j] % m, m), m);
+    }
+    Some(term)
+}
+
+/// `out = a * b mod m` for row-major `d x d` matrices.
+fn mat_mul_mod(a: &[u64], b: &[u64], out: &mut [u64], d: usize, m: u64) {
+    for i in 0..d {
+        for j in 0..d {
+            let mut acc = 0;
+            for k in 0..d {
+                acc = addmod(acc, mulmod(a[i * d + k], b[k * d + j], m), m);
+            }
+            out[i * d + j] = acc;
+        }
+    }
 }
 
 pub fn popcount(n: u64) -> u32 { n.count_ones() }
@@ -979,21 +2655,34 @@ pub enum Point {
     Coordinate { x: u64, y: u64 },
 }
 
+/// Whether `p` satisfies `y^2 = x^3 + ax + b (mod modulus)` with reduced coordinates.
+/// Infinity is on every curve.
+pub fn is_on_curve(p: Point, a: u64, b: u64, modulus: u64) -> bool {
+    if modulus == 0 { return false; }
+    match p {
+        Point::Infinity => true,
+        Point::Coordinate { x, y } => {
+            if x >= modulus || y >= modulus { return false; }
+            let x3 = mulmod(mulmod(x, x, modulus), x, modulus);
+            let rhs = addmod(addmod(x3, mulmod(a % modulus, x, modulus), modulus), b % modulus, modulus);
+            mulmod(y, y, modulus) == rhs
+        }
+    }
+}
+
 pub fn point_double(p: Point, a: u64, modulus: u64) -> Option<Point> {
     match p {
         Point::Infinity => Some(Point::Infinity),
         Point::Coordinate { x, y } => {
-            if y == 0 || modulus == 0 { return Some(Point::Infinity); }
-            let two_y_inv = match modinv(2 * y as i64, modulus as i64) {
-                Some(val) => val as u64,
-                None => return None,
-            };
-            let three_x_sq = modexp(x, 2, modulus).saturating_mul(3) % modulus;
-            let lambda = ((three_x_sq.saturating_add(a)) % modulus).saturating_mul(two_y_inv) % modulus;
-            let lambda_sq = modexp(lambda, 2, modulus);
-            let two_x = (2 * x) % modulus;
-            let x_r = (lambda_sq + modulus - two_x) % modulus;
-            let y_r = (lambda.saturating_mul((x + modulus - x_r) % modulus) % modulus + modulus - y) % modulus;
+            if modulus == 0 { return Some(Point::Infinity); }
+            let (x, y, a) = (x % modulus, y % modulus, a % modulus);
+            if y == 0 { return Some(Point::Infinity); }
+            let two_y_inv = modinv(addmod(y, y, modulus), modulus)?;
+            let three_x_sq = mulmod(3, mulmod(x, x, modulus), modulus);
+            let lambda = mulmod(addmod(three_x_sq, a, modulus), two_y_inv, modulus);
+            let lambda_sq = mulmod(lambda, lambda, modulus);
+            let x_r = submod(lambda_sq, addmod(x, x, modulus), modulus);
+            let y_r = submod(mulmod(lambda, submod(x, x_r, modulus), modulus), y, modulus);
             Some(Point::Coordinate { x: x_r, y: y_r })
         }
     }
@@ -1005,52 +2694,352 @@ pub fn point_add(p1: Point, p2: Point, a: u64, modulus: u64) -> Option<Point> {
         (Point::Infinity, _) => Some(p2),
         (_, Point::Infinity) => Some(p1),
         (Point::Coordinate { x: x1, y: y1 }, Point::Coordinate { x: x2, y: y2 }) => {
+            let (x1, y1, x2, y2) = (x1 % modulus, y1 % modulus, x2 % modulus, y2 % modulus);
             if x1 == x2 {
                 if y1 == y2 { return point_double(p1, a, modulus); }
                 else { return Some(Point::Infinity); }
             }
-            let x_diff = (x2 + modulus - x1) % modulus;
-            let x_diff_inv = match modinv(x_diff as i64, modulus as i64) {
-                Some(val) => val as u64,
-                None => return None,
-            };
-            let y_diff = (y2 + modulus - y1) % modulus;
-            let lambda = y_diff.saturating_mul(x_diff_inv) % modulus;
-            let lambda_sq = modexp(lambda, 2, modulus);
-            let x_sum = (x1 + x2) % modulus;
-            let x_r = (lambda_sq + modulus - x_sum) % modulus;
-            let y_r = (lambda.saturating_mul((x1 + modulus - x_r) % modulus) % modulus + modulus - y1) % modulus;
+            let x_diff = submod(x2, x1, modulus);
+            let x_diff_inv = modinv(x_diff, modulus)?;
+            let y_diff = submod(y2, y1, modulus);
+            let lambda = mulmod(y_diff, x_diff_inv, modulus);
+            let lambda_sq = mulmod(lambda, lambda, modulus);
+            let x_r = submod(submod(lambda_sq, x1, modulus), x2, modulus);
+            let y_r = submod(mulmod(lambda, submod(x1, x_r, modulus), modulus), y1, modulus);
             Some(Point::Coordinate { x: x_r, y: y_r })
         }
     }
 }
+
+/// `-P`: the reflection of `P` in the x-axis.
+pub fn point_neg(p: Point, modulus: u64) -> Point {
+    match p {
+        Point::Infinity => Point::Infinity,
+        Point::Coordinate { x, y } => Point::Coordinate { x, y: submod(0, y % modulus, modulus) },
+    }
+}
+
+/// All ones if `bit` is 1, all zeros if it is 0. `black_box` keeps the optimizer from turning
+/// the masked selections that use it back into branches.
+fn ct_mask(bit: u64) -> u64 {
+    core::hint::black_box(0u64.wrapping_sub(bit & 1))
+}
+
+/// `a` where `mask` is all ones, `b` where it is all zeros.
+fn ct_select(mask: u64, a: u64, b: u64) -> u64 {
+    b ^ (mask & (a ^ b))
+}
+
+/// Arithmetic mod a 64-bit modulus with no branches or divisions on the operands: sums are
+/// corrected by masked subtractions and products reduced by Barrett's method, whose only
+/// division, for `mu = floor((2^128 - 1) / m)`, depends on the public modulus alone.
+struct CtField {
+    m: u64,
+    mu: u128,
+}
+
+impl CtField {
+    fn new(m: u64) -> Self {
+        CtField { m, mu: u128::MAX / m as u128 }
+    }
+
+    /// `x - m` if that does not borrow, else `x`.
+    fn reduce_once(&self, x: u128) -> u128 {
+        let (difference, borrow) = x.overflowing_sub(self.m as u128);
+        let mask = core::hint::black_box(0u128.wrapping_sub(borrow as u128));
+        difference ^ (mask & (difference ^ x))
+    }
+
+    fn add(&self, a: u64, b: u64) -> u64 {
+        self.reduce_once(a as u128 + b as u128) as u64
+    }
+
+    fn sub(&self, a: u64, b: u64) -> u64 {
+        let (difference, borrow) = a.overflowing_sub(b);
+        difference.wrapping_add(ct_mask(borrow as u64) & self.m)
+    }
+
+    fn mul(&self, a: u64, b: u64) -> u64 {
+        let x = a as u128 * b as u128;
+        // q = floor(x * mu / 2^128) from four 64-bit products; it falls short of x / m by at
+        // most 2, so r = x - q * m < 3m.
+        let (x1, x0) = ((x >> 64) as u64 as u128, x as u64 as u128);
+        let (u1, u0) = ((self.mu >> 64) as u64 as u128, self.mu as u64 as u128);
+        let (low, cross1, cross2) = (x0 * u0, x1 * u0, x0 * u1);
+        let middle = (low >> 64) + (cross1 as u64 as u128) + (cross2 as u64 as u128);
+        let q = (x1 * u1).wrapping_add(cross1 >> 64).wrapping_add(cross2 >> 64).wrapping_add(middle >> 64);
+        let r = x.wrapping_sub(q.wrapping_mul(self.m as u128));
+        self.reduce_once(self.reduce_once(r)) as u64
+    }
+
+    /// `a^(m - 2)`, the inverse of `a` when `m` is prime. The exponent is public, so its bits
+    /// may steer the loop.
+    fn invert(&self, a: u64) -> u64 {
+        let (mut result, mut base, mut exp) = (1 % self.m, a, self.m.wrapping_sub(2));
+        while exp > 0 {
+            if exp & 1 == 1 { result = self.mul(result, base); }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        result
+    }
+}
+
+/// A point in homogeneous projective coordinates `(X : Y : Z)`, standing for `(X/Z, Y/Z)`, with
+/// infinity as `(0 : 1 : 0)`.
+#[derive(Clone, Copy)]
+struct ProjectivePoint {
+    x: u64,
+    y: u64,
+    z: u64,
+}
+
+impl ProjectivePoint {
+    /// Swaps `p` and `q` if `mask` is all ones.
+    fn ct_swap(mask: u64, p: &mut Self, q: &mut Self) {
+        for (a, b) in [(&mut p.x, &mut q.x), (&mut p.y, &mut q.y), (&mut p.z, &mut q.z)] {
+            let t = mask & (*a ^ *b);
+            *a ^= t;
+            *b ^= t;
+        }
+    }
+}
+
+/// `p + q` by the complete addition formulas of Renes, Costello and Batina (2016, algorithm 1)
+/// for `y^2 = x^3 + ax + b`, with `b3 = 3b`. One sequence of field operations serves for
+/// doubling and for infinity too; the only exceptional inputs are pairs differing by a point
+/// of order 2.
+fn complete_add(f: &CtField, p: &ProjectivePoint, q: &ProjectivePoint, a: u64, b3: u64) -> ProjectivePoint {
+    let (x1, y1, z1, x2, y2, z2) = (p.x, p.y, p.z, q.x, q.y, q.z);
+    let mut t0 = f.mul(x1, x2);
+    let mut t1 = f.mul(y1, y2);
+    let mut t2 = f.mul(z1, z2);
+    let mut t3 = f.mul(f.add(x1, y1), f.add(x2, y2));
+    let mut t4 = f.add(t0, t1);
+    t3 = f.sub(t3, t4);
+    t4 = f.mul(f.add(x1, z1), f.add(x2, z2));
+    let mut t5 = f.add(t0, t2);
+    t4 = f.sub(t4, t5);
+    t5 = f.mul(f.add(y1, z1), f.add(y2, z2));
+    let mut x3 = f.add(t1, t2);
+    t5 = f.sub(t5, x3);
+    let mut z3 = f.mul(a, t4);
+    x3 = f.mul(b3, t2);
+    z3 = f.add(x3, z3);
+    x3 = f.sub(t1, z3);
+    z3 = f.add(t1, z3);
+    let mut y3 = f.mul(x3, z3);
+    t1 = f.add(f.add(t0, t0), t0);
+    t2 = f.mul(a, t2);
+    t4 = f.mul(b3, t4);
+    t1 = f.add(t1, t2);
+    t2 = f.mul(a, f.sub(t0, t2));
+    t4 = f.add(t4, t2);
+    t0 = f.mul(t1, t4);
+    y3 = f.add(y3, t0);
+    t0 = f.mul(t5, t4);
+    x3 = f.sub(f.mul(t3, x3), t0);
+    t0 = f.mul(t3, t1);
+    z3 = f.add(f.mul(t5, z3), t0);
+    ProjectivePoint { x: x3, y: y3, z: z3 }
+}
+
+/// `k * P` by a Montgomery ladder in constant time with respect to `k`: all 64 bits are
+/// processed, each by one conditional swap, one complete addition and one complete doubling,
+/// on branch-free field arithmetic, and the result is made affine by a Fermat inversion. `P`,
+/// the curve and the modulus are treated as public.
+///
+/// The modulus must be prime; `None` otherwise. `b` is recovered from `P`, which must be on
+/// the curve. A `P` of order 2, the one exceptional case of the formulas, is handled by a
+/// masked selection on the lowest bit of `k`.
+pub fn scalar_mul_ladder(p: Point, k: u64, a: u64, modulus: u64) -> Option<Point> {
+    if !is_prime(modulus) { return None; }
+    let Point::Coordinate { x, y } = p else { return Some(Point::Infinity) };
+    let f = CtField::new(modulus);
+    let (x, y, a) = (x % modulus, y % modulus, a % modulus);
+    // b = y^2 - x^3 - ax
+    let b = f.sub(f.sub(f.mul(y, y), f.mul(f.mul(x, x), x)), f.mul(a, x));
+    let b3 = f.add(f.add(b, b), b);
+
+    let (mut r0, mut r1) = (ProjectivePoint { x: 0, y: 1, z: 0 }, ProjectivePoint { x, y, z: 1 });
+    let mut swapped = 0;
+    for i in (0..64).rev() {
+        // Invariant: r1 = r0 + P. Swapping on each change of bit doubles the right one.
+        let bit = k >> i & 1;
+        ProjectivePoint::ct_swap(ct_mask(swapped ^ bit), &mut r0, &mut r1);
+        swapped = bit;
+        r1 = complete_add(&f, &r0, &r1, a, b3);
+        r0 = complete_add(&f, &r0, &r0, a, b3);
+    }
+    ProjectivePoint::ct_swap(ct_mask(swapped), &mut r0, &mut r1);
+
+    // With 2P = O the additions above degenerate; kP is P for odd k and O for even k.
+    let order_two = ct_mask((y == 0) as u64);
+    let odd = ct_mask(k & 1);
+    r0.x = ct_select(order_two, ct_select(odd, x, 0), r0.x);
+    r0.y = ct_select(order_two, ct_select(odd, y, 1), r0.y);
+    r0.z = ct_select(order_two, ct_select(odd, 1, 0), r0.z);
+
+    if r0.z == 0 { return Some(Point::Infinity); }
+    let z_inv = f.invert(r0.z);
+    Some(Point::Coordinate { x: f.mul(r0.x, z_inv), y: f.mul(r0.y, z_inv) })
+}
+
+/// `k_1 * P_1 + ... + k_n * P_n` on the small curve, by the same bucket method as `ec_msm`.
+/// `None` if an intermediate addition hits a non-invertible value mod a composite modulus.
+pub fn msm(points: &[Point], scalars: &[u64], a: u64, modulus: u64) -> Option<Point> {
+    if modulus == 0 { return None; }
+    let scalars: Vec<Uint<1>> = scalars.iter().map(|&k| Uint::from_u64(k)).collect();
+    let add = |p: &Point, q: &Point| point_add(*p, *q, a, modulus);
+    let double = |p: &Point| point_double(*p, a, modulus);
+    msm::pippenger(points, &scalars, Point::Infinity, add, double)
+}
+
+/// Window width for `scalar_mul_wnaf`; 2^(w-2) odd multiples of P are precomputed.
+const WNAF_WIDTH: u32 = 4;
+
+/// `k * P` using the width-w non-adjacent form of `k`, which has at most one non-zero digit
+/// in any w consecutive positions. Faster than the ladder, but its timing depends on `k`.
+pub fn scalar_mul_wnaf(p: Point, k: u64, a: u64, modulus: u64) -> Option<Point> {
+    if modulus == 0 { return None; }
+    // Digits least significant first; each non-zero digit is odd with |d| < 2^(w-1).
+    let mut digits = [0i8; 65];
+    let mut len = 0;
+    let mut rest = k as u128;
+    while rest > 0 {
+        if rest & 1 == 1 {
+            let window = (rest & ((1 << WNAF_WIDTH) - 1)) as i8;
+            let digit = if window >= 1 << (WNAF_WIDTH - 1) { window - (1 << WNAF_WIDTH) } else { window };
+            rest = rest.wrapping_sub(digit as i128 as u128);
+            digits[len] = digit;
+        }
+        len += 1;
+        rest >>= 1;
+    }
+    // table[i] = (2i + 1) * P
+    let mut table = [Point::Infinity; 1 << (WNAF_WIDTH - 2)];
+    table[0] = p;
+    let two_p = point_double(p, a, modulus)?;
+    for i in 1..table.len() {
+        table[i] = point_add(table[i - 1], two_p, a, modulus)?;
+    }
+    let mut q = Point::Infinity;
+    for &digit in digits[..len].iter().rev() {
+        q = point_double(q, a, modulus)?;
+        if digit > 0 {
+            q = point_add(q, table[(digit / 2) as usize], a, modulus)?;
+        } else if digit < 0 {
+            q = point_add(q, point_neg(table[(-digit / 2) as usize], modulus), a, modulus)?;
+        }
+    }
+    Some(q)
+}
+
+/// `p1 + p2` on a 256-bit curve; `None` if either input is not a point on the curve.
+pub fn ec_add(curve: &Curve, p1: &AffinePoint, p2: &AffinePoint) -> Option<AffinePoint> {
+    let sum = curve.add(&curve.to_jacobian(p1)?, &curve.to_jacobian(p2)?);
+    Some(curve.to_affine(&sum))
+}
+
+pub fn ec_double(curve: &Curve, p: &AffinePoint) -> Option<AffinePoint> {
+    Some(curve.to_affine(&curve.double(&curve.to_jacobian(p)?)))
+}
+
+pub fn ec_scalar_mul(curve: &Curve, p: &AffinePoint, k: &U256) -> Option<AffinePoint> {
+    Some(curve.to_affine(&curve.scalar_mul(&curve.to_jacobian(p)?, k)))
+}
+
+/// `k_1 * P_1 + ... + k_n * P_n` on a 256-bit curve; `None` if a point is not on the curve.
+pub fn ec_msm(curve: &Curve, points: &[AffinePoint], scalars: &[U256]) -> Option<AffinePoint> {
+    let points = points.iter().map(|p| curve.to_jacobian(p)).collect::<Option<Vec<_>>>()?;
+    Some(curve.to_affine(&curve.msm(&points, scalars)))
+}
+
+/// The last 20 bytes of `keccak256(x || y)`.
+pub fn ethereum_address(pubkey: &AffinePoint) -> [u8; 20] {
+    let mut preimage = [0u8; 64];
+    if let AffinePoint::Coordinate { x, y } = pubkey {
+        x.write_be_bytes(&mut preimage[..32]);
+        y.write_be_bytes(&mut preimage[32..]);
+    }
+    let mut hash = [0u8; 32];
+    host::hash_keccak_256(&preimage, &mut hash);
+    hash[12..].try_into().unwrap()
+}
+
+/// Adds two points on the BN254 G2 twist; like `ec_add`, inputs need only be on the curve.
+pub fn bn254_g2_add(p1: &G2Affine<4>, p2: &G2Affine<4>) -> Option<G2Affine<4>> {
+    let g2 = &bn254::G2;
+    Some(g2.to_affine(&g2.add(&g2.to_jacobian(p1)?, &g2.to_jacobian(p2)?)))
+}
+
+pub fn bn254_g2_scalar_mul(p: &G2Affine<4>, k: &U256) -> Option<G2Affine<4>> {
+    let g2 = &bn254::G2;
+    Some(g2.to_affine(&g2.scalar_mul(&g2.to_jacobian(p)?, k)))
+}
+
+/// Parses EIP-197 input, a sequence of 192-byte records `(x, y, x_im, x_re, y_im, y_re)`, and
+/// checks that the product of the pairings is 1. `None` if the input is malformed.
+pub fn bn254_pairing_check(input: &[u8]) -> Option<bool> {
+    if input.len() % 192 != 0 { return None; }
+    let mut pairs = Vec::with_capacity(input.len() / 192);
+    for record in input.chunks_exact(192) {
+        let mut words = [[0u8; 32]; 6];
+        for (word, chunk) in words.iter_mut().zip(record.chunks_exact(32)) {
+            word.copy_from_slice(chunk);
+        }
+        pairs.push((affine_point_from_abi_words(&words[0], &words[1]), g2_point_from_abi_words(&words[2..])));
+    }
+    bn254::pairing_check(&pairs)
+}
+
+/// Adds two compressed BLS12-381 G1 points; `None` if either is not a valid encoding of a
+/// point in the order-`r` subgroup.
+pub fn bls12_381_g1_add(p1: &[u8], p2: &[u8]) -> Option<Vec<u8>> {
+    let g1 = &bls12_381::G1;
+    let sum = g1.add(&bls12_381::decode_g1(p1)?, &bls12_381::decode_g1(p2)?);
+    Some(bls12_381::encode_g1(&sum))
+}
+
+pub fn bls12_381_g1_scalar_mul(p: &[u8], k: &U256) -> Option<Vec<u8>> {
+    Some(bls12_381::encode_g1(&bls12_381::G1.scalar_mul(&bls12_381::decode_g1(p)?, k)))
+}
+
+pub fn bls12_381_g2_add(p1: &[u8], p2: &[u8]) -> Option<Vec<u8>> {
+    let g2 = &bls12_381::G2;
+    let sum = g2.add(&bls12_381::decode_g2(p1)?, &bls12_381::decode_g2(p2)?);
+    Some(bls12_381::encode_g2(&sum))
+}
+
+pub fn bls12_381_g2_scalar_mul(p: &[u8], k: &U256) -> Option<Vec<u8>> {
+    Some(bls12_381::encode_g2(&bls12_381::G2.scalar_mul(&bls12_381::decode_g2(p)?, k)))
+}
+
 /// Fixed-point multiplication for two signed i64 values, scaled by 100.
 fn mul_signed(a: i64, b: i64) -> i64 {
     (a as i128 * b as i128 / 100) as i64
 }
 
-/// Fixed-point division for two signed i64 values, scaled by 100.
-fn div_signed(a: i64, b: i64) -> i64 {
-    if b == 0 { return i64::MAX; } // Return max as error indicator
-    (a as i128 * 100 / b as i128) as i64
+/// Fixed-point division for two signed i64 values, scaled by 100. `None` when `b` is zero.
+fn div_signed(a: i64, b: i64) -> Option<i64> {
+    if b == 0 { return None; }
+    Some((a as i128 * 100 / b as i128) as i64)
 }
 
 /// Fixed-point square for a signed i64 value, scaled by 100.
 fn square_signed(a: i64) -> i64 {
     (a as i128 * a as i128 / 100) as i64
 }
+/// `None` for a vertical launch or a zero velocity, where the trajectory is not a function of x.
 pub fn get_projectile_trajectory_coefficients(
     angle_deg_times_10: u32,
     initial_velocity: u64,
     gravity: u64,
-) -> (i64, i64) {
+) -> Option<(i64, i64)> {
     let sin_val = sin(angle_deg_times_10);
     let cos_val = cos(angle_deg_times_10);
 
-    let c1 = {
-        if cos_val == 0 { i64::MAX } 
-        else { div_signed(sin_val, cos_val) }
-    };
+    let c1 = div_signed(sin_val, cos_val)?;
 
     let c2 = {
         let v0_squared = square(initial_velocity);
@@ -1058,12 +3047,10 @@ pub fn get_projectile_trajectory_coefficients(
         let denom_part1 = mul_signed(v0_squared as i64, cos_squared);
         let denominator = denom_part1.saturating_mul(2);
 
-        if denominator == 0 { i64::MIN } 
-        else { div_signed(-(gravity as i64), denominator) }
+        div_signed(-(gravity as i64), denominator)?
     };
-    
-    if c1 == i64::MAX { (c1, 0) } 
-    else { (c1, c2) }
+
+    Some((c1, c2))
 }
 
 #[derive(Clone)]
@@ -1073,10 +3060,806 @@ impl Xorshift64Star {
         if seed == 0 { Xorshift64Star { state: 1 } }
         else { Xorshift64Star { state: seed } }
     }
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> u64 {
         self.state ^= self.state >> 12;
         self.state ^= self.state << 25;
         self.state ^= self.state >> 27;
         self.state.wrapping_mul(0x2545F4914F6CDD1D)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCALARS: [u64; 10] = [0, 1, 2, 3, 5, 96, 97, 1 << 63, 0x0123_4567_89ab_cdef, u64::MAX];
+
+    /// Every affine point of `y^2 = x^3 + ax + b` over a small prime field.
+    fn points(a: u64, b: u64, modulus: u64) -> Vec<Point> {
+        let mut points = Vec::new();
+        for x in 0..modulus {
+            for y in 0..modulus {
+                if is_on_curve(Point::Coordinate { x, y }, a, b, modulus) { points.push(Point::Coordinate { x, y }); }
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn ladder_agrees_with_wnaf_on_every_point_of_small_curves() {
+        // y^2 = x^3 + x has points of order 2 and 4 mod 103; y^2 = x^3 + 2x + 3 mod 97 has
+        // an order-2 point too, and mod 5 the field is small enough to hit every corner.
+        for (a, b, modulus) in [(2, 3, 97), (1, 0, 103), (0, 7, 101), (1, 1, 5)] {
+            for p in points(a, b, modulus) {
+                for k in SCALARS.into_iter().chain(0..2 * modulus) {
+                    assert_eq!(
+                        scalar_mul_ladder(p, k, a, modulus),
+                        scalar_mul_wnaf(p, k, a, modulus),
+                        "k = {k}, P = {p:?} on y^2 = x^3 + {a}x + {b} mod {modulus}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ladder_agrees_with_wnaf_modulo_a_64_bit_prime() {
+        // The ladder derives b from P, so any point defines a curve through it.
+        let modulus = u64::MAX - 58; // 2^64 - 59
+        let mut rng = Xorshift64Star::new(0x5eed);
+        for _ in 0..64 {
+            let p = Point::Coordinate { x: rng.next() % modulus, y: rng.next() % modulus };
+            let (a, k) = (rng.next() % modulus, rng.next());
+            assert_eq!(scalar_mul_ladder(p, k, a, modulus), scalar_mul_wnaf(p, k, a, modulus));
+        }
+    }
+
+    #[test]
+    fn ladder_edge_cases() {
+        let p = Point::Coordinate { x: 3, y: 6 };
+        assert_eq!(scalar_mul_ladder(Point::Infinity, 5, 2, 97), Some(Point::Infinity));
+        assert_eq!(scalar_mul_ladder(p, 0, 2, 97), Some(Point::Infinity));
+        assert_eq!(scalar_mul_ladder(p, 1, 2, 97), Some(p));
+        // The inversion at the end needs a prime modulus.
+        assert_eq!(scalar_mul_ladder(p, 5, 2, 0), None);
+        assert_eq!(scalar_mul_ladder(p, 5, 2, 1), None);
+        assert_eq!(scalar_mul_ladder(p, 5, 2, 91), None);
+    }
+
+    /// Calldata for a call taking `(bytes data)`, behind a zero selector.
+    fn bytes_call(data: &[u8]) -> Vec<u8> {
+        let mut call = vec![0; 4];
+        call.extend(abi::encode(&data.to_vec()));
+        call
+    }
+
+    #[test]
+    fn hash_handlers_flag_malformed_calldata() {
+        host::set_call_data(&bytes_call(b"abc"));
+        let mut expected = abi::encode(&true);
+        expected.extend(hash::sha256(b"abc"));
+        assert_eq!(sha256_handler(), expected);
+        let mut expected = abi::encode(&true);
+        expected.extend(hash::blake2b::<64>(b"abc"));
+        assert_eq!(blake2b_512_handler(), expected);
+
+        // A `bytes` offset pointing past the end of the calldata.
+        let mut call = bytes_call(b"abc");
+        call[4 + 31] = 0xff;
+        host::set_call_data(&call);
+        assert_eq!(keccak256_handler(), abi::encode(&None::<[u8; 32]>));
+        assert_eq!(sha512_handler(), abi::encode(&None::<([u8; 32], [u8; 32])>));
+        assert_eq!(sha512_handler().len(), 96);
+    }
+
+    #[test]
+    fn address_to_account_id_rejects_dirty_addresses() {
+        let mut call = vec![0; 36];
+        call[16..].fill(0xaa);
+        host::set_call_data(&call);
+        let expected = abi::encode(&Some(account::to_fallback_account_id(&[0xaa; 20])));
+        assert_eq!(address_to_account_id_handler(), expected);
+
+        call[15] = 1;
+        host::set_call_data(&call);
+        assert_eq!(address_to_account_id_handler(), abi::encode(&None::<[u8; 32]>));
+    }
+
+    fn hex_bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn text_handlers_flag_malformed_calldata() {
+        host::set_call_data(&bytes_call(&[0x01, 0xab]));
+        assert_eq!(hex_encode_handler(), abi::encode(&Some(String::from("01ab"))));
+        host::set_call_data(&bytes_call(b"0x01AB"));
+        assert_eq!(hex_decode_handler(), abi::encode(&Some(vec![0x01u8, 0xab])));
+        host::set_call_data(&bytes_call(b"0x1"));
+        assert_eq!(hex_decode_handler(), abi::encode(&None::<Vec<u8>>));
+
+        // BIP-173's P2WPKH example.
+        let address = b"BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4";
+        let program = hex_bytes("751e76e8199196d454941c45d1b3a323f1433bd6");
+        let mut call = vec![0; 4];
+        call.extend(abi::Encoder::new().bytes(b"bc").bytes(address).finish());
+        host::set_call_data(&call);
+        assert_eq!(segwit_decode_handler(), abi::encode(&Some((0u32, program.clone()))));
+        // BIP-173's valid string holding the digits 0 to 31, which fill 20 bytes.
+        host::set_call_data(&bytes_call(b"abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw"));
+        let data = hex_bytes("00443214c74254b635cf84653a56d7c675be77df");
+        assert_eq!(bech32_decode_handler(), abi::encode(&Some((String::from("abcdef"), data, 0u32))));
+        // The witness version digit leaves a segwit address 5 bits past a whole byte.
+        host::set_call_data(&bytes_call(address));
+        assert_eq!(bech32_decode_handler(), abi::encode(&None::<(String, Vec<u8>, u32)>));
+
+        // A `bytes` offset pointing past the end of the calldata.
+        for handler in [hex_encode_handler, hex_decode_handler, bech32_decode_handler] {
+            let mut call = bytes_call(b"00");
+            call[4 + 31] = 0xff;
+            host::set_call_data(&call);
+            assert_eq!(handler()[..32], abi::encode(&false)[..]);
+        }
+        let mut call = vec![0; 4];
+        call.extend(abi::Encoder::new().bytes(b"bc").bytes(address).finish());
+        call[4 + 63] = 0xff;
+        host::set_call_data(&call);
+        assert_eq!(segwit_decode_handler(), abi::encode(&None::<(u32, Vec<u8>)>));
+    }
+
+    /// Calldata for `ecrecover(hash, v, r, s)`.
+    fn ecrecover_call(hash: &[u8; 32], v: &[u8; 32], r: &str, s: &str) -> Vec<u8> {
+        let mut call = vec![0; 4];
+        call.extend_from_slice(hash);
+        call.extend_from_slice(v);
+        call.extend(hex_bytes(r));
+        call.extend(hex_bytes(s));
+        call
+    }
+
+    #[test]
+    fn ecrecover_matches_the_precompile() {
+        // The generator is the public key of private key 1.
+        let address: [u8; 20] = hex_bytes("7e5f4552091a69125d5dfcb7b8c2659029395bdf").try_into().unwrap();
+        assert_eq!(ethereum_address(&curve::SECP256K1.generator), address);
+
+        // `k256` signatures over `keccak256("hello")` with private key 1 (recovery id 0) and
+        // `keccak256("x")` with private key 3 (recovery id 1).
+        let hello = hash::keccak256(b"hello");
+        let r = "433ec3d37e4f1253df15e2dea412fed8e915737730f74b3dfb1353268f932ef5";
+        let s = "557c9158e0b34bce39de28d11797b42e9b1acb2749230885fe075aedc3e491a4";
+        let v27 = U256::from_u64(27).to_be_bytes();
+        host::set_call_data(&ecrecover_call(&hello, &v27, r, s));
+        assert_eq!(ecrecover_handler(), abi::encode(&Some(address)));
+
+        let three: [u8; 20] = hex_bytes("6813eb9362372eef6200f3b1dbc3f819671cba69").try_into().unwrap();
+        host::set_call_data(&ecrecover_call(
+            &hash::keccak256(b"x"),
+            &U256::from_u64(28).to_be_bytes(),
+            "e6172c6649f6475e830fc626f00dc7081ad220f1832f3109b6417d15b0afdeec",
+            "7643e272623099ee8e29a2e113ba21154162d655a0ea99a20fb32b7d15cd9544",
+        ));
+        assert_eq!(ecrecover_handler(), abi::encode(&Some(three)));
+
+        // `v` must be exactly 27 or 28, high bytes included.
+        let mut dirty = v27;
+        dirty[0] = 1;
+        for v in [dirty, U256::from_u64(0).to_be_bytes(), U256::from_u64(29).to_be_bytes()] {
+            host::set_call_data(&ecrecover_call(&hello, &v, r, s));
+            assert_eq!(ecrecover_handler(), abi::encode(&None::<[u8; 20]>));
+        }
+        host::set_call_data(&ecrecover_call(&hello, &v27, r, s)[..100]);
+        assert_eq!(ecrecover_handler(), abi::encode(&None::<[u8; 20]>));
+    }
+
+    fn hex_words(words: &[&str]) -> Vec<[u8; 32]> {
+        words.iter().map(|word| hex_bytes(word).try_into().unwrap()).collect()
+    }
+
+    /// EIP-197 input from go-ethereum's `bn256Pairing.json` ("jeff1"): two pairs whose
+    /// product is 1.
+    const PAIRING_JEFF1: &str = concat!(
+        "1c76476f4def4bb94541d57ebba1193381ffa7aa76ada664dd31c16024c43f59",
+        "3034dd2920f673e204fee2811c678745fc819b55d3e9d294e45c9b03a76aef41",
+        "209dd15ebff5d46c4bd888e51a93cf99a7329636c63514396b4a452003a35bf7",
+        "04bf11ca01483bfa8b34b43561848d28905960114c8ac04049af4b6315a41678",
+        "2bb8324af6cfc93537a2ad1a445cfd0ca2a71acd7ac41fadbf933c2a51be344d",
+        "120a2a4cf30c1bf9845f20c6fe39e07ea2cce61f0c9bb048165fe5e4de877550",
+        "111e129f1cf1097710d41c4ac70fcdfa5ba2023c6ff1cbeac322de49d1b6df7c",
+        "2032c61a830e3c17286de9462bf242fca2883585b93870a73853face6a6bf411",
+        "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+        "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+        "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+        "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+    );
+
+    /// `(xP, yQ), (-xyP, Q)` for the generators `P`, `Q`, computed with arkworks' `ark-bn254`.
+    const PAIRING_BILINEAR: &str = concat!(
+        "142a7688cf05c29f7593351e1b86eb87e3ad5dcb1b0fc3d853e9852040c57019",
+        "136b5d7e238ae6edc22d1fba5a2dcde8a7b0df53b0c4af7f600e6a0c4610c899",
+        "29bac1eff6c8e711995776e67794f519f1215724d5cc5d07c156e0ec3fe6e507",
+        "121e1038451de561ccd0c47beb4dd2eb2445bd0c070f9d922accfa1b8eba281c",
+        "1e4142496c8166dde165bf6822668da569b4cef3acb67ca206ab044d29bbde42",
+        "255f2574b3c7af38cbd482bfc229dc3cf99ed0a1809d15104e5bd9c116bc71d3",
+        "1757bd057d5598d0e148e48d7e18df19cbd84472cece7c97d0929da5a290042c",
+        "2c6ca2de8486096cce7f6e95d3ec38da559f21296d774e404b023ea802685406",
+        "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+        "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+        "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+        "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+    );
+
+    /// The same with `-(xy + 1)P`, whose product is not 1.
+    const PAIRING_PERTURBED: &str = concat!(
+        "142a7688cf05c29f7593351e1b86eb87e3ad5dcb1b0fc3d853e9852040c57019",
+        "136b5d7e238ae6edc22d1fba5a2dcde8a7b0df53b0c4af7f600e6a0c4610c899",
+        "29bac1eff6c8e711995776e67794f519f1215724d5cc5d07c156e0ec3fe6e507",
+        "121e1038451de561ccd0c47beb4dd2eb2445bd0c070f9d922accfa1b8eba281c",
+        "1e4142496c8166dde165bf6822668da569b4cef3acb67ca206ab044d29bbde42",
+        "255f2574b3c7af38cbd482bfc229dc3cf99ed0a1809d15104e5bd9c116bc71d3",
+        "245581805c8d176bb9b232558ec33c89b00084604a53f72654b84239105a0911",
+        "0e8f5fe03776f0caad019281e1a9614eccdb3e485eaf43d1f5a5145b224f7da2",
+        "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+        "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+        "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+        "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+    );
+
+    /// The G1 generator with a point on the twist outside the order-`r` subgroup, the first
+    /// one from `x = 1 + 0u` upwards.
+    const PAIRING_NON_SUBGROUP: &str = concat!(
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0d1271953ed9ea0836846e70a1934187998c7f790cb4d7511b7f8da82de048a4",
+        "2869111d5381f072f8e2728fdb825a51aadd70e52c9830e9ab4b871c0531f1bb",
+    );
+
+    #[test]
+    fn eip197_pairing_check() {
+        assert_eq!(bn254_pairing_check(&hex_bytes(PAIRING_JEFF1)), Some(true));
+        assert_eq!(bn254_pairing_check(&hex_bytes(PAIRING_BILINEAR)), Some(true));
+        assert_eq!(bn254_pairing_check(&hex_bytes(PAIRING_PERTURBED)), Some(false));
+        assert_eq!(bn254_pairing_check(&[]), Some(true));
+        // A pair with G1 at infinity contributes 1.
+        let mut with_infinity = hex_bytes(PAIRING_JEFF1);
+        with_infinity.extend([0; 64]);
+        with_infinity.extend_from_slice(&hex_bytes(PAIRING_JEFF1)[64..192]);
+        assert_eq!(bn254_pairing_check(&with_infinity), Some(true));
+        // Only the first pair of a product that is 1.
+        assert_eq!(bn254_pairing_check(&hex_bytes(PAIRING_JEFF1)[..192]), Some(false));
+
+        assert_eq!(bn254_pairing_check(&hex_bytes(PAIRING_NON_SUBGROUP)), None);
+        assert_eq!(bn254_pairing_check(&[0x11; 192]), None);
+        assert_eq!(bn254_pairing_check(&hex_bytes(PAIRING_JEFF1)[..191]), None);
+        // A G1 coordinate replaced by itself plus p.
+        let mut unreduced = hex_bytes(PAIRING_JEFF1);
+        let x = U256::from_be_bytes(&unreduced[..32].try_into().unwrap());
+        unreduced[..32].copy_from_slice(&x.wrapping_add(&curve::BN254.field.modulus).to_be_bytes());
+        assert_eq!(bn254_pairing_check(&unreduced), None);
+    }
+
+    /// A Groth16 key and proof from `ark-groth16` 0.4 (seeded `StdRng`), for the circuit
+    /// `a * b = c, a + b = d` with public `(c, d)` and witness `a = 3, b = 11`; flattened as
+    /// the handler takes them, G2 coordinates imaginary part first.
+    const GROTH16_VK: [&str; 20] = [
+        "16cde7672d240777d5e1e109af2a17cf9c6f65aabd05a76d82461ed92edabcfb",
+        "21d9f38f3d392cb6605fe0986b27e2ce9c23dc0b8936d7af05eb213dfdaed020",
+        "135410eb08cc99678e973f836aac67573b37106e8f6b65ddc7b2976fc16ad2eb",
+        "2fa32830b84e4463e4dede31a16278eb742416eae4bef6b156903587cff98380",
+        "1489d87c0678fe268212b8a9f9e57aa5ee218a972b6aa066a6d9229f4fea9b99",
+        "1788fe169ad482a20fd2c4a70f3c65a55fed2022df58aca286c1acdf8c34c5f9",
+        "2bbec1646afc4b6f9eaa3926da6b62021e69922d4f2ffcacf2896a7bb636d4a9",
+        "2cf2bb37e9ec0305b71e42f5cfb5a6117a30ba1086fa0600a9720def28ba4ee7",
+        "0d1b2b86dac682bd0e36ee8de9c63750ffeeb520c92681976e362d1b3dc0e85d",
+        "062f3d3b4d162be8243a46c651e1fe735841bcaed0b7b2ec9d4979fa97d48ff0",
+        "23391ed97851cfb5d0bb6bc901ec00feaa36235fac6a8dba43d0ee75e12c2aa2",
+        "01135fa9c047229ca25627c2bf2194c043e03f34a8f6cc94b5ff1d61f19b9a7e",
+        "137ac003c9aff19bd67336083096af7eee88535985cbb6e0b40d74e8404559d4",
+        "2d270a276a2590d9e1b2b8bd70447b7880e92942981c2b0136ac58bafb389d87",
+        "126fae40e4b46a23175cede5a0681f5dab2b586d4d1502a58bac524d35b55c3f",
+        "10a61c5ad9f32491a3f9c787cce5327df48aa1e7f847e2921dd14127f81de096",
+        "1b43a6f7be7b26afd7f3510ea6f1b421b5f3ee3f1a871f0a77bba3ae3ed2912e",
+        "05b64a938de9e44805e233801df981b59519f05e1831936d53057580dba4013a",
+        "1cdfdeeab0f613b284bf9fcccbf83fcb202a6c718e9a2c56fa6ee58f8748f2a7",
+        "040376d75718aacc72b5b27f9990f0be688de14addc34c3c8be8e6b2be52a7fa",
+    ];
+    const GROTH16_PROOF: [&str; 8] = [
+        "0453a76fb083648a8b704965e134ac606d54188ddd3fb6bf92938f327303a961",
+        "268bf9b96fcd2ff167bfb5c825dbc4d5f980a2f7f630d9fed8c676748e7479df",
+        "01919fd0bec8fdd0e337eb33a1a27ca7ea52ed8780881af6e35835bdd376d5eb",
+        "25bf8a322c17bac797fd656b559777e8f4c0f06fe8beefdf79152701c0b74662",
+        "01dee7aa1d7d31734e9def9bddf05e5451adebd9397f37bf5e7fa5168af1fc32",
+        "04ad1c994e6c7fa21aaa487911290bb6490d20208971d02eb67a42ab70857336",
+        "1cc77625aa8543ecfec57d3ab69bfe7372553ac9bf75fef0d557c2a91fdd4fff",
+        "0da804efbea2fa541cf65f1edefb14a382dacfa1118d19cd360b09ee9b36045c",
+    ];
+
+    #[test]
+    fn groth16_ark_proof() {
+        let vk = groth16_vk_from_abi_words(&hex_words(&GROTH16_VK)).unwrap();
+        let proof_words = hex_words(&GROTH16_PROOF);
+        let proof = groth16_proof_from_abi_words(&proof_words).unwrap();
+        let inputs = [U256::from_u64(33), U256::from_u64(14)];
+        assert_eq!(groth16::verify(&vk, &proof, &inputs), Some(true));
+
+        // Other public inputs, and a proof with A and C swapped.
+        assert_eq!(groth16::verify(&vk, &proof, &[U256::from_u64(33), U256::from_u64(15)]), Some(false));
+        let mut swapped = proof_words.clone();
+        swapped[..2].copy_from_slice(&proof_words[6..]);
+        swapped[6..].copy_from_slice(&proof_words[..2]);
+        assert_eq!(groth16::verify(&vk, &groth16_proof_from_abi_words(&swapped).unwrap(), &inputs), Some(false));
+
+        // Malformed: an input not below r, the wrong number of inputs, and A off the curve.
+        let r = curve::BN254.scalar.modulus;
+        assert_eq!(groth16::verify(&vk, &proof, &[U256::from_u64(33).wrapping_add(&r), U256::from_u64(14)]), None);
+        assert_eq!(groth16::verify(&vk, &proof, &inputs[..1]), None);
+        let mut off_curve = proof_words.clone();
+        off_curve[1][31] ^= 1;
+        assert_eq!(groth16::verify(&vk, &groth16_proof_from_abi_words(&off_curve).unwrap(), &inputs), None);
+    }
+
+    #[test]
+    fn ct_field_matches_u128_arithmetic() {
+        let mut rng = Xorshift64Star::new(7);
+        for modulus in [2, 3, 97, (1 << 61) - 1, u64::MAX - 58] {
+            let f = CtField::new(modulus);
+            for _ in 0..1000 {
+                let (a, b) = (rng.next() % modulus, rng.next() % modulus);
+                assert_eq!(f.mul(a, b), mulmod(a, b, modulus));
+                assert_eq!(f.add(a, b), addmod(a, b, modulus));
+                assert_eq!(f.sub(a, b), submod(a, b, modulus));
+            }
+            assert_eq!(f.mul(modulus - 1, modulus - 1), 1 % modulus);
+        }
+    }
+
+    #[test]
+    fn binomials_match_pascals_triangle() {
+        // Rows of Pascal's triangle, with None once an entry passes 256 bits.
+        let mut row: Vec<Option<U256>> = vec![Some(U256::ONE)];
+        for n in 0..400u64 {
+            for (k, expected) in row.iter().enumerate() {
+                assert_eq!(n_choose_k_u256(n, k as u64), *expected, "C({n}, {k})");
+                assert_eq!(n_choose_k(n, k as u64), expected.and_then(U256::to_u64), "C({n}, {k})");
+            }
+            assert_eq!(n_choose_k_u256(n, n + 1), Some(U256::ZERO));
+            let mut next = vec![Some(U256::ONE)];
+            next.extend(row.windows(2).map(|pair| pair[0]?.checked_add(&pair[1]?)));
+            next.push(Some(U256::ONE));
+            row = next;
+        }
+        // The largest central binomial in a u64, and the first past it.
+        assert_eq!(n_choose_k(67, 33), Some(14_226_520_737_620_288_370));
+        assert_eq!(n_choose_k(68, 34), None);
+        assert_eq!(n_choose_k_u256(68, 34).and_then(U256::to_u128), Some(28_453_041_475_240_576_740));
+        // Huge n: C(n, 2) = n(n-1)/2 fits in 256 bits, C(n, 5) does not.
+        let n = u64::MAX;
+        let (product, _) = U256::from_u64(n).widening_mul(&U256::from_u64(n - 1));
+        assert_eq!(n_choose_k_u256(n, 2), Some(product.shr(1)));
+        assert_eq!(n_choose_k_u256(n, n - 1), Some(U256::from_u64(n)));
+        assert_eq!(n_choose_k_u256(n, 5), None);
+    }
+
+    /// `C(n, k) mod p` from the multiplicative formula with every factor of `p` taken out and
+    /// counted: zero if `p` divides the binomial, the remaining product otherwise.
+    fn n_choose_k_mod_reference(n: u64, k: u64, p: u64) -> u64 {
+        let (mut num, mut den, mut p_power) = (1, 1, 0i64);
+        for i in 0..k {
+            let (mut up, mut down) = (n - i, i + 1);
+            while up % p == 0 { up /= p; p_power += 1; }
+            while down % p == 0 { down /= p; p_power -= 1; }
+            num = mulmod(num, up % p, p);
+            den = mulmod(den, down % p, p);
+        }
+        if p_power > 0 { 0 } else { mulmod(num, modexp(den, p - 2, p), p) }
+    }
+
+    #[test]
+    fn n_choose_k_mod_by_lucas() {
+        let mut rng = Xorshift64Star::new(26);
+        // Primes with a factorial table (up to 1024) and without, with n spanning several
+        // base-p digits.
+        for p in [2, 3, 5, 7, 97, 1021, 1031, 1033, 65537] {
+            for _ in 0..200 {
+                let n = rng.next() % (3 * p * p).min(100_000);
+                let k = rng.next() % (n + 1);
+                assert_eq!(n_choose_k_mod(n, k, p), Some(n_choose_k_mod_reference(n, k, p)), "C({n}, {k}) mod {p}");
+            }
+        }
+        for n in 0..100 {
+            for k in 0..=n {
+                assert_eq!(n_choose_k_mod(n, k, 13), Some(n_choose_k_u256(n, k).unwrap().div_rem_u64(13).unwrap().1));
+            }
+        }
+        // One digit below a prime past u32, and digits of n below those of k.
+        let p = 4_294_967_311;
+        assert_eq!(n_choose_k_mod(p - 1, 3, p), Some(n_choose_k_mod_reference(p - 1, 3, p)));
+        assert_eq!(n_choose_k_mod(1031 * 5 + 2, 1031 + 3, 1031), Some(0));
+        assert_eq!(n_choose_k_mod(5, 7, 1031), Some(0));
+        assert_eq!(n_choose_k_mod(10, 3, 1), None);
+        assert_eq!(n_choose_k_mod(10, 3, 1024), None);
+        // Digits needing more multiplications than the cap, alone or together.
+        let p = (1 << 61) - 1;
+        assert_eq!(n_choose_k_mod(p - 1, (p - 1) / 2, p), None);
+        assert_eq!(n_choose_k_mod(p - 1, MAX_MODULAR_PRODUCT_TERMS, p), Some(n_choose_k_mod_reference(p - 1, MAX_MODULAR_PRODUCT_TERMS, p)));
+        assert_eq!(n_choose_k_mod(p - 1, MAX_MODULAR_PRODUCT_TERMS + 1, p), None);
+        // Base 65537: digits (p-1, p-1) choose (half, half) cost exactly the cap, one more digit
+        // choosing 1 of 2 passes it.
+        let (p, half) = (65537, MAX_MODULAR_PRODUCT_TERMS / 2);
+        let (n, k) = ((p - 1) * p + p - 1, half * p + half);
+        let digit = n_choose_k_mod_reference(p - 1, half, p);
+        assert_eq!(n_choose_k_mod(n, k, p), Some(mulmod(digit, digit, p)));
+        assert_eq!(n_choose_k_mod(2 * p * p + n, p * p + k, p), None);
+    }
+
+    #[test]
+    fn factorial_mod_takes_wilsons_shortcut() {
+        // Every n for small primes, and for a larger one those around the switch at m / 2.
+        for m in [2, 3, 1009, 65537] {
+            let mut direct = 1 % m;
+            for n in 0..m + 2 {
+                if n > 0 { direct = mulmod(direct, n % m, m); }
+                if m < 65537 || n.abs_diff(m / 2) < 8 || n % 4099 == 0 || n + 8 > m {
+                    assert_eq!(factorial_mod(n, m), Some(direct), "{n}! mod {m}");
+                }
+            }
+        }
+        // Past the halfway mark of a large prime, Wilson's theorem gives (p-1)! = -1 and
+        // (p-2)! = 1 without a long product.
+        let p = 1_000_000_007;
+        assert_eq!(factorial_mod(p - 1, p), Some(p - 1));
+        assert_eq!(factorial_mod(p - 2, p), Some(1));
+        assert_eq!(factorial_mod(p - 3, p), Some((p - 1) / 2));
+        // Composite moduli take the plain product.
+        assert_eq!(factorial_mod(5, 6), Some(0));
+        assert_eq!(factorial_mod(20, u64::MAX), factorial(20));
+        assert_eq!(factorial_mod(3, 1), Some(0));
+        assert_eq!(factorial_mod(3, 0), None);
+        // Products longer than the cap, on either side of m / 2.
+        let m = u64::MAX;
+        assert!(factorial_mod(MAX_MODULAR_PRODUCT_TERMS, m).is_some());
+        assert_eq!(factorial_mod(MAX_MODULAR_PRODUCT_TERMS + 1, m), None);
+        assert_eq!(factorial_mod(m - 2, m), None);
+        let p = u64::MAX - 58;
+        assert!(factorial_mod(p - 1 - MAX_MODULAR_PRODUCT_TERMS, p).is_some());
+        assert_eq!(factorial_mod(p - 2 - MAX_MODULAR_PRODUCT_TERMS, p), None);
+    }
+
+    /// Euclid's algorithm, to check the binary one against.
+    fn euclid<T: Unsigned>(mut a: T, mut b: T) -> T {
+        while !b.is_zero() {
+            (a, b) = (b, a.div_mod(&b).1);
+        }
+        a
+    }
+
+    /// Checks `binary_gcd`, `extended_gcd` and `modinv` on `(a, m)` against their definitions.
+    fn check_gcd_and_inverse<T: Unsigned + core::fmt::Debug>(a: T, m: T) {
+        let g = euclid(a, m);
+        assert_eq!(binary_gcd(a, m), g, "gcd({a:?}, {m:?})");
+        let (eg, x) = extended_gcd(a, m);
+        assert_eq!(eg, g);
+        if !m.is_zero() && !g.is_zero() {
+            assert!(x < m.div_mod(&g).0.max(T::ONE), "x = {x:?} for ({a:?}, {m:?})");
+            assert_eq!(a.mul_mod(&x, &m), g.div_mod(&m).1, "a * x = g for ({a:?}, {m:?})");
+        }
+        match modinv(a, m) {
+            Some(inverse) => {
+                assert!(g == T::ONE && inverse < m);
+                assert_eq!(a.mul_mod(&inverse, &m), T::ONE.div_mod(&m).1, "inverse of {a:?} mod {m:?}");
+            }
+            None => assert!(m.is_zero() || g != T::ONE, "{a:?} has an inverse mod {m:?}"),
+        }
+    }
+
+    #[test]
+    fn gcd_and_inverses_at_every_width() {
+        let mut rng = Xorshift64Star::new(27);
+        // Odd, even, power-of-two and full-width moduli, including m = 2^s * q with large s.
+        let moduli64 = [1, 2, 3, 4, 12, 97, 1 << 32, 3 << 40, (1 << 63) + 1, u64::MAX - 1, u64::MAX];
+        for m in moduli64 {
+            for a in [0, 1, 2, 3, m / 2, m - 1, m, u64::MAX] {
+                check_gcd_and_inverse(a, m);
+            }
+            for _ in 0..200 {
+                check_gcd_and_inverse(rng.next(), m);
+                check_gcd_and_inverse(rng.next() | 1, m);
+            }
+        }
+        let wide = |rng: &mut Xorshift64Star| (rng.next() as u128) << 64 | rng.next() as u128;
+        for m in [1u128 << 64, 1 << 127, 5 << 100, u128::MAX - 1, u128::MAX, wide(&mut rng) & !1] {
+            for _ in 0..100 {
+                check_gcd_and_inverse(wide(&mut rng), m);
+                check_gcd_and_inverse(wide(&mut rng) | 1, m);
+            }
+            check_gcd_and_inverse(m - 1, m);
+        }
+        let word = |rng: &mut Xorshift64Star| U256::from_u128(wide(rng)).shl(128).wrapping_add(&U256::from_u128(wide(rng)));
+        // 2^255 - 19, the Curve25519 field prime.
+        let p25519 = U256::ONE.shl(255).wrapping_sub(&U256::from_u64(19));
+        for m in [U256::ONE.shl(255), U256::MAX.wrapping_sub(&U256::ONE), U256::MAX, p25519, word(&mut rng).shl(17)] {
+            for _ in 0..20 {
+                check_gcd_and_inverse(word(&mut rng), m);
+                check_gcd_and_inverse(word(&mut rng).shr(3).shl(3).wrapping_add(&U256::ONE), m);
+            }
+        }
+        assert_eq!(modinv(3u64, 0), None);
+        assert_eq!(modinv(5u64, 1), Some(0));
+        // 3 * 0xAAAA...AB = 2^64 + 1, the inverse of 3 modulo 2^64 written as an even u128 modulus.
+        assert_eq!(modinv(3u128, 1 << 64), Some(0xAAAA_AAAA_AAAA_AAAB));
+        assert_eq!(binary_gcd(0u64, 0), 0);
+        assert_eq!(binary_gcd(3u64 << 40, 12 << 20), 12 << 20);
+    }
+
+    #[test]
+    fn batch_inverses() {
+        let mut rng = Xorshift64Star::new(127);
+        for m in [97u64, 1 << 40, u64::MAX - 1, u64::MAX] {
+            let values: Vec<u64> = (0..50).map(|_| rng.next() | 1).filter(|&v| euclid(v, m) == 1).collect();
+            let inverses = batch_modinv(&values, m).unwrap();
+            for (value, inverse) in values.iter().zip(&inverses) {
+                assert_eq!(Some(*inverse), modinv(*value, m));
+            }
+            // A single value without an inverse spoils the batch.
+            let mut spoiled = values.clone();
+            spoiled.insert(values.len() / 2, if m % 2 == 0 { 2 } else { m });
+            assert_eq!(batch_modinv(&spoiled, m), None);
+        }
+        assert_eq!(batch_modinv::<u64>(&[], 7), Some(vec![]));
+        assert_eq!(batch_modinv(&[1u64], 0), None);
+        let m = U256::MAX.wrapping_sub(&U256::ONE);
+        let values = [U256::from_u64(3), U256::MAX, U256::from_u64(1 << 40 | 1)];
+        let inverses = batch_modinv(&values, m).unwrap();
+        for (value, inverse) in values.iter().zip(&inverses) {
+            assert_eq!(value.mul_mod(inverse, &m), U256::ONE);
+        }
+    }
+
+    /// Checks the nth roots and logarithms of `n` against their defining inequalities, where a
+    /// power that overflows `T` counts as larger than any `n`.
+    fn check_roots_and_logs<T: Unsigned + core::fmt::Debug>(n: T) {
+        let le = |power: Option<T>| power.is_some_and(|power| power <= n);
+        let lt = |power: Option<T>| power.is_some_and(|power| power < n);
+        for k in 1..=T::BITS + 1 {
+            let floor = nth_root_floor(n, k).unwrap();
+            let next = floor.wrapping_add(&T::ONE);
+            assert!(le(checked_pow(floor, k)) && (next.is_zero() || !le(checked_pow(next, k))), "floor root {k} of {n:?}");
+            let ceil = nth_root_ceil(n, k).unwrap();
+            assert!(!lt(checked_pow(ceil, k)) && (ceil.is_zero() || lt(checked_pow(ceil.wrapping_sub(&T::ONE), k))), "ceil root {k} of {n:?}");
+        }
+        assert_eq!(nth_root_floor(n, 0), None);
+        for base in [2, 3, 10, 255, 256, 1 << 32].map(T::from_u64) {
+            if n.is_zero() {
+                assert_eq!(ilog_floor(n, base), None);
+                continue;
+            }
+            let floor = ilog_floor(n, base).unwrap();
+            assert!(le(checked_pow(base, floor)) && !le(checked_pow(base, floor + 1)), "floor log {base:?} of {n:?}");
+            let ceil = ilog_ceil(n, base).unwrap();
+            assert!(!lt(checked_pow(base, ceil)) && (ceil == 0 || lt(checked_pow(base, ceil - 1))), "ceil log {base:?} of {n:?}");
+        }
+    }
+
+    #[test]
+    fn roots_and_logs_at_every_width() {
+        let mut rng = Xorshift64Star::new(28);
+        for n in [0, 1, 2, 3, 4, 8, 9, 1 << 32, (1 << 32) - 1, (1 << 32) + 1, u64::MAX - 1, u64::MAX] {
+            check_roots_and_logs(n);
+        }
+        for _ in 0..100 {
+            let n = rng.next() >> (rng.next() % 64);
+            check_roots_and_logs(n);
+            check_roots_and_logs((n as u128) << 64 | rng.next() as u128);
+            assert_eq!(ilog_floor(n.max(1), 2), log2_floor(n.max(1)));
+            assert_eq!(ilog_floor(n.max(1), 10), Some(log10_floor(n.max(1))));
+        }
+        check_roots_and_logs(u128::MAX);
+        check_roots_and_logs(U256::MAX);
+        check_roots_and_logs(U256::ONE.shl(255));
+        check_roots_and_logs(U256::from_u128(u128::MAX).shl(100));
+
+        // The ceiling of a root or logarithm may exceed every representable power.
+        assert_eq!(nth_root_floor(u64::MAX, 2), Some(u32::MAX as u64));
+        assert_eq!(nth_root_ceil(u64::MAX, 2), Some(1 << 32));
+        assert_eq!(nth_root_ceil(U256::MAX, 2), Some(U256::ONE.shl(128)));
+        assert_eq!(nth_root_ceil(2u64, 1000), Some(2));
+        assert_eq!(ilog_ceil(u64::MAX, 2), Some(64));
+        assert_eq!(ilog_ceil(u64::MAX, u64::MAX), Some(1));
+        assert_eq!(ilog_floor(u64::MAX - 1, u64::MAX), Some(0));
+        assert_eq!(ilog_floor(10_000_000_000_000_000_000u64, 10), Some(19));
+        assert_eq!(ilog_ceil(10_000_000_000_000_000_000u64, 10), Some(19));
+        assert_eq!(ilog_ceil(10_000_000_000_000_000_001u64, 10), Some(20));
+        assert_eq!(ilog_floor(1u64, 2), Some(0));
+        assert_eq!(ilog_ceil(1u64, 2), Some(0));
+        assert_eq!(ilog_floor(8u64, 1), None);
+        assert_eq!(ilog_ceil(8u64, 0), None);
+    }
+
+    #[test]
+    fn perfect_powers() {
+        assert_eq!(is_perfect_power(0u64), None);
+        assert_eq!(is_perfect_power(1u64), None);
+        assert_eq!(is_perfect_power(97u64), None);
+        assert_eq!(is_perfect_power(36u64), Some((6, 2)));
+        assert_eq!(is_perfect_power(64u64), Some((2, 6)));
+        assert_eq!(is_perfect_power(1u64 << 63), Some((2, 63)));
+        assert_eq!(is_perfect_power(12_157_665_459_056_928_801u64), Some((3, 40)));
+        assert_eq!(is_perfect_power(1_000_000_000_000_000_000u64), Some((10, 18)));
+        // 2^32 - 1 = 3 * 5 * 17 * 257 * 65537 is no power itself.
+        assert_eq!(is_perfect_power(u32::MAX as u64 * u32::MAX as u64), Some((u32::MAX as u64, 2)));
+        assert_eq!(is_perfect_power(u64::MAX), None);
+        assert_eq!(is_perfect_power(3u128.pow(80)), Some((3, 80)));
+        assert_eq!(is_perfect_power(U256::ONE.shl(255)), Some((U256::from_u64(2), 255)));
+        assert_eq!(is_perfect_power(U256::MAX), None);
+
+        let mut rng = Xorshift64Star::new(128);
+        for _ in 0..300 {
+            let base = 2 + rng.next() % 1000;
+            let exponent = 2 + (rng.next() % 20) as u32;
+            let Some(n) = checked_pow(base as u128, exponent) else { continue };
+            // The largest exponent is a multiple of this one, for a base that is a power too.
+            let (root, k) = is_perfect_power(n).unwrap();
+            assert!(k % exponent == 0 && checked_pow(root, k) == Some(n), "{base}^{exponent}");
+        }
+    }
+
+    #[test]
+    fn fibonacci_and_lucas_up_to_overflow() {
+        // Step both sequences one term at a time until they pass 256 bits.
+        let (mut f, mut f_next) = (Some(U256::ZERO), Some(U256::ONE));
+        let (mut l, mut l_next) = (Some(U256::from_u64(2)), Some(U256::ONE));
+        for n in 0..400u64 {
+            assert_eq!(fibonacci(n), f, "F({n})");
+            assert_eq!(lucas(n), l, "L({n})");
+            (f, f_next) = (f_next, f.zip(f_next).and_then(|(a, b)| a.checked_add(&b)));
+            (l, l_next) = (l_next, l.zip(l_next).and_then(|(a, b)| a.checked_add(&b)));
+        }
+        // F(370) and L(368) are the last to fit.
+        assert!(fibonacci(370).is_some() && fibonacci(371).is_none());
+        assert!(lucas(368).is_some() && lucas(369).is_none());
+        assert_eq!(fibonacci(93).and_then(U256::to_u64), Some(12_200_160_415_121_876_738));
+        assert_eq!(fibonacci(100).and_then(U256::to_u128), Some(354_224_848_179_261_915_075));
+        assert_eq!(lucas(10).and_then(U256::to_u64), Some(123));
+        assert_eq!(fibonacci(u64::MAX), None);
+    }
+
+    #[test]
+    fn fibonacci_and_lucas_mod() {
+        for m in [1, 2, 10, 97, (1 << 32) + 15, u64::MAX] {
+            let (mut f, mut f_next) = (0, 1 % m);
+            let (mut l, mut l_next) = (2 % m, 1 % m);
+            for n in 0..1000 {
+                assert_eq!(fibonacci_mod(n, m), Some(f), "F({n}) mod {m}");
+                assert_eq!(lucas_mod(n, m), Some(l), "L({n}) mod {m}");
+                (f, f_next) = (f_next, addmod(f, f_next, m));
+                (l, l_next) = (l_next, addmod(l, l_next, m));
+            }
+        }
+        // The Pisano period of 10 is 60, and L(n) = F(n-1) + F(n+1).
+        let n = 1_000_000_000_000_000_000;
+        assert_eq!(fibonacci_mod(n + 60, 10), fibonacci_mod(n, 10));
+        let m = u64::MAX - 58;
+        assert_eq!(lucas_mod(n, m), Some(addmod(fibonacci_mod(n - 1, m).unwrap(), fibonacci_mod(n + 1, m).unwrap(), m)));
+        assert_eq!(fibonacci_mod(u64::MAX, 1), Some(0));
+        assert_eq!(fibonacci_mod(5, 0), None);
+        assert_eq!(lucas_mod(5, 0), None);
+    }
+
+    #[test]
+    fn linear_recurrences() {
+        let mut rng = Xorshift64Star::new(29);
+        // Random recurrences of orders 1 to 5 against stepping them directly.
+        for order in 1..=5 {
+            let m = rng.next() | 1;
+            let coefficients: Vec<u64> = (0..order).map(|_| rng.next()).collect();
+            let initial: Vec<u64> = (0..order).map(|_| rng.next()).collect();
+            let mut terms: Vec<u64> = initial.iter().map(|term| term % m).collect();
+            for n in 0..200 {
+                if n as usize >= order {
+                    let next = (0..order).fold(0, |acc, j| addmod(acc, mulmod(coefficients[j] % m, terms[n as usize - 1 - j], m), m));
+                    terms.push(next);
+                }
+                assert_eq!(linear_recurrence_mod(&coefficients, &initial, n, m), Some(terms[n as usize]), "order {order}, term {n}");
+            }
+        }
+        // Fibonacci as a recurrence, far out.
+        let m = 1_000_000_007;
+        for n in [0, 1, 2, 1 << 40, u64::MAX] {
+            assert_eq!(linear_recurrence_mod(&[1, 1], &[0, 1], n, m), fibonacci_mod(n, m));
+        }
+        // a(n) = 2 a(n-1) with a(0) = 1 gives powers of two.
+        assert_eq!(linear_recurrence_mod(&[2], &[1], 1000, m), Some(modexp(2, 1000, m)));
+        // The largest order, and malformed input.
+        let ones = vec![1; MAX_RECURRENCE_ORDER];
+        assert!(linear_recurrence_mod(&ones, &ones, 1000, m).is_some());
+        let too_many = vec![1; MAX_RECURRENCE_ORDER + 1];
+        assert_eq!(linear_recurrence_mod(&too_many, &too_many, 1000, m), None);
+        assert_eq!(linear_recurrence_mod(&[1, 1], &[0], 10, m), None);
+        assert_eq!(linear_recurrence_mod(&[], &[], 10, m), None);
+        assert_eq!(linear_recurrence_mod(&[1, 1], &[0, 1], 10, 0), None);
+        assert_eq!(linear_recurrence_mod(&[1, 1], &[0, 1], 10, 1), Some(0));
+    }
+
+    fn rational(num: u64, den: u64) -> Rational {
+        Rational::new(num, den).unwrap()
+    }
+
+    #[test]
+    fn continued_fractions_and_convergents() {
+        assert_eq!(to_continued_fraction(415, 93), Some(vec![4, 2, 6, 7]));
+        assert_eq!(convergents(&[4, 2, 6, 7]), Some(vec![rational(4, 1), rational(9, 2), rational(58, 13), rational(415, 93)]));
+        // pi to 14 decimal places, and sqrt(2) to 15.
+        let pi = to_continued_fraction(314_159_265_358_979, 100_000_000_000_000).unwrap();
+        assert_eq!(pi[..5], [3, 7, 15, 1, 292]);
+        assert_eq!(convergents(&pi[..5]).unwrap()[1..], [rational(22, 7), rational(333, 106), rational(355, 113), rational(103_993, 33_102)]);
+        let sqrt2 = to_continued_fraction(1_414_213_562_373_095, 1_000_000_000_000_000).unwrap();
+        assert!(sqrt2[0] == 1 && sqrt2[1..15].iter().all(|&term| term == 2));
+
+        let mut rng = Xorshift64Star::new(30);
+        for _ in 0..500 {
+            let (num, den) = (rng.next() >> (rng.next() % 64), (rng.next() >> (rng.next() % 64)).max(1));
+            let terms = to_continued_fraction(num, den).unwrap();
+            assert_eq!(convergents(&terms).unwrap().last(), Some(&rational(num, den)));
+        }
+        assert_eq!(to_continued_fraction(0, 5), Some(vec![0]));
+        assert_eq!(to_continued_fraction(5, 0), None);
+        // A zero partial quotient after the first, and convergents past 64 bits.
+        assert_eq!(convergents(&[1, 0]), None);
+        assert_eq!(convergents(&[u64::MAX, 2]), None);
+        assert_eq!(convergents(&[]), Some(vec![]));
+    }
+
+    #[test]
+    fn best_rational_approximations() {
+        let pi = rational(314_159_265_358_979, 100_000_000_000_000);
+        assert_eq!(best_rational_approximation(pi, 1), Some(rational(3, 1)));
+        assert_eq!(best_rational_approximation(pi, 10), Some(rational(22, 7)));
+        // A semiconvergent between 22/7 and 333/106.
+        assert_eq!(best_rational_approximation(pi, 100), Some(rational(311, 99)));
+        assert_eq!(best_rational_approximation(pi, 1000), Some(rational(355, 113)));
+        assert_eq!(best_rational_approximation(pi, 100_000), Some(rational(312_689, 99_532)));
+        assert_eq!(best_rational_approximation(pi, 0), None);
+        assert_eq!(best_rational_approximation(rational(7, 3), 3), Some(rational(7, 3)));
+
+        // Against a search over every denominator: nothing within the bound comes closer.
+        let mut rng = Xorshift64Star::new(130);
+        for _ in 0..300 {
+            let x = rational(rng.next() % 10_000, 1 + rng.next() % 10_000);
+            let max_denominator = 1 + rng.next() % 60;
+            let best = best_rational_approximation(x, max_denominator).unwrap();
+            assert!(best.den <= max_denominator);
+            let error = |candidate: Rational| candidate.abs_diff_unreduced(x);
+            let (best_num, best_den) = error(best);
+            for den in 1..=max_denominator {
+                let floor = x.num * den / x.den;
+                for num in [floor, floor + 1] {
+                    let (num, den2) = error(rational(num, den));
+                    assert!(best_num * den2 <= num * best_den, "{x:?} within {max_denominator}: {best:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rational_arithmetic() {
+        assert_eq!(Rational::new(6, 4), Some(Rational { num: 3, den: 2 }));
+        assert_eq!(Rational::new(0, 7), Some(Rational { num: 0, den: 1 }));
+        assert_eq!(Rational::new(1, 0), None);
+        assert_eq!(rational(1, 6).checked_add(rational(1, 3)), Some(rational(1, 2)));
+        assert_eq!(rational(2, 3).checked_mul(rational(3, 4)), Some(rational(1, 2)));
+        assert_eq!(rational(0, 1).checked_mul(rational(u64::MAX, 1)), Some(rational(0, 1)));
+        // Cancelling before multiplying keeps results that fit from overflowing.
+        let m = u64::MAX;
+        assert_eq!(rational(m / 3, 1).checked_mul(rational(3, m)), Some(rational(1, 1)));
+        assert_eq!(rational(1, m - 1).checked_add(rational(1, m - 1)), Some(rational(1, (m - 1) / 2)));
+        assert_eq!(rational(m, 1).checked_add(rational(1, 1)), None);
+        assert_eq!(rational(m, 2).checked_mul(rational(m, 3)), None);
+        assert_eq!(rational(1, m).checked_add(rational(1, m - 1)), None);
+        // Comparison by cross-multiplication, exact near the top of the range.
+        assert!(rational(m - 1, m) > rational(m - 2, m - 1));
+        assert!(rational(1, 3) > rational(33_333, 100_000));
+        assert_eq!(rational(2, 4).cmp(&rational(1, 2)), core::cmp::Ordering::Equal);
+    }
+}
@@ -0,0 +1,369 @@
+//! Binary Merkle trees over `bytes32` nodes: proof and multiproof verification and root
+//! construction.
+//!
+//! With keccak256 each parent hashes the sorted pair, as OpenZeppelin's `MerkleProof` does, so
+//! proofs need no positions and an odd node at the end of a layer moves up unchanged
+//! (merkletreejs with `sortPairs`). SHA-256 and Poseidon hash the ordered pair instead: the
+//! leaf index gives the path, one bit per level from the bottom, and trees are padded with
+//! zero leaves to a power of two.
+
+use alloc::vec::Vec;
+
+use crate::host;
+use crate::poseidon;
+use crate::uint::U256;
+
+pub type Node = [u8; 32];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Hasher {
+    Keccak256Sorted,
+    Sha256,
+    Poseidon,
+}
+
+impl Hasher {
+    /// Maps the ABI hash identifier: 0 = keccak256 of the sorted pair, 1 = SHA-256,
+    /// 2 = Poseidon over BN254.
+    pub fn from_id(id: u64) -> Option<Self> {
+        match id {
+            0 => Some(Hasher::Keccak256Sorted),
+            1 => Some(Hasher::Sha256),
+            2 => Some(Hasher::Poseidon),
+            _ => None,
+        }
+    }
+
+    /// Sets the hash up for one tree or proof: Poseidon's parameters are derived here, once
+    /// for all its nodes.
+    fn prepare(self) -> Option<PairHash> {
+        Some(match self {
+            Hasher::Keccak256Sorted => PairHash::Keccak256Sorted,
+            Hasher::Sha256 => PairHash::Sha256,
+            Hasher::Poseidon => PairHash::Poseidon(poseidon::Poseidon::new(2)?),
+        })
+    }
+}
+
+/// `left || right` on the stack: the bump heap never frees, and a tree hashes many pairs.
+fn pair(left: &Node, right: &Node) -> [u8; 64] {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left);
+    data[32..].copy_from_slice(right);
+    data
+}
+
+/// A `Hasher` ready to hash the nodes of one tree or proof.
+enum PairHash {
+    Keccak256Sorted,
+    Sha256,
+    Poseidon(poseidon::Poseidon),
+}
+
+impl PairHash {
+    /// The parent of `left` and `right`; `None` for Poseidon if either is not a field element.
+    fn parent(&self, left: &Node, right: &Node) -> Option<Node> {
+        let mut hash = [0u8; 32];
+        match self {
+            PairHash::Keccak256Sorted => {
+                let (a, b) = if left <= right { (left, right) } else { (right, left) };
+                host::hash_keccak_256(&pair(a, b), &mut hash);
+            }
+            PairHash::Sha256 => host::hash_sha2_256(&pair(left, right), &mut hash),
+            PairHash::Poseidon(poseidon) => {
+                hash = poseidon.hash(&[U256::from_be_bytes(left), U256::from_be_bytes(right)])?.to_be_bytes();
+            }
+        }
+        Some(hash)
+    }
+}
+
+/// The node hash of the sparse and mountain-range trees, over the ordered pair.
+#[derive(Clone, Copy)]
+pub enum NodeHash {
+    Keccak256,
+    Blake2b256,
+}
+
+impl NodeHash {
+    /// Maps the ABI hash identifier: 0 = keccak256, 1 = BLAKE2b-256.
+    pub fn from_id(id: u64) -> Option<Self> {
+        match id {
+            0 => Some(NodeHash::Keccak256),
+            1 => Some(NodeHash::Blake2b256),
+            _ => None,
+        }
+    }
+
+    /// `H(left || right)`.
+    pub fn merge(self, left: &Node, right: &Node) -> Node {
+        let data = pair(left, right);
+        let mut hash = [0u8; 32];
+        match self {
+            NodeHash::Keccak256 => host::hash_keccak_256(&data, &mut hash),
+            NodeHash::Blake2b256 => host::hash_blake2_256(&data, &mut hash),
+        }
+        hash
+    }
+}
+
+/// Whether `proof` leads from `leaf`, at position `index`, to `root`. `None` if a node is not
+/// valid for the hash, or, for ordered hashes, `index` does not fit the proof's depth.
+pub fn verify(hasher: Hasher, leaf: &Node, proof: &[Node], index: u64, root: &Node) -> Option<bool> {
+    if hasher != Hasher::Keccak256Sorted && proof.len() < 64 && index >> proof.len() != 0 { return None; }
+    let hasher = hasher.prepare()?;
+    let mut node = *leaf;
+    for (level, sibling) in proof.iter().enumerate() {
+        let is_right = level < 64 && index >> level & 1 == 1;
+        node = if is_right { hasher.parent(sibling, &node)? } else { hasher.parent(&node, sibling)? };
+    }
+    Some(node == *root)
+}
+
+/// The root over `leaves`, hashed in place layer by layer; `None` if there are none.
+pub fn root(hasher: Hasher, mut leaves: Vec<Node>) -> Option<Node> {
+    if leaves.is_empty() { return None; }
+    if hasher != Hasher::Keccak256Sorted {
+        leaves.resize(leaves.len().next_power_of_two(), [0; 32]);
+    }
+    let hasher = hasher.prepare()?;
+    while leaves.len() > 1 {
+        let len = leaves.len();
+        for i in 0..len / 2 {
+            leaves[i] = hasher.parent(&leaves[2 * i], &leaves[2 * i + 1])?;
+        }
+        if len % 2 == 1 { leaves[len / 2] = leaves[len - 1]; }
+        leaves.truncate(len.div_ceil(2));
+    }
+    Some(leaves[0])
+}
+
+/// OpenZeppelin's `multiProofVerify`: rebuilds the root from `leaves` (in tree order) and the
+/// `proof` nodes, where `flags[i]` tells whether the `i`-th hash takes its second operand from
+/// the leaves and computed hashes or from the proof. `None` if the three lengths disagree or
+/// the flags consume nodes that are not there.
+pub fn multiproof_verify(hasher: Hasher, leaves: &[Node], proof: &[Node], flags: &[bool], root: &Node) -> Option<bool> {
+    if leaves.len() + proof.len() != flags.len() + 1 { return None; }
+    let hasher = hasher.prepare()?;
+    let mut hashes: Vec<Node> = Vec::with_capacity(flags.len());
+    let (mut leaf_pos, mut hash_pos, mut proof_pos) = (0, 0, 0);
+    // The next leaf, or else the next computed hash.
+    let mut next = |hashes: &Vec<Node>| -> Option<Node> {
+        if leaf_pos < leaves.len() {
+            leaf_pos += 1;
+            Some(leaves[leaf_pos - 1])
+        } else {
+            hash_pos += 1;
+            hashes.get(hash_pos - 1).copied()
+        }
+    };
+    for &flag in flags {
+        let a = next(&hashes)?;
+        let b = if flag {
+            next(&hashes)?
+        } else {
+            proof_pos += 1;
+            *proof.get(proof_pos - 1)?
+        };
+        hashes.push(hasher.parent(&a, &b)?);
+    }
+    let computed = match hashes.last() {
+        Some(hash) if proof_pos == proof.len() => *hash,
+        Some(_) => return None,
+        None => *leaves.first().or(proof.first())?,
+    };
+    Some(computed == *root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(value: u64) -> Node {
+        U256::from_u64(value).to_be_bytes()
+    }
+
+    fn poseidon_parent(left: &Node, right: &Node) -> Node {
+        poseidon::poseidon(&[U256::from_be_bytes(left), U256::from_be_bytes(right)]).unwrap().to_be_bytes()
+    }
+
+    /// A standard depth-20 proof and a 32-leaf tree each take a small part of the heap, which
+    /// used to overflow when every node derived its own Poseidon parameters.
+    #[test]
+    fn poseidon_trees_fit_the_heap() {
+        let leaves: Vec<Node> = (0..32).map(node).collect();
+        let mut layer = leaves.clone();
+        while layer.len() > 1 {
+            layer = layer.chunks_exact(2).map(|pair| poseidon_parent(&pair[0], &pair[1])).collect();
+        }
+        let before = host::allocated_bytes();
+        assert_eq!(root(Hasher::Poseidon, leaves), Some(layer[0]));
+        assert!(host::allocated_bytes() - before < crate::HEAP_SIZE / 8);
+
+        let proof: Vec<Node> = (100..120).map(node).collect();
+        let index = 0b1010_0110_0101_1001_1100;
+        let mut expected = node(7);
+        for (level, sibling) in proof.iter().enumerate() {
+            expected = if index >> level & 1 == 1 { poseidon_parent(sibling, &expected) } else { poseidon_parent(&expected, sibling) };
+        }
+        let before = host::allocated_bytes();
+        assert_eq!(verify(Hasher::Poseidon, &node(7), &proof, index, &expected), Some(true));
+        assert!(host::allocated_bytes() - before < crate::HEAP_SIZE / 8);
+        assert_eq!(verify(Hasher::Poseidon, &node(7), &proof, index ^ 1, &expected), Some(false));
+    }
+
+    #[test]
+    fn keccak_trees_allocate_nothing_per_node() {
+        let leaves: Vec<Node> = (0..1000).map(node).collect();
+        let before = host::allocated_bytes();
+        root(Hasher::Keccak256Sorted, leaves).unwrap();
+        assert_eq!(host::allocated_bytes(), before);
+    }
+
+    fn hex_node(text: &str) -> Node {
+        crate::hex::decode(text.as_bytes()).unwrap().try_into().unwrap()
+    }
+
+    /// The tree of @openzeppelin/merkle-tree's `core` tests (as ported by `merkle-tree-rs`), in
+    /// its array layout: the root, the two inner nodes, then the leaves at indices 3 to 6.
+    const OZ_TREE: [&str; 7] = [
+        "73d176c80504454dc263f0791b2f9fd4efb92a00f1484d8e2d20589e083d2c0b",
+        "ce08fa786c7139b0695c4ea69b60a8b09d3925c7a5009829486df4d7469fca92",
+        "e612afaeeec03d6ee8081e5a21e0d15b2555ab7238dbe7d23ed9e62a121c8bcb",
+        "e950a5934db7a2c711cf3a07e165a15d128f46d3a64cd0e518644334ed6fc660",
+        "0fa417b185bdb92482b30b25130ef0de190d271ca91c8a661c2d40a61e8f6c5c",
+        "e958a5934db7a2c7aacf3a43e165a15d128f07d3a64cf8e5e0714334ed83c660",
+        "9da417b185bdb924824f0b07be0ef0de377b27eea9e48a66082d40a6038f305c",
+    ];
+
+    /// The `StandardMerkleTree` of @openzeppelin/merkle-tree's README, over `(address, uint256)`
+    /// leaves hashed as `keccak256(bytes.concat(keccak256(abi.encode(account, amount))))`: the
+    /// root, then the leaves at indices 1 and 2.
+    const OZ_README_TREE: [&str; 3] = [
+        "d4dee0beab2d53f2cc83e567171bd2820e49898130a22622b10ead383e90bd77",
+        "eb02c421cfa48976e66dfb29120745909ea3a0f843456c263cf8f1253483e283",
+        "b92c48e9d7abe27fd8dfd6b5dfdbfb1c9a463f80c712b66f3a5180a090cccafc",
+    ];
+
+    #[test]
+    fn openzeppelin_standard_tree() {
+        let [root_node, first, second] = OZ_README_TREE.map(hex_node);
+        let leaf = |account: u8, amount: u64| {
+            let mut encoded = [0u8; 64];
+            encoded[12..32].fill(account);
+            encoded[32..].copy_from_slice(&U256::from_u64(amount).to_be_bytes());
+            crate::hash::keccak256(&crate::hash::keccak256(&encoded))
+        };
+        assert_eq!(leaf(0x11, 5_000_000_000_000_000_000), first);
+        assert_eq!(leaf(0x22, 2_500_000_000_000_000_000), second);
+        assert_eq!(root(Hasher::Keccak256Sorted, alloc::vec![first, second]), Some(root_node));
+        assert_eq!(verify(Hasher::Keccak256Sorted, &first, &[second], 0, &root_node), Some(true));
+        assert_eq!(verify(Hasher::Keccak256Sorted, &second, &[first], 0, &root_node), Some(true));
+        assert_eq!(multiproof_verify(Hasher::Keccak256Sorted, &[second, first], &[], &[true], &root_node), Some(true));
+    }
+
+    #[test]
+    fn openzeppelin_multiproofs() {
+        let t = OZ_TREE.map(hex_node);
+        let verify_multi = |leaves: &[Node], proof: &[Node], flags: &[bool]| {
+            multiproof_verify(Hasher::Keccak256Sorted, leaves, proof, flags, &t[0])
+        };
+        assert_eq!(root(Hasher::Keccak256Sorted, t[3..].to_vec()), Some(t[0]));
+        assert_eq!(verify(Hasher::Keccak256Sorted, &t[6], &[t[5], t[1]], 0, &t[0]), Some(true));
+
+        // `getMultiProof(tree, [4, 6])`, whose leaves come in descending tree order.
+        assert_eq!(verify_multi(&[t[6], t[4]], &[t[5], t[3]], &[false, false, true]), Some(true));
+        assert_eq!(verify_multi(&[t[6], t[4]], &[t[5], t[3]], &[true, false, false]), Some(false));
+        // Leaves out of order pair with the wrong proof nodes.
+        assert_eq!(verify_multi(&[t[4], t[6]], &[t[5], t[3]], &[false, false, true]), Some(false));
+        assert_eq!(
+            multiproof_verify(Hasher::Keccak256Sorted, &[t[6], t[4]], &[t[5], t[3]], &[false, false, true], &t[1]),
+            Some(false)
+        );
+
+        // Leaves only: every leaf, or a single one that is the root.
+        assert_eq!(verify_multi(&[t[6], t[5], t[4], t[3]], &[], &[true, true, true]), Some(true));
+        assert_eq!(verify_multi(&[t[0]], &[], &[]), Some(true));
+        assert_eq!(verify_multi(&[t[1]], &[], &[]), Some(false));
+        // Proof only: the root itself.
+        assert_eq!(verify_multi(&[], &[t[0]], &[]), Some(true));
+        assert_eq!(verify_multi(&[], &[t[2]], &[]), Some(false));
+
+        // `leaves + proof != flags + 1`.
+        assert_eq!(verify_multi(&[], &[], &[]), None);
+        assert_eq!(verify_multi(&[t[6], t[4]], &[t[5], t[3]], &[false, false, true, true]), None);
+        assert_eq!(verify_multi(&[t[6], t[4]], &[t[5]], &[false, false, true]), None);
+        assert_eq!(verify_multi(&[t[6], t[5], t[4]], &[t[5], t[3]], &[false, false, true]), None);
+        assert_eq!(verify_multi(&[t[0]], &[t[0]], &[]), None);
+        // Flags asking for a proof node or a hash that is not there yet.
+        assert_eq!(verify_multi(&[t[6], t[4]], &[t[5], t[3]], &[false, false, false]), None);
+        assert_eq!(verify_multi(&[], &[t[1], t[2]], &[false]), None);
+        assert_eq!(verify_multi(&[t[6], t[5], t[4], t[3]], &[], &[true, true, false]), None);
+    }
+
+    /// `sha256(Z_i || Z_i)` from `Z_0 = 0`: the zero subtree roots of the Ethereum deposit
+    /// contract.
+    const SHA256_ZERO_HASHES: [&str; 4] = [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b",
+        "db56114e00fdd4c1f85c892bf35ac9a89289aaecb1ebd0a96cde606a748b5d71",
+        "c78009fdf07fc56a11f122370658a353aaa542ed63e44c4bc15ff4cd105ab33c",
+    ];
+
+    #[test]
+    fn sha256_ordered_pairs() {
+        let zeros = SHA256_ZERO_HASHES.map(hex_node);
+        assert_eq!(root(Hasher::Sha256, alloc::vec![zeros[0]; 8]), Some(zeros[3]));
+        // Five leaves pad up to eight.
+        assert_eq!(root(Hasher::Sha256, alloc::vec![zeros[0]; 5]), Some(zeros[3]));
+        assert_eq!(root(Hasher::Sha256, alloc::vec![zeros[0]]), Some(zeros[0]));
+        assert_eq!(verify(Hasher::Sha256, &zeros[0], &zeros[..3], 5, &zeros[3]), Some(true));
+
+        // `sha256("a")`, `sha256("b")` and `sha256("c")` padded with a zero leaf: the root is
+        // `sha256(sha256(a || b) || sha256(c || 0))`, from Python's `hashlib`.
+        let [a, b, c] = [b"a", b"b", b"c"].map(|text| crate::hash::sha256(text));
+        let ab = hex_node("e5a01fee14e0ed5c48714f22180f25ad8365b53f9779f79dc4a3d7e93963f94a");
+        let c0 = hex_node("898184a7d6a032c38817722d914121832084222eb30943d7ae635fc479d1a859");
+        let expected = hex_node("d0a664079d491a97357efa1ce1eab5aeb566adef78a2b910e8d13e901e192832");
+        assert_eq!(root(Hasher::Sha256, alloc::vec![a, b, c]), Some(expected));
+        assert_ne!(root(Hasher::Keccak256Sorted, alloc::vec![a, b, c]), Some(expected));
+
+        for (index, leaf, proof) in [(0, a, [b, c0]), (1, b, [a, c0]), (2, c, [zeros[0], ab]), (3, zeros[0], [c, ab])] {
+            assert_eq!(verify(Hasher::Sha256, &leaf, &proof, index, &expected), Some(true), "{index}");
+            // The order matters: the other side at the bottom level fails.
+            assert_eq!(verify(Hasher::Sha256, &leaf, &proof, index ^ 1, &expected), Some(false));
+        }
+        // The index must fit the depth of the proof.
+        assert_eq!(verify(Hasher::Sha256, &a, &[b, c0], 4, &expected), None);
+        assert_eq!(verify(Hasher::Sha256, &expected, &[], 0, &expected), Some(true));
+        assert_eq!(verify(Hasher::Sha256, &expected, &[], 1, &expected), None);
+    }
+
+    /// Roots over `keccak256` of "a" to "c" and of "a" to "e" with sorted pairs, where an odd
+    /// node moves up unchanged as in merkletreejs (`sortPairs`, no `duplicateOdd`); computed
+    /// with the `sha3` crate.
+    const KECCAK_ODD_ROOTS: [(usize, &str); 2] = [
+        (3, "5842148bc6ebeb52af882a317c765fccd3ae80589b21a9b8cbf21abb630e46a7"),
+        (5, "1dd0d2a6ae466d665cb26e1a31f07c57ae5df7d2bc559cd5826d417be9141a5d"),
+    ];
+
+    #[test]
+    fn keccak_sorted_trees_with_an_odd_leaf() {
+        let leaves: Vec<Node> = [b"a", b"b", b"c", b"d", b"e"].iter().map(|text| crate::hash::keccak256(*text)).collect();
+        for (count, expected) in KECCAK_ODD_ROOTS {
+            assert_eq!(root(Hasher::Keccak256Sorted, leaves[..count].to_vec()), Some(hex_node(expected)), "{count}");
+        }
+        let five = hex_node(KECCAK_ODD_ROOTS[1].1);
+        let four = root(Hasher::Keccak256Sorted, leaves[..4].to_vec()).unwrap();
+        let cd = root(Hasher::Keccak256Sorted, leaves[2..4].to_vec()).unwrap();
+        // "e" is promoted twice, so its proof is a single node; the others carry it at the top.
+        assert_eq!(verify(Hasher::Keccak256Sorted, &leaves[4], &[four], 0, &five), Some(true));
+        assert_eq!(verify(Hasher::Keccak256Sorted, &leaves[0], &[leaves[1], cd, leaves[4]], 0, &five), Some(true));
+        assert_eq!(verify(Hasher::Keccak256Sorted, &leaves[0], &[leaves[1], cd], 0, &five), Some(false));
+        // The multiproof for "a" and "e" takes the same nodes.
+        assert_eq!(
+            multiproof_verify(Hasher::Keccak256Sorted, &[leaves[0]], &[leaves[1], cd, leaves[4]], &[false, false, false], &five),
+            Some(true)
+        );
+    }
+}
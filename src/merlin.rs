@@ -0,0 +1,114 @@
+//! Merlin transcripts (STROBE-128 over Keccak-f[1600]), restricted to the operations that
+//! Schnorrkel verification needs: appending messages and squeezing challenge bytes.
+
+use crate::hash::keccak_f1600;
+
+const STROBE_R: usize = 166;
+
+const FLAG_I: u8 = 1;
+const FLAG_A: u8 = 1 << 1;
+const FLAG_C: u8 = 1 << 2;
+const FLAG_M: u8 = 1 << 4;
+
+struct Strobe128 {
+    state: [u8; 200],
+    pos: usize,
+    pos_begin: u8,
+}
+
+impl Strobe128 {
+    fn new(protocol_label: &[u8]) -> Self {
+        let mut state = [0u8; 200];
+        state[..6].copy_from_slice(&[1, STROBE_R as u8 + 2, 1, 0, 1, 96]);
+        state[6..18].copy_from_slice(b"STROBEv1.0.2");
+        permute(&mut state);
+        let mut strobe = Strobe128 { state, pos: 0, pos_begin: 0 };
+        strobe.meta_ad(protocol_label, false);
+        strobe
+    }
+
+    fn meta_ad(&mut self, data: &[u8], more: bool) {
+        self.begin_op(FLAG_M | FLAG_A, more);
+        self.absorb(data);
+    }
+
+    fn ad(&mut self, data: &[u8], more: bool) {
+        self.begin_op(FLAG_A, more);
+        self.absorb(data);
+    }
+
+    fn prf(&mut self, out: &mut [u8], more: bool) {
+        self.begin_op(FLAG_I | FLAG_A | FLAG_C, more);
+        self.squeeze(out);
+    }
+
+    fn run_f(&mut self) {
+        self.state[self.pos] ^= self.pos_begin;
+        self.state[self.pos + 1] ^= 0x04;
+        self.state[STROBE_R + 1] ^= 0x80;
+        permute(&mut self.state);
+        self.pos = 0;
+        self.pos_begin = 0;
+    }
+
+    fn absorb(&mut self, data: &[u8]) {
+        for byte in data {
+            self.state[self.pos] ^= byte;
+            self.pos += 1;
+            if self.pos == STROBE_R { self.run_f(); }
+        }
+    }
+
+    fn squeeze(&mut self, out: &mut [u8]) {
+        for byte in out {
+            *byte = self.state[self.pos];
+            self.state[self.pos] = 0;
+            self.pos += 1;
+            if self.pos == STROBE_R { self.run_f(); }
+        }
+    }
+
+    fn begin_op(&mut self, flags: u8, more: bool) {
+        if more { return; }
+        let old_begin = self.pos_begin;
+        self.pos_begin = self.pos as u8 + 1;
+        self.absorb(&[old_begin, flags]);
+        // Operations that produce output start from a fresh permutation.
+        if flags & FLAG_C != 0 && self.pos != 0 { self.run_f(); }
+    }
+}
+
+fn permute(state: &mut [u8; 200]) {
+    let mut lanes = [0u64; 25];
+    for (lane, chunk) in lanes.iter_mut().zip(state.chunks_exact(8)) {
+        *lane = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    keccak_f1600(&mut lanes);
+    for (chunk, lane) in state.chunks_exact_mut(8).zip(lanes) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+}
+
+pub struct Transcript {
+    strobe: Strobe128,
+}
+
+impl Transcript {
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = Transcript { strobe: Strobe128::new(b"Merlin v1.0") };
+        transcript.append_message(b"dom-sep", label);
+        transcript
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.strobe.meta_ad(label, false);
+        self.strobe.meta_ad(&(message.len() as u32).to_le_bytes(), true);
+        self.strobe.ad(message, false);
+    }
+
+    pub fn challenge_bytes(&mut self, label: &[u8], out: &mut [u8]) {
+        self.strobe.meta_ad(label, false);
+        self.strobe.meta_ad(&(out.len() as u32).to_le_bytes(), true);
+        self.strobe.prf(out, false);
+    }
+}
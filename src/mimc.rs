@@ -0,0 +1,145 @@
+//! MiMC-sponge over the BN254 scalar field, as circomlib's `MiMCSponge` (`mimcsponge.circom`,
+//! `circomlibjs`) and Tornado Cash's Merkle tree use it: a 220-round Feistel network with `x^5`
+//! rounds, keyed, absorbing one element per permutation.
+//!
+//! The round constants are a keccak256 chain from the seed `"mimcsponge"`, reduced mod `r`, with
+//! the first and last set to zero.
+
+use alloc::vec::Vec;
+
+use crate::curve::BN254;
+use crate::field::PrimeField;
+use crate::host;
+use crate::uint::U256;
+
+const FR: PrimeField<4> = BN254.scalar;
+
+const ROUNDS: usize = 220;
+const SEED: &[u8] = b"mimcsponge";
+
+/// Bounds the squeezed outputs, each of which costs a permutation.
+pub const MAX_OUTPUTS: u64 = 64;
+
+/// The round constants in Montgomery form.
+fn constants() -> Vec<U256> {
+    let mut constants = Vec::with_capacity(ROUNDS);
+    constants.push(U256::ZERO);
+    let mut hash = [0u8; 32];
+    host::hash_keccak_256(SEED, &mut hash);
+    for _ in 1..ROUNDS - 1 {
+        let previous = hash;
+        host::hash_keccak_256(&previous, &mut hash);
+        constants.push(FR.element_reduced(&U256::from_be_bytes(&hash)));
+    }
+    constants.push(U256::ZERO);
+    constants
+}
+
+/// The Feistel permutation of `(xl, xr)` under `key`; the halves swap after every round but
+/// the last.
+fn permute(constants: &[U256], xl: &mut U256, xr: &mut U256, key: &U256) {
+    for (i, c) in constants.iter().enumerate() {
+        let t = FR.add(&FR.add(xl, key), c);
+        let t5 = FR.mul(&FR.square(&FR.square(&t)), &t);
+        let mixed = FR.add(xr, &t5);
+        if i < ROUNDS - 1 {
+            *xr = *xl;
+            *xl = mixed;
+        } else {
+            *xr = mixed;
+        }
+    }
+}
+
+/// circomlib's `multiHash`: each input is added to the rate element before a permutation, then
+/// `outputs` rate elements are squeezed, permuting between them. `None` if an input or the key
+/// is not below `r`, or `outputs` is not in `1..=MAX_OUTPUTS`.
+pub fn sponge(inputs: &[U256], key: &U256, outputs: u64) -> Option<Vec<U256>> {
+    if outputs == 0 || outputs > MAX_OUTPUTS { return None; }
+    let key = FR.element(key)?;
+    let constants = constants();
+    let (mut rate, mut capacity) = (U256::ZERO, U256::ZERO);
+    for input in inputs {
+        rate = FR.add(&rate, &FR.element(input)?);
+        permute(&constants, &mut rate, &mut capacity, &key);
+    }
+    let mut squeezed = Vec::with_capacity(outputs as usize);
+    squeezed.push(FR.canonical(&rate));
+    for _ in 1..outputs {
+        permute(&constants, &mut rate, &mut capacity, &key);
+        squeezed.push(FR.canonical(&rate));
+    }
+    Some(squeezed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fr(text: &str) -> U256 {
+        U256::from_be_hex(text)
+    }
+
+    /// The first four levels of Tornado Cash's `MerkleTreeWithHistory.zeros()`: the leaf is
+    /// `keccak256("tornado") mod r` and each level is `hashLeftRight(z, z)`, i.e.
+    /// `multiHash([z, z], 0, 1)` against the deployed circomlib `MiMCSponge` contract.
+    const TORNADO_ZEROS: [&str; 4] = [
+        "2fe54c60d3acabf3343a35b6eba15db4821b340f76e741e2249685ed4899af6c",
+        "256a6135777eee2fd26f54b8b7037a25439d5235caee224154186d2b8a52e31d",
+        "1151949895e82ab19924de92c40a3d6f7bcb60d92b00504b8199613683f0c200",
+        "20121ee811489ff8d61f09fb89e313f14959a0f28bb428a20dba6b0b068b3bdb",
+    ];
+
+    /// `multiHash([1, 2], 0, 3)`, the case circomlib's `mimcspongecircuit` test runs. The first
+    /// output is the value circomlib publishes; the other two are pinned here and tied to it by
+    /// the squeeze rule below.
+    const ONE_TWO_THREE_OUTPUTS: [&str; 3] = [
+        "2bcea035a1251603f1ceaf73cd4ae89427c47075bb8e3a944039ff1e3d6d2a6f",
+        "2f7d340a3c24b8ef9899ab5f019b85b87354c7f6c965a19ca090321f7e5425e9",
+        "0cf71423c39e70b9858eaa8e1dc3ac40a09c3927dc31d12014af16066f2bdcb6",
+    ];
+
+    #[test]
+    fn tornado_cash_zero_hashes() {
+        let mut hash = [0u8; 32];
+        host::hash_keccak_256(b"tornado", &mut hash);
+        let leaf = U256::from_be_bytes(&hash).div_rem(&FR.modulus).unwrap().1;
+        assert_eq!(leaf, fr(TORNADO_ZEROS[0]));
+        for level in TORNADO_ZEROS.windows(2) {
+            let zero = fr(level[0]);
+            assert_eq!(sponge(&[zero, zero], &U256::ZERO, 1), Some(alloc::vec![fr(level[1])]));
+        }
+    }
+
+    #[test]
+    fn multi_hash_with_several_outputs() {
+        let inputs = [U256::from_u64(1), U256::from_u64(2)];
+        let expected: Vec<U256> = ONE_TWO_THREE_OUTPUTS.iter().map(|text| fr(text)).collect();
+        assert_eq!(sponge(&inputs, &U256::ZERO, 3), Some(expected.clone()));
+        assert_eq!(sponge(&inputs, &U256::ZERO, 1), Some(expected[..1].to_vec()));
+        // Each further output is one more permutation, the same as absorbing a zero.
+        let padded = [inputs[0], inputs[1], U256::ZERO, U256::ZERO];
+        for (outputs, expected) in expected.iter().enumerate() {
+            assert_eq!(sponge(&padded[..2 + outputs], &U256::ZERO, 1), Some(alloc::vec![*expected]));
+        }
+        let all = sponge(&inputs, &U256::ZERO, MAX_OUTPUTS).unwrap();
+        assert_eq!(all.len() as u64, MAX_OUTPUTS);
+        assert_eq!(all[..3], expected[..]);
+    }
+
+    #[test]
+    fn keys_and_bounds() {
+        let inputs = [U256::from_u64(1), U256::from_u64(2)];
+        let unkeyed = sponge(&inputs, &U256::ZERO, 1).unwrap();
+        let keyed = sponge(&inputs, &U256::from_u64(3), 1).unwrap();
+        assert_ne!(keyed, unkeyed);
+        assert_eq!(sponge(&inputs, &FR.modulus.wrapping_sub(&U256::ONE), 1).map(|out| out.len()), Some(1));
+        // With no input the output is the permutation of zero.
+        assert!(sponge(&[], &U256::ZERO, 1).is_some());
+
+        assert_eq!(sponge(&inputs, &FR.modulus, 1), None);
+        assert_eq!(sponge(&[U256::ONE, FR.modulus], &U256::ZERO, 1), None);
+        assert_eq!(sponge(&inputs, &U256::ZERO, 0), None);
+        assert_eq!(sponge(&inputs, &U256::ZERO, MAX_OUTPUTS + 1), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! Merkle Mountain Range leaf proofs, as generated by Substrate's `pallet-mmr` (the
+//! `ckb-merkle-mountain-range` layout) and checked by BEEFY light clients.
+//!
+//! `n` leaves form one perfect tree per set bit of `n`, largest first, and the root bags their
+//! peaks from the right: `H(right || left)` until one remains. A proof lists the peaks left of
+//! the leaf's tree, the siblings inside it from the bottom up, and then the peaks to its right,
+//! usually already bagged into one node.
+
+use alloc::vec::Vec;
+
+use crate::merkle::{Node, NodeHash};
+
+/// Whether `proof` shows `leaf` at `leaf_index` among `leaf_count` leaves under `root`. `None`
+/// if the index is out of range or the proof is short of siblings or has nodes to spare.
+pub fn verify_leaf(hash: NodeHash, root: &Node, leaf: &Node, leaf_index: u64, leaf_count: u64, proof: &[Node]) -> Option<bool> {
+    if leaf_index >= leaf_count { return None; }
+    let mut items = proof.iter();
+    let mut peaks: Vec<Node> = Vec::with_capacity(leaf_count.count_ones() as usize + 1);
+    let mut first_leaf = 0u64;
+    let mut included = false;
+    for height in (0..64).rev().filter(|&h| leaf_count >> h & 1 == 1) {
+        let size = 1u64 << height;
+        let peak = if (first_leaf..first_leaf + size).contains(&leaf_index) {
+            let offset = leaf_index - first_leaf;
+            let mut node = *leaf;
+            for level in 0..height {
+                let sibling = items.next()?;
+                node = if offset >> level & 1 == 1 { hash.merge(sibling, &node) } else { hash.merge(&node, sibling) };
+            }
+            included = true;
+            node
+        } else {
+            // Past the leaf's tree, a single node may stand for all the peaks to its right,
+            // bagged; the proof then ends early.
+            match items.next() {
+                Some(peak) => *peak,
+                None => break,
+            }
+        };
+        peaks.push(peak);
+        first_leaf += size;
+    }
+    if !included || items.next().is_some() { return None; }
+
+    let mut bagged = peaks.pop()?;
+    while let Some(left) = peaks.pop() {
+        bagged = hash.merge(&bagged, &left);
+    }
+    Some(bagged == *root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(text: &str) -> Node {
+        crate::hex::decode(text.as_bytes()).unwrap().try_into().unwrap()
+    }
+
+    /// A leaf index and its proof.
+    type Proof = (u64, &'static [&'static str]);
+
+    /// Roots and leaf proofs from `ckb-merkle-mountain-range` 0.6: `(hash, leaf count, root,
+    /// proofs)`, where leaf `i` is the hash of the byte `i`.
+    const PROOFS: [(NodeHash, u64, &str, &[Proof]); 4] = [
+        // Peaks of 8, 2 and 1 leaves.
+        (NodeHash::Keccak256, 11, "59d5dfabff33ea4520e961149dda2e67ba134bc1fef4f5b50201293627c921c1", &[
+            // Three siblings, then the two peaks to the right bagged into one.
+            (3, &[
+                "f2ee15ea639b73fa3db9b34a245bdfa015c260c598b211bf05a1ecc4b3e3b4f2",
+                "57d772147cdf27f5f67d679f0f3a513f8b87622ce598a3cf0b048ab178ddfc6e",
+                "f9d70df2915506deac3e2cbd36624c90fe2a7f6bdc9599b837377b725bffa243",
+                "0d70c071e6befa647914fab16565d46dd4c12cf340a8a2cd0af5513251c77ef5",
+            ]),
+            // A left peak, a sibling and a single right peak.
+            (9, &[
+                "791521f02a712f28265f5200914f9772b133bc2692260f8c8f426e176b1713ed",
+                "d33e25809fcaa2b6900567812852539da8559dc8b76a7ce3fc5ddd77e8d19a69",
+                "0ef9d8f8804d174666011a394cab7901679a8944d24249fd148a6a36071151f8",
+            ]),
+            // The last leaf is a peak of its own: two left peaks and nothing else.
+            (10, &[
+                "791521f02a712f28265f5200914f9772b133bc2692260f8c8f426e176b1713ed",
+                "d8173b90a730df1ca0307cb022b98433ff2ba83f6bf76bab48733f36a5ffd184",
+            ]),
+        ]),
+        // Peaks of 8, 4, 2 and 1 leaves.
+        (NodeHash::Keccak256, 15, "7b3914d11d0e865a09d32553e1195b5017cc719f1988d81020316f05070970e0", &[
+            (0, &[
+                "5fe7f977e71dba2ea1a68e21057beebb9be2ac30c6410aa38d4f3fbe41dcffd2",
+                "2b07d07815e57c23883128aa268a683b3b39aca921fa5f247e9a30c4035d7107",
+                "f9d70df2915506deac3e2cbd36624c90fe2a7f6bdc9599b837377b725bffa243",
+                "fee082fe37a8d29e5dabe4f11f8c5b6dac9b0305ee0b5fe61168e1940bf88ea9",
+            ]),
+            (9, &[
+                "791521f02a712f28265f5200914f9772b133bc2692260f8c8f426e176b1713ed",
+                "d33e25809fcaa2b6900567812852539da8559dc8b76a7ce3fc5ddd77e8d19a69",
+                "905be01e25a661e3918a53a7206cb16ad11eb184bb53275f975607e8f245ad6e",
+                "4b1497e27f1b334e7f081cf09bad00624b968544870c031a2272fc44fbd2c3d7",
+            ]),
+            (14, &[
+                "791521f02a712f28265f5200914f9772b133bc2692260f8c8f426e176b1713ed",
+                "ca2127208621b3a451e6b026fdbf83cb3550f363608b7baacc6a573ccbe6d9b5",
+                "c4190c5ad27401fb41ad418e06f19aa3ce47011a031bd91c65959c7b681ce6e9",
+            ]),
+        ]),
+        // A single leaf is its own root.
+        (NodeHash::Keccak256, 1, "bc36789e7a1e281436464229828f817d6612f7b477d66591ff96a9e064bcc98a", &[(0, &[])]),
+        (NodeHash::Blake2b256, 7, "8acd64bb3be4ac6282e04def23d8a832b7460617b1d9107bb04157f243d63cc2", &[
+            (2, &[
+                "e88bd757ad5b9bedf372d8d3f0cf6c962a469db61a265f6418e1ffed86da29ec",
+                "52c98fcb9a132e1e7acb398852d7c4c8023a112ac2d511761f6705079d07e443",
+                "a3a6914c0f9da911e56bbc2ca19dd630031f734946ae54b96c9a3e2d3c5341a7",
+            ]),
+            (5, &[
+                "c021171f1f623b208ab7dc3b1ba2716c6ae2aa8d0e248f013d00d829e03f736e",
+                "642206314f534b29ad297d82440a5f9f210e30ca5ced805a587ca402de927342",
+                "bfa726c3c149165b108e6ff550cb1a1c4f0fdc2e9f26a9a16f48babe73b600ce",
+            ]),
+        ]),
+    ];
+
+    fn leaf_node(hash: NodeHash, index: u64) -> Node {
+        let mut node = [0u8; 32];
+        match hash {
+            NodeHash::Keccak256 => crate::host::hash_keccak_256(&[index as u8], &mut node),
+            NodeHash::Blake2b256 => crate::host::hash_blake2_256(&[index as u8], &mut node),
+        }
+        node
+    }
+
+    #[test]
+    fn ckb_proofs() {
+        for (hash, leaf_count, root, proofs) in PROOFS {
+            let root = node(root);
+            for &(leaf_index, proof) in proofs {
+                let proof: Vec<Node> = proof.iter().map(|item| node(item)).collect();
+                let leaf = leaf_node(hash, leaf_index);
+                assert_eq!(verify_leaf(hash, &root, &leaf, leaf_index, leaf_count, &proof), Some(true), "leaf {leaf_index} of {leaf_count}");
+
+                let other = leaf_index ^ 1;
+                if other < leaf_count {
+                    assert_ne!(verify_leaf(hash, &root, &leaf_node(hash, other), leaf_index, leaf_count, &proof), Some(true));
+                }
+                for tampered in 0..proof.len() {
+                    let mut proof = proof.clone();
+                    proof[tampered][31] ^= 1;
+                    assert_eq!(verify_leaf(hash, &root, &leaf, leaf_index, leaf_count, &proof), Some(false));
+                }
+                let mut longer = proof.clone();
+                longer.push(root);
+                assert_ne!(verify_leaf(hash, &root, &leaf, leaf_index, leaf_count, &longer), Some(true));
+                if let Some((_, shorter)) = proof.split_last() {
+                    assert_ne!(verify_leaf(hash, &root, &leaf, leaf_index, leaf_count, shorter), Some(true));
+                }
+                assert_eq!(verify_leaf(hash, &root, &leaf, leaf_count, leaf_count, &proof), None);
+            }
+        }
+    }
+
+    #[test]
+    fn right_peaks_may_be_given_unbagged() {
+        // Leaf 0 of 15, with the peaks of 4, 2 and 1 leaves in place of their bag.
+        let hash = NodeHash::Keccak256;
+        let leaves: Vec<Node> = (0..15).map(|index| leaf_node(hash, index)).collect();
+        let pair = |left: &Node, right: &Node| hash.merge(left, right);
+        let peak4 = pair(&pair(&leaves[8], &leaves[9]), &pair(&leaves[10], &leaves[11]));
+        let peak2 = pair(&leaves[12], &leaves[13]);
+        let (_, _, root, proofs) = PROOFS[1];
+        let (_, bagged) = proofs[0];
+        let mut proof: Vec<Node> = bagged[..3].iter().map(|item| node(item)).collect();
+        assert_eq!(pair(&pair(&leaves[14], &peak2), &peak4), node(bagged[3]));
+        proof.extend([peak4, peak2, leaves[14]]);
+        assert_eq!(verify_leaf(hash, &node(root), &leaves[0], 0, 15, &proof), Some(true));
+        assert_eq!(verify_leaf(hash, &node(root), &leaves[0], 0, 15, &[[0; 32]; 2]), None);
+        // Once every peak is accounted for, a further node has nothing to stand for.
+        proof.push(leaves[14]);
+        assert_eq!(verify_leaf(hash, &node(root), &leaves[0], 0, 15, &proof), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Multi-scalar multiplication `k_1 * P_1 + ... + k_n * P_n` by Pippenger's bucket method.
+//!
+//! Scalars are cut into `c`-bit windows. Per window, every point is added once into the bucket
+//! of its digit, and the buckets are combined with two running sums, so a window costs about
+//! `n + 2^(c+1)` additions instead of the `n * c` doublings of separate multiplications.
+//!
+//! The heap never frees, so the one bucket array is allocated up front and reused by every
+//! window, and `c` is capped at `MAX_WINDOW` to bound its size.
+
+use alloc::vec;
+
+use crate::uint::Uint;
+
+/// Caps the bucket array at `2^8 - 1` points.
+const MAX_WINDOW: u32 = 8;
+
+/// The window width minimizing `ceil(bits / c) * (n + 2^(c+1))`.
+fn window_size(n: usize, bits: u32) -> u32 {
+    let cost = |c: u32| bits.div_ceil(c) as usize * (n + (1 << (c + 1)));
+    (1..=MAX_WINDOW).min_by_key(|&c| cost(c)).unwrap_or(1)
+}
+
+/// The `width`-bit digit of `k` starting at bit `start`.
+fn digit<const N: usize>(k: &Uint<N>, start: u32, width: u32) -> usize {
+    (0..width).filter(|&j| k.bit(start + j)).fold(0, |acc, j| acc | 1 << j)
+}
+
+/// Computes the sum with the group given by `identity`, `add` and `double`, which may fail
+/// (as affine arithmetic modulo a composite does). Extra points or scalars are ignored.
+pub fn pippenger<P: Copy, const N: usize>(
+    points: &[P],
+    scalars: &[Uint<N>],
+    identity: P,
+    add: impl Fn(&P, &P) -> Option<P>,
+    double: impl Fn(&P) -> Option<P>,
+) -> Option<P> {
+    let bits = scalars.iter().map(Uint::bits).max().unwrap_or(0);
+    if bits == 0 { return Some(identity); }
+    let c = window_size(points.len().min(scalars.len()), bits);
+    let mut buckets = vec![identity; (1 << c) - 1];
+    let mut acc = identity;
+    for window in (0..bits.div_ceil(c)).rev() {
+        for _ in 0..c {
+            acc = double(&acc)?;
+        }
+        buckets.fill(identity);
+        for (point, k) in points.iter().zip(scalars) {
+            let d = digit(k, window * c, c);
+            if d != 0 { buckets[d - 1] = add(&buckets[d - 1], point)?; }
+        }
+        // sum_d d * B_d, as the sum of the running suffix sums B_top + ... + B_d.
+        let (mut running, mut sum) = (identity, identity);
+        for bucket in buckets.iter().rev() {
+            running = add(&running, bucket)?;
+            sum = add(&sum, &running)?;
+        }
+        acc = add(&acc, &sum)?;
+    }
+    Some(acc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{JacobianPoint, SECP256K1};
+    use crate::uint::U256;
+    use crate::{is_on_curve, point_add, point_double, scalar_mul_wnaf, Point, Xorshift64Star};
+    use alloc::vec::Vec;
+
+    /// `y^2 = x^3 + 2x + 3 mod 97`.
+    const A: u64 = 2;
+    const MODULUS: u64 = 97;
+
+    fn small_points() -> Vec<Point> {
+        (0..MODULUS)
+            .flat_map(|x| (0..MODULUS).map(move |y| Point::Coordinate { x, y }))
+            .filter(|&p| is_on_curve(p, A, 3, MODULUS))
+            .collect()
+    }
+
+    fn small_msm(points: &[Point], scalars: &[u64]) -> Option<Point> {
+        let scalars: Vec<Uint<1>> = scalars.iter().map(|&k| Uint::from_u64(k)).collect();
+        let add = |p: &Point, q: &Point| point_add(*p, *q, A, MODULUS);
+        let double = |p: &Point| point_double(*p, A, MODULUS);
+        pippenger(points, &scalars, Point::Infinity, add, double)
+    }
+
+    /// The sum of separate multiplications, over the shorter of the two slices.
+    fn small_reference(points: &[Point], scalars: &[u64]) -> Point {
+        points.iter().zip(scalars).fold(Point::Infinity, |acc, (&p, &k)| {
+            point_add(acc, scalar_mul_wnaf(p, k, A, MODULUS).unwrap(), A, MODULUS).unwrap()
+        })
+    }
+
+    #[test]
+    fn matches_separate_multiplications_on_a_small_curve() {
+        let points = small_points();
+        let mut rng = Xorshift64Star::new(38);
+        // Every count up to past the first few window widths, scalars up to the full 64 bits.
+        for n in 0..=40 {
+            let points: Vec<Point> = (0..n).map(|_| points[rng.next() as usize % points.len()]).collect();
+            for shift in [0, 8, 32, 63] {
+                let scalars: Vec<u64> = (0..n).map(|_| rng.next() >> shift).collect();
+                assert_eq!(small_msm(&points, &scalars), Some(small_reference(&points, &scalars)), "n = {n}");
+            }
+        }
+    }
+
+    #[test]
+    fn window_boundaries() {
+        let points = small_points();
+        // The counts at which the window widens, checked on both sides.
+        let widths: Vec<usize> = (1..300).filter(|&n| window_size(n - 1, 64) != window_size(n, 64)).collect();
+        assert!(widths.len() >= 3);
+        for n in widths.into_iter().flat_map(|n| [n - 1, n]) {
+            let c = window_size(n, 64);
+            let points: Vec<Point> = points.iter().cycle().take(n).copied().collect();
+            // Scalars whose length is a whole number of windows, all-ones digits, and the
+            // top bit alone.
+            for k in [u64::MAX >> (64 % c), (1 << c) - 1, 1 << c, 1 << 63, u64::MAX] {
+                let scalars = vec![k; n];
+                assert_eq!(small_msm(&points, &scalars), Some(small_reference(&points, &scalars)), "n = {n}, k = {k:#x}");
+            }
+        }
+    }
+
+    #[test]
+    fn zero_scalars_and_mismatched_lengths() {
+        let points = small_points();
+        assert_eq!(small_msm(&[], &[]), Some(Point::Infinity));
+        assert_eq!(small_msm(&points[..5], &[0; 5]), Some(Point::Infinity));
+        assert_eq!(small_msm(&points[..5], &[]), Some(Point::Infinity));
+        assert_eq!(small_msm(&[], &[1, 2, 3]), Some(Point::Infinity));
+        let scalars = [0, 5, 0, 0, 96, 0];
+        assert_eq!(small_msm(&points[..6], &scalars), Some(small_reference(&points[..6], &scalars)));
+        // Extra points or scalars are ignored.
+        let scalars = [3, 1, 4, 1, 5];
+        let expected = Some(small_reference(&points[..3], &scalars[..3]));
+        assert_eq!(small_msm(&points[..3], &scalars), expected);
+        assert_eq!(small_msm(&points[..10], &scalars[..3]), expected);
+        // A scalar that only pairs with a missing point still sets the window count.
+        assert_eq!(small_msm(&points[..2], &[1, 1, u64::MAX]), Some(small_reference(&points[..2], &[1, 1])));
+    }
+
+    #[test]
+    fn failing_group_operations_propagate() {
+        let fail = |_: &u8, _: &u8| None;
+        let double = |p: &u8| Some(*p);
+        assert_eq!(pippenger(&[1], &[Uint::<1>::from_u64(0)], 0, fail, double), Some(0));
+        assert_eq!(pippenger(&[1], &[Uint::<1>::from_u64(1)], 0, fail, double), None);
+    }
+
+    #[test]
+    fn matches_separate_multiplications_on_secp256k1() {
+        let curve = &SECP256K1;
+        let g = curve.to_jacobian(&curve.generator).unwrap();
+        let mut rng = Xorshift64Star::new(256);
+        let mut random_scalar = || Uint([rng.next(), rng.next(), rng.next(), rng.next()]);
+        let points: Vec<JacobianPoint> = (0..20).map(|_| curve.scalar_mul(&g, &random_scalar())).collect();
+        let add = |p: &JacobianPoint, q: &JacobianPoint| Some(curve.add(p, q));
+        let double = |p: &JacobianPoint| Some(curve.double(p));
+        for n in [1, 2, 7, 20] {
+            let mut scalars: Vec<U256> = (0..n).map(|_| random_scalar()).collect();
+            // A zero scalar, the group order (which multiplies to infinity) and `n - 1`.
+            if n >= 7 {
+                scalars[1] = U256::ZERO;
+                scalars[3] = curve.scalar.modulus;
+                scalars[5] = curve.scalar.modulus.wrapping_sub(&U256::ONE);
+            }
+            let expected = points.iter().zip(&scalars)
+                .fold(JacobianPoint::INFINITY, |acc, (p, k)| curve.add(&acc, &curve.scalar_mul(p, k)));
+            let sum = pippenger(&points[..n], &scalars, JacobianPoint::INFINITY, add, double).unwrap();
+            assert_eq!(curve.to_affine(&sum), curve.to_affine(&expected), "n = {n}");
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! G2 groups on sextic twists `y^2 = x^3 + b'` over `Fp2`, and the line functions of the
+//! optimal-ate Miller loop shared by pairing-friendly curves.
+//!
+//! G2 arithmetic uses Jacobian coordinates like `curve.rs`. The Miller loop instead keeps the
+//! running multiple of `Q` in homogeneous projective coordinates, where doubling and addition
+//! steps also yield the tangent and chord lines without inversions (ePrint 2013/722).
+
+use crate::tower::{Fp12, Fp2, Fp2Hex, Tower};
+use crate::uint::{Uint, U256};
+
+/// Whether the twist is divisive (`b' = b / xi`) or multiplicative (`b' = b * xi`), which
+/// decides where line coefficients land in `Fp12`.
+pub enum TwistType {
+    D,
+    M,
+}
+
+pub struct G2Curve<const L: usize> {
+    pub tower: Tower<L>,
+    /// The twist coefficient `b'`, in Montgomery form.
+    pub b: Fp2<L>,
+    /// The prime order `r` of the G2 subgroup.
+    pub order: U256,
+    pub twist: TwistType,
+    /// `1/2` in Montgomery form, for the doubling step.
+    two_inv: Uint<L>,
+}
+
+impl<const L: usize> G2Curve<L> {
+    pub const fn new(tower: Tower<L>, b: Fp2Hex, order: &str, two_inv: &str, twist: TwistType) -> Self {
+        let b = crate::tower::fp2_from_hex(&tower.fp, b);
+        let two_inv = tower.fp.element_reduced(&Uint::from_be_hex(two_inv));
+        G2Curve { tower, b, order: U256::from_be_hex(order), twist, two_inv }
+    }
+}
+
+/// An affine G2 point with canonical `Fp2` coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum G2Affine<const L: usize> {
+    Infinity,
+    Coordinate { x: Fp2<L>, y: Fp2<L> },
+}
+
+/// A G2 point in Jacobian coordinates; `z == 0` is the point at infinity.
+#[derive(Debug, Clone, Copy)]
+pub struct G2Jacobian<const L: usize> {
+    pub x: Fp2<L>,
+    pub y: Fp2<L>,
+    pub z: Fp2<L>,
+}
+
+impl<const L: usize> G2Jacobian<L> {
+    pub const INFINITY: Self = G2Jacobian { x: Fp2::ZERO, y: Fp2::ZERO, z: Fp2::ZERO };
+
+    pub fn is_infinity(&self) -> bool { self.z.is_zero() }
+}
+
+impl<const L: usize> G2Curve<L> {
+    /// Lifts an affine point, or `None` if a coordinate is not a field element or the point
+    /// is not on the twist. Subgroup membership is checked separately by `is_in_subgroup`.
+    pub fn to_jacobian(&self, p: &G2Affine<L>) -> Option<G2Jacobian<L>> {
+        let t = &self.tower;
+        match p {
+            G2Affine::Infinity => Some(G2Jacobian::INFINITY),
+            G2Affine::Coordinate { x, y } => {
+                let x = t.fp2_element(x)?;
+                let y = t.fp2_element(y)?;
+                let rhs = t.fp2_add(&t.fp2_mul(&t.fp2_square(&x), &x), &self.b);
+                if t.fp2_square(&y) != rhs { return None; }
+                Some(G2Jacobian { x, y, z: t.fp2_one() })
+            }
+        }
+    }
+
+    pub fn to_affine(&self, p: &G2Jacobian<L>) -> G2Affine<L> {
+        let t = &self.tower;
+        let Some(z_inv) = t.fp2_inv(&p.z) else { return G2Affine::Infinity };
+        let z_inv2 = t.fp2_square(&z_inv);
+        let x = t.fp2_mul(&p.x, &z_inv2);
+        let y = t.fp2_mul(&p.y, &t.fp2_mul(&z_inv2, &z_inv));
+        G2Affine::Coordinate { x: t.fp2_canonical(&x), y: t.fp2_canonical(&y) }
+    }
+
+    /// Doubling ("dbl-2009-l", for `a = 0`).
+    pub fn double(&self, p: &G2Jacobian<L>) -> G2Jacobian<L> {
+        let t = &self.tower;
+        if p.is_infinity() || p.y.is_zero() { return G2Jacobian::INFINITY; }
+        let a = t.fp2_square(&p.x);
+        let b = t.fp2_square(&p.y);
+        let c = t.fp2_square(&b);
+        let d = t.fp2_double(&t.fp2_sub(&t.fp2_sub(&t.fp2_square(&t.fp2_add(&p.x, &b)), &a), &c));
+        let e = t.fp2_add(&t.fp2_double(&a), &a);
+        let x = t.fp2_sub(&t.fp2_square(&e), &t.fp2_double(&d));
+        let eight_c = t.fp2_double(&t.fp2_double(&t.fp2_double(&c)));
+        let y = t.fp2_sub(&t.fp2_mul(&e, &t.fp2_sub(&d, &x)), &eight_c);
+        let z = t.fp2_double(&t.fp2_mul(&p.y, &p.z));
+        G2Jacobian { x, y, z }
+    }
+
+    pub fn add(&self, p: &G2Jacobian<L>, q: &G2Jacobian<L>) -> G2Jacobian<L> {
+        let t = &self.tower;
+        if p.is_infinity() { return *q; }
+        if q.is_infinity() { return *p; }
+        let z1z1 = t.fp2_square(&p.z);
+        let z2z2 = t.fp2_square(&q.z);
+        let u1 = t.fp2_mul(&p.x, &z2z2);
+        let u2 = t.fp2_mul(&q.x, &z1z1);
+        let s1 = t.fp2_mul(&p.y, &t.fp2_mul(&q.z, &z2z2));
+        let s2 = t.fp2_mul(&q.y, &t.fp2_mul(&p.z, &z1z1));
+        let h = t.fp2_sub(&u2, &u1);
+        let r = t.fp2_sub(&s2, &s1);
+        if h.is_zero() {
+            return if r.is_zero() { self.double(p) } else { G2Jacobian::INFINITY };
+        }
+        let hh = t.fp2_square(&h);
+        let hhh = t.fp2_mul(&h, &hh);
+        let v = t.fp2_mul(&u1, &hh);
+        let x = t.fp2_sub(&t.fp2_sub(&t.fp2_square(&r), &hhh), &t.fp2_double(&v));
+        let y = t.fp2_sub(&t.fp2_mul(&r, &t.fp2_sub(&v, &x)), &t.fp2_mul(&s1, &hhh));
+        let z = t.fp2_mul(&t.fp2_mul(&p.z, &q.z), &h);
+        G2Jacobian { x, y, z }
+    }
+
+    /// `k * p` by double-and-add from the top bit; `k` may be wider than the group order, as
+    /// cofactor clearing needs.
+    pub fn scalar_mul<const N: usize>(&self, p: &G2Jacobian<L>, k: &Uint<N>) -> G2Jacobian<L> {
+        let mut acc = G2Jacobian::INFINITY;
+        for i in (0..k.bits()).rev() {
+            acc = self.double(&acc);
+            if k.bit(i) { acc = self.add(&acc, p); }
+        }
+        acc
+    }
+
+    /// Twists have cofactors, so points on the curve must also be checked to have order `r`.
+    pub fn is_in_subgroup(&self, p: &G2Jacobian<L>) -> bool {
+        self.scalar_mul(p, &self.order).is_infinity()
+    }
+}
+
+// --- Miller loop ---
+
+/// The three `Fp2` coefficients of a line, in the order the twist's sparse product takes them
+/// once scaled by `P`: slots 0, 3 and 4 of `Fp12` for D-type twists, 0, 1 and 4 for M-type.
+pub type LineCoeffs<const L: usize> = (Fp2<L>, Fp2<L>, Fp2<L>);
+
+/// One `(P, Q)` term of a multi-pairing, with `P` in G1 and `Q` in G2 as affine Montgomery-form
+/// coordinates, and the running multiple `T` of `Q` in homogeneous projective coordinates.
+pub struct MillerPair<const L: usize> {
+    pub px: Uint<L>,
+    pub py: Uint<L>,
+    pub qx: Fp2<L>,
+    pub qy: Fp2<L>,
+    tx: Fp2<L>,
+    ty: Fp2<L>,
+    tz: Fp2<L>,
+}
+
+impl<const L: usize> G2Curve<L> {
+    /// Starts a Miller loop term with `T = Q`.
+    pub fn miller_pair(&self, px: Uint<L>, py: Uint<L>, qx: Fp2<L>, qy: Fp2<L>) -> MillerPair<L> {
+        MillerPair { px, py, qx, qy, tx: qx, ty: qy, tz: self.tower.fp2_one() }
+    }
+
+    /// `T = 2T`, returning the tangent line at `T`.
+    fn doubling_step(&self, pair: &mut MillerPair<L>) -> LineCoeffs<L> {
+        let t = &self.tower;
+        let a = t.fp2_mul_fp(&t.fp2_mul(&pair.tx, &pair.ty), &self.two_inv);
+        let b = t.fp2_square(&pair.ty);
+        let c = t.fp2_square(&pair.tz);
+        let e = t.fp2_mul(&self.b, &t.fp2_add(&t.fp2_double(&c), &c));
+        let f = t.fp2_add(&t.fp2_double(&e), &e);
+        let g = t.fp2_mul_fp(&t.fp2_add(&b, &f), &self.two_inv);
+        let h = t.fp2_sub(&t.fp2_square(&t.fp2_add(&pair.ty, &pair.tz)), &t.fp2_add(&b, &c));
+        let i = t.fp2_sub(&e, &b);
+        let j = t.fp2_square(&pair.tx);
+        let e_square = t.fp2_square(&e);
+        pair.tx = t.fp2_mul(&a, &t.fp2_sub(&b, &f));
+        pair.ty = t.fp2_sub(&t.fp2_square(&g), &t.fp2_add(&t.fp2_double(&e_square), &e_square));
+        pair.tz = t.fp2_mul(&b, &h);
+        let three_j = t.fp2_add(&t.fp2_double(&j), &j);
+        match self.twist {
+            TwistType::D => (t.fp2_neg(&h), three_j, i),
+            TwistType::M => (i, three_j, t.fp2_neg(&h)),
+        }
+    }
+
+    /// `T = T + (qx, qy)`, returning the line through both points.
+    fn addition_step(&self, pair: &mut MillerPair<L>, qx: &Fp2<L>, qy: &Fp2<L>) -> LineCoeffs<L> {
+        let t = &self.tower;
+        let theta = t.fp2_sub(&pair.ty, &t.fp2_mul(qy, &pair.tz));
+        let lambda = t.fp2_sub(&pair.tx, &t.fp2_mul(qx, &pair.tz));
+        let c = t.fp2_square(&theta);
+        let d = t.fp2_square(&lambda);
+        let e = t.fp2_mul(&lambda, &d);
+        let f = t.fp2_mul(&pair.tz, &c);
+        let g = t.fp2_mul(&pair.tx, &d);
+        let h = t.fp2_sub(&t.fp2_add(&e, &f), &t.fp2_double(&g));
+        pair.tx = t.fp2_mul(&lambda, &h);
+        pair.ty = t.fp2_sub(&t.fp2_mul(&theta, &t.fp2_sub(&g, &h)), &t.fp2_mul(&e, &pair.ty));
+        pair.tz = t.fp2_mul(&pair.tz, &e);
+        let j = t.fp2_sub(&t.fp2_mul(&theta, qx), &t.fp2_mul(&lambda, qy));
+        match self.twist {
+            TwistType::D => (lambda, t.fp2_neg(&theta), j),
+            TwistType::M => (j, t.fp2_neg(&theta), lambda),
+        }
+    }
+
+    /// Multiplies `f` by a line evaluated at `P`.
+    fn ell(&self, f: &Fp12<L>, (c0, c1, c2): LineCoeffs<L>, px: &Uint<L>, py: &Uint<L>) -> Fp12<L> {
+        let t = &self.tower;
+        let c1 = t.fp2_mul_fp(&c1, px);
+        match self.twist {
+            TwistType::D => t.fp12_mul_by_034(f, &t.fp2_mul_fp(&c0, py), &c1, &c2),
+            TwistType::M => t.fp12_mul_by_014(f, &c0, &c1, &t.fp2_mul_fp(&c2, py)),
+        }
+    }
+
+    /// Doubles the pair's `T` and multiplies the tangent line into `f`.
+    pub fn line_double(&self, f: &Fp12<L>, pair: &mut MillerPair<L>) -> Fp12<L> {
+        let coeffs = self.doubling_step(pair);
+        self.ell(f, coeffs, &pair.px, &pair.py)
+    }
+
+    /// Adds `(qx, qy)` to the pair's `T` and multiplies the chord into `f`.
+    pub fn line_add(&self, f: &Fp12<L>, pair: &mut MillerPair<L>, qx: &Fp2<L>, qy: &Fp2<L>) -> Fp12<L> {
+        let coeffs = self.addition_step(pair, qx, qy);
+        self.ell(f, coeffs, &pair.px, &pair.py)
+    }
+}
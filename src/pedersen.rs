@@ -0,0 +1,94 @@
+//! Pedersen commitments `C = v * G + r * H` on secp256k1.
+//!
+//! Both generators come from hashing a fixed tag onto the curve, so no one knows the discrete
+//! log of `H` to base `G`: opening a commitment to two different values would reveal it.
+//! Commitments add like their openings, `C(v1, r1) + C(v2, r2) = C(v1 + v2, r1 + r2)`, which is
+//! what balance and range-proof arguments build on.
+
+use crate::curve::{AffinePoint, JacobianPoint, SECP256K1};
+use crate::uint::U256;
+
+/// `G`, hashed onto the curve from the tag `RustPedersen/G` (see `tests::hash_to_curve`).
+const G: AffinePoint = AffinePoint::Coordinate {
+    x: U256::from_be_hex("e79b9cd3ca455f401435ba0f430465e22274daab3eef36ed173a265650dd175e"),
+    y: U256::from_be_hex("7ccfc8c1b11202352b388155341c8bdb081141b8c1e6e629d9844781cc5be07c"),
+};
+
+/// `H`, hashed onto the curve from the tag `RustPedersen/H`.
+const H: AffinePoint = AffinePoint::Coordinate {
+    x: U256::from_be_hex("3a78ca7fd95617f2ae5b191fb689896d72d27633bc3ee80b8d09ffac1f8c98e3"),
+    y: U256::from_be_hex("e3ee93b703477869df3ef3ae415707e149c53bebb2feacdad6757373150d73b2"),
+};
+
+/// The generator pair `(G, H)`.
+pub fn generators() -> (AffinePoint, AffinePoint) {
+    (G, H)
+}
+
+/// `value * G + blinding * H`; `None` unless both are below the group order, so that every
+/// opening is unique.
+pub fn commit(value: &U256, blinding: &U256) -> Option<AffinePoint> {
+    let n = &SECP256K1.scalar.modulus;
+    if value >= n || blinding >= n { return None; }
+    let (g, h) = generators();
+    let (g, h) = (SECP256K1.to_jacobian(&g)?, SECP256K1.to_jacobian(&h)?);
+    Some(SECP256K1.to_affine(&SECP256K1.double_scalar_mul(&g, value, &h, blinding)))
+}
+
+/// Whether `(value, blinding)` opens `commitment`; `None` if the commitment is not on the curve.
+pub fn verify(commitment: &AffinePoint, value: &U256, blinding: &U256) -> Option<bool> {
+    SECP256K1.to_jacobian(commitment)?;
+    Some(commit(value, blinding).as_ref() == Some(commitment))
+}
+
+/// `c1 + c2`, a commitment to the sums of the openings mod the group order.
+pub fn add(c1: &AffinePoint, c2: &AffinePoint) -> Option<AffinePoint> {
+    let sum = SECP256K1.add(&SECP256K1.to_jacobian(c1)?, &SECP256K1.to_jacobian(c2)?);
+    Some(SECP256K1.to_affine(&sum))
+}
+
+/// `c1 - c2`, a commitment to the differences of the openings mod the group order.
+pub fn sub(c1: &AffinePoint, c2: &AffinePoint) -> Option<AffinePoint> {
+    let c2 = SECP256K1.to_jacobian(c2)?;
+    let neg_c2 = JacobianPoint { y: SECP256K1.field.neg(&c2.y), ..c2 };
+    Some(SECP256K1.to_affine(&SECP256K1.add(&SECP256K1.to_jacobian(c1)?, &neg_c2)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    /// Try-and-increment: the first `x = keccak256(tag || counter)` that is the x-coordinate of
+    /// a curve point, taken with even `y`. Half of all `x` qualify, so this ends quickly. The
+    /// hash is the in-crate Keccak, so the derivation runs without the runtime.
+    fn hash_to_curve(tag: &[u8]) -> AffinePoint {
+        let mut preimage = alloc::vec![0u8; tag.len() + 4];
+        preimage[..tag.len()].copy_from_slice(tag);
+        let mut encoded = [0u8; 33];
+        encoded[0] = 0x02;
+        for counter in 0u32.. {
+            preimage[tag.len()..].copy_from_slice(&counter.to_be_bytes());
+            encoded[1..].copy_from_slice(&hash::keccak256(&preimage));
+            if let Some(point) = SECP256K1.decode_sec1(&encoded) { return point; }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn generators_match_their_derivation() {
+        assert_eq!(hash_to_curve(b"RustPedersen/G"), G);
+        assert_eq!(hash_to_curve(b"RustPedersen/H"), H);
+    }
+
+    #[test]
+    fn commitments_add_like_their_openings() {
+        let (v1, r1, v2, r2) = (U256::from_u64(10), U256::from_u64(1234), U256::from_u64(32), U256::from_u64(99));
+        let (c1, c2) = (commit(&v1, &r1).unwrap(), commit(&v2, &r2).unwrap());
+        let sum = add(&c1, &c2).unwrap();
+        assert_eq!(verify(&sum, &U256::from_u64(42), &U256::from_u64(1333)), Some(true));
+        assert_eq!(verify(&sum, &U256::from_u64(43), &U256::from_u64(1333)), Some(false));
+        assert_eq!(sub(&sum, &c2).unwrap(), c1);
+        assert!(commit(&SECP256K1.scalar.modulus, &r1).is_none());
+    }
+}
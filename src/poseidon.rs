@@ -0,0 +1,287 @@
+//! Poseidon and Poseidon2 over the BN254 scalar field with `x^5` S-boxes.
+//!
+//! Round constants and the Poseidon MDS matrix come from the Grain LFSR of the reference
+//! parameter scripts, seeded with the instance parameters, rather than from tables: circomlib
+//! ships megabytes of them for its 16 widths. Poseidon matches circomlib (`poseidon.circom`,
+//! `circomlibjs`) and light-poseidon; the Poseidon2 permutation matches the `t = 3` instance of
+//! the reference implementation (HorizenLabs `zkhash`).
+
+use alloc::vec::Vec;
+
+use crate::curve::BN254;
+use crate::field::PrimeField;
+use crate::uint::U256;
+
+const FR: PrimeField<4> = BN254.scalar;
+
+const FULL_ROUNDS: usize = 8;
+
+/// Partial rounds for widths `t = 2..=17`, i.e. 1 to 16 inputs.
+const POSEIDON_PARTIAL_ROUNDS: [usize; 16] = [56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68];
+
+pub const MAX_INPUTS: usize = POSEIDON_PARTIAL_ROUNDS.len();
+
+/// The 80-bit Grain LFSR in self-shrinking mode, as the reference scripts use it to derive
+/// parameters.
+struct Grain {
+    state: u128,
+}
+
+impl Grain {
+    /// Seeds the register with the instance description: field type (prime), S-box type
+    /// (`x^alpha`), field size, width and round numbers, padded with ones; then discards 160
+    /// bits.
+    fn new(width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        let fields = [(1, 2), (0, 4), (254, 12), (width, 12), (full_rounds, 10), (partial_rounds, 10), ((1 << 30) - 1, 30)];
+        let mut state = 0u128;
+        let mut position = 0;
+        for (value, width) in fields {
+            for bit in (0..width).rev() {
+                state |= (((value >> bit) & 1) as u128) << position;
+                position += 1;
+            }
+        }
+        let mut grain = Grain { state };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let s = self.state;
+        let bit = (s ^ s >> 13 ^ s >> 23 ^ s >> 38 ^ s >> 51 ^ s >> 62) & 1;
+        self.state = s >> 1 | bit << 79;
+        bit == 1
+    }
+
+    /// Bits come in pairs; the second is output only if the first is set.
+    fn next_bit(&mut self) -> bool {
+        while !self.step() {
+            self.step();
+        }
+        self.step()
+    }
+
+    /// 254 bits, most significant first.
+    fn next_bits(&mut self) -> U256 {
+        let mut value = U256::ZERO;
+        for _ in 0..254 {
+            value = value.shl(1);
+            value.0[0] |= self.next_bit() as u64;
+        }
+        value
+    }
+
+    /// A round constant: 254-bit values are drawn until one is below `r`. Montgomery form.
+    fn next_constant(&mut self) -> U256 {
+        loop {
+            if let Some(value) = FR.element(&self.next_bits()) { return value; }
+        }
+    }
+}
+
+fn sbox(x: &U256) -> U256 {
+    let x2 = FR.square(x);
+    FR.mul(&FR.square(&x2), x)
+}
+
+/// The round constants and MDS matrix of the Poseidon instance for one number of inputs.
+/// Deriving them takes far longer than a permutation, and a few kilobytes of the heap, so
+/// callers hashing many times, such as a Merkle tree, derive them once and reuse them.
+pub struct Poseidon {
+    width: usize,
+    partial_rounds: usize,
+    constants: Vec<U256>,
+    mds: Vec<U256>,
+}
+
+impl Poseidon {
+    /// The instance for 1 to 16 inputs; `None` for any other count.
+    pub fn new(inputs: usize) -> Option<Self> {
+        if inputs == 0 || inputs > MAX_INPUTS { return None; }
+        let t = inputs + 1;
+        let partial_rounds = POSEIDON_PARTIAL_ROUNDS[inputs - 1];
+        let mut grain = Grain::new(t, FULL_ROUNDS, partial_rounds);
+        let constants = (0..(FULL_ROUNDS + partial_rounds) * t).map(|_| grain.next_constant()).collect();
+        // The Cauchy matrix `M[i][j] = 1 / (x_i + y_j)`, from 2t further draws reduced mod `r`.
+        let xy: Vec<U256> = (0..2 * t).map(|_| FR.element_reduced(&grain.next_bits())).collect();
+        let mut mds = Vec::with_capacity(t * t);
+        for i in 0..t {
+            for j in 0..t {
+                mds.push(FR.inv(&FR.add(&xy[i], &xy[t + j]))?);
+            }
+        }
+        Some(Poseidon { width: t, partial_rounds, constants, mds })
+    }
+
+    /// Poseidon of as many field elements below `r` as the instance takes, with the capacity
+    /// element set to zero and the first state element as output; `None` for any other input.
+    /// The state lives on the stack, so hashing allocates nothing.
+    pub fn hash(&self, inputs: &[U256]) -> Option<U256> {
+        let t = self.width;
+        if inputs.len() + 1 != t { return None; }
+        let mut state = [U256::ZERO; MAX_INPUTS + 1];
+        for (x, input) in state[1..t].iter_mut().zip(inputs) {
+            *x = FR.element(input)?;
+        }
+        let mut mixed = [U256::ZERO; MAX_INPUTS + 1];
+        for (round, round_constants) in self.constants.chunks_exact(t).enumerate() {
+            for (x, c) in state.iter_mut().zip(round_constants) {
+                *x = FR.add(x, c);
+            }
+            let is_full = round < FULL_ROUNDS / 2 || round >= FULL_ROUNDS / 2 + self.partial_rounds;
+            if is_full {
+                for x in &mut state[..t] {
+                    *x = sbox(x);
+                }
+            } else {
+                state[0] = sbox(&state[0]);
+            }
+            for (i, out) in mixed[..t].iter_mut().enumerate() {
+                *out = state[..t].iter().zip(&self.mds[i * t..]).fold(U256::ZERO, |acc, (x, m)| FR.add(&acc, &FR.mul(x, m)));
+            }
+            core::mem::swap(&mut state, &mut mixed);
+        }
+        Some(FR.canonical(&state[0]))
+    }
+}
+
+/// Poseidon of 1 to 16 field elements below `r`; `None` for any other input. Derives the
+/// instance for this one hash.
+pub fn poseidon(inputs: &[U256]) -> Option<U256> {
+    Poseidon::new(inputs.len())?.hash(inputs)
+}
+
+const POSEIDON2_WIDTH: usize = 3;
+const POSEIDON2_PARTIAL_ROUNDS: usize = 56;
+
+/// `circ(2, 1, 1)`: adds the sum of the state to every element.
+fn poseidon2_external(state: &mut [U256; POSEIDON2_WIDTH]) {
+    let sum = FR.add(&FR.add(&state[0], &state[1]), &state[2]);
+    for x in state.iter_mut() {
+        *x = FR.add(x, &sum);
+    }
+}
+
+/// `1 + diag(1, 1, 2)`: as the external layer, but doubling the last element first.
+fn poseidon2_internal(state: &mut [U256; POSEIDON2_WIDTH]) {
+    let sum = FR.add(&FR.add(&state[0], &state[1]), &state[2]);
+    state[2] = FR.double(&state[2]);
+    for x in state.iter_mut() {
+        *x = FR.add(x, &sum);
+    }
+}
+
+/// The round constants of the `t = 3` Poseidon2 permutation: three for each full round and one
+/// for each partial round, in the order they are used.
+pub struct Poseidon2 {
+    constants: Vec<U256>,
+}
+
+impl Poseidon2 {
+    pub fn new() -> Self {
+        let mut grain = Grain::new(POSEIDON2_WIDTH, FULL_ROUNDS, POSEIDON2_PARTIAL_ROUNDS);
+        let count = FULL_ROUNDS * POSEIDON2_WIDTH + POSEIDON2_PARTIAL_ROUNDS;
+        Poseidon2 { constants: (0..count).map(|_| grain.next_constant()).collect() }
+    }
+
+    /// The permutation over Montgomery-form elements, as HorizenLabs `zkhash` defines it.
+    pub fn permute(&self, state: &mut [U256; POSEIDON2_WIDTH]) {
+        let full_round = |state: &mut [U256; POSEIDON2_WIDTH], constants: &[U256]| {
+            for (x, c) in state.iter_mut().zip(constants) {
+                *x = sbox(&FR.add(x, c));
+            }
+            poseidon2_external(state);
+        };
+        let half = FULL_ROUNDS / 2 * POSEIDON2_WIDTH;
+        let (first, rest) = self.constants.split_at(half);
+        let (partial, last) = rest.split_at(POSEIDON2_PARTIAL_ROUNDS);
+        poseidon2_external(state);
+        for constants in first.chunks_exact(POSEIDON2_WIDTH) {
+            full_round(state, constants);
+        }
+        for c in partial {
+            state[0] = sbox(&FR.add(&state[0], c));
+            poseidon2_internal(state);
+        }
+        for constants in last.chunks_exact(POSEIDON2_WIDTH) {
+            full_round(state, constants);
+        }
+    }
+}
+
+/// A sponge over the `t = 3` Poseidon2 permutation: rate 2, with the capacity element starting
+/// at `n * 2^64` for `n` inputs. Inputs are absorbed two at a time and the first state element
+/// is squeezed; `None` if there are no inputs or one is not below `r`.
+///
+/// Only the permutation is standard: it matches HorizenLabs `zkhash`. The sponge is this
+/// library's own, and does not give the same hashes as Noir's `Poseidon2::hash`, which absorbs
+/// three elements at a time into the `t = 4` permutation.
+pub fn poseidon2(inputs: &[U256]) -> Option<U256> {
+    if inputs.is_empty() { return None; }
+    let permutation = Poseidon2::new();
+    let iv = FR.element(&U256::from_u128((inputs.len() as u128) << 64))?;
+    let mut state = [U256::ZERO, U256::ZERO, iv];
+    for chunk in inputs.chunks(2) {
+        for (x, input) in state.iter_mut().zip(chunk) {
+            *x = FR.add(x, &FR.element(input)?);
+        }
+        permutation.permute(&mut state);
+    }
+    Some(FR.canonical(&state[0]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(value: &U256) -> alloc::string::String {
+        crate::hex::encode(&value.to_be_bytes())
+    }
+
+    /// circomlibjs's own test vectors for 1, 2 and 4 inputs, and light-poseidon for 6 and 12.
+    #[test]
+    fn poseidon_circomlib_vectors() {
+        for (inputs, digest) in [
+            (1, "29176100eaa962bdc1fe6c654d6a3c130e96a4d1168b33848b897dc502820133"),
+            (2, "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"),
+            (4, "299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465"),
+            (6, "2d1a03850084442813c8ebf094dea47538490a68b05f2239134a4cca2f6302e1"),
+            (12, "058814945232937db248a01e7cc55b3d681cc08702c8168494e856c1ef7693b5"),
+        ] {
+            let inputs: Vec<U256> = (1..=inputs).map(U256::from_u64).collect();
+            assert_eq!(hex(&poseidon(&inputs).unwrap()), digest);
+        }
+    }
+
+    #[test]
+    fn poseidon_rejects_bad_inputs() {
+        assert!(poseidon(&[]).is_none());
+        assert!(poseidon(&[U256::ZERO; MAX_INPUTS + 1]).is_none());
+        assert!(poseidon(&[FR.modulus, U256::ZERO]).is_none());
+        let instance = Poseidon::new(2).unwrap();
+        assert!(instance.hash(&[U256::ZERO]).is_none());
+        assert_eq!(instance.hash(&[U256::from_u64(1), U256::from_u64(2)]), poseidon(&[U256::from_u64(1), U256::from_u64(2)]));
+    }
+
+    /// zkhash's known answer for the BN254 `t = 3` instance.
+    #[test]
+    fn poseidon2_permutation_zkhash_vector() {
+        let mut state = [0, 1, 2].map(|x| FR.element(&U256::from_u64(x)).unwrap());
+        Poseidon2::new().permute(&mut state);
+        assert_eq!(state.map(|x| hex(&FR.canonical(&x))), [
+            "0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033",
+            "303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570",
+            "1ed25194542b12eef8617361c3ba7c52e660b145994427cc86296242cf766ec8",
+        ]);
+    }
+
+    #[test]
+    fn poseidon2_sponge_separates_lengths() {
+        let one = poseidon2(&[U256::from_u64(1)]).unwrap();
+        assert_ne!(one, poseidon2(&[U256::from_u64(1), U256::ZERO]).unwrap());
+        assert!(poseidon2(&[]).is_none());
+        assert!(poseidon2(&[FR.modulus]).is_none());
+    }
+}
@@ -0,0 +1,121 @@
+//! Ristretto255 (RFC 9496) and Sr25519 verification, i.e. Schnorrkel signatures over
+//! Ristretto with Merlin transcripts, as used by Substrate.
+
+use crate::edwards::{self, EdwardsPoint, FIELD, SQRT_M1, D};
+use crate::merlin::Transcript;
+use crate::uint::U256;
+
+const INVSQRT_A_MINUS_D: U256 =
+    FIELD.element_reduced(&U256::from_be_hex("786c8905cfaffca216c27b91fe01d8409d2f16175a4172be99c8fdaa805d40ea"));
+
+/// Signing context Substrate uses for every sr25519 signature.
+const SUBSTRATE_CONTEXT: &[u8] = b"substrate";
+
+/// Decodes a Ristretto255 element; rejects non-canonical encodings and non-square inputs.
+pub fn decode(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+    let f = &FIELD;
+    let s = f.element(&edwards::u256_from_le_bytes(bytes))?;
+    if edwards::is_negative(&s) { return None; }
+    let ss = f.square(&s);
+    let u1 = f.sub(&f.one, &ss);
+    let u2 = f.add(&f.one, &ss);
+    let u2_sqr = f.square(&u2);
+    let v = f.sub(&f.neg(&f.mul(&D, &f.square(&u1))), &u2_sqr);
+    let (was_square, invsqrt) = edwards::sqrt_ratio_m1(&f.one, &f.mul(&v, &u2_sqr));
+    let den_x = f.mul(&invsqrt, &u2);
+    let den_y = f.mul(&f.mul(&invsqrt, &den_x), &v);
+    let x = f.mul(&f.double(&s), &den_x);
+    let x = if edwards::is_negative(&x) { f.neg(&x) } else { x };
+    let y = f.mul(&u1, &den_y);
+    let t = f.mul(&x, &y);
+    if !was_square || edwards::is_negative(&t) || y.is_zero() { return None; }
+    Some(EdwardsPoint { x, y, z: f.one, t })
+}
+
+/// Encodes the Ristretto255 class of `p`.
+pub fn encode(p: &EdwardsPoint) -> [u8; 32] {
+    let f = &FIELD;
+    let u1 = f.mul(&f.add(&p.z, &p.y), &f.sub(&p.z, &p.y));
+    let u2 = f.mul(&p.x, &p.y);
+    let (_, invsqrt) = edwards::sqrt_ratio_m1(&f.one, &f.mul(&u1, &f.square(&u2)));
+    let den1 = f.mul(&invsqrt, &u1);
+    let den2 = f.mul(&invsqrt, &u2);
+    let z_inv = f.mul(&f.mul(&den1, &den2), &p.t);
+    let rotate = edwards::is_negative(&f.mul(&p.t, &z_inv));
+    let (x, y, den_inv) = if rotate {
+        (f.mul(&p.y, &SQRT_M1), f.mul(&p.x, &SQRT_M1), f.mul(&den1, &INVSQRT_A_MINUS_D))
+    } else {
+        (p.x, p.y, den2)
+    };
+    let y = if edwards::is_negative(&f.mul(&x, &z_inv)) { f.neg(&y) } else { y };
+    let s = f.mul(&den_inv, &f.sub(&p.z, &y));
+    let s = if edwards::is_negative(&s) { f.neg(&s) } else { s };
+    edwards::u256_to_le_bytes(&f.canonical(&s))
+}
+
+/// Verifies an sr25519 signature `(R, s)` made in the Substrate signing context. Schnorrkel
+/// marks its signatures by setting the top bit of `s`, which must be present.
+pub fn sr25519_verify(public_key: &[u8; 32], r: &[u8; 32], s: &[u8; 32], message: &[u8]) -> bool {
+    if s[31] & 0x80 == 0 { return false; }
+    let mut s_bytes = *s;
+    s_bytes[31] &= 0x7f;
+    let Some(s) = edwards::scalar_from_canonical_bytes(&s_bytes) else { return false };
+    let Some(a) = decode(public_key) else { return false };
+
+    let mut transcript = Transcript::new(b"SigningContext");
+    transcript.append_message(b"", SUBSTRATE_CONTEXT);
+    transcript.append_message(b"sign-bytes", message);
+    transcript.append_message(b"proto-name", b"Schnorr-sig");
+    transcript.append_message(b"sign:pk", public_key);
+    transcript.append_message(b"sign:R", r);
+    let mut challenge = [0u8; 64];
+    transcript.challenge_bytes(b"sign:c", &mut challenge);
+    let k = edwards::scalar_from_wide_bytes(&challenge);
+
+    encode(&edwards::double_scalar_mul(&edwards::BASEPOINT, &s, &a.neg(), &k)) == *r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    /// Signed by schnorrkel 0.11 in the `substrate` context, with the key expanded
+    /// Ed25519-style from the mini secret key `[0x5A; 32]`.
+    const PUBLIC_KEY: &str = "9eda665dd261ad54955ad987dbb02c116100953d1f841b81a54912f01f3bc057";
+    const MESSAGE: &[u8] = b"Polkadot Asset Hub";
+    const SIGNATURE: &str = "f6c139411be348af622aeaaf8e10cdab609e0054ede200ae03aab3841356b23a3606455615d63706284ddf45d8ee4fc3e633946dfe94f513d28f83f79c01368b";
+
+    fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        sr25519_verify(public_key.try_into().unwrap(), signature[..32].try_into().unwrap(), signature[32..].try_into().unwrap(), message)
+    }
+
+    #[test]
+    fn schnorrkel_vector() {
+        assert!(verify(&bytes(PUBLIC_KEY), MESSAGE, &bytes(SIGNATURE)));
+    }
+
+    #[test]
+    fn rejects_altered_signatures() {
+        let (public_key, signature) = (bytes(PUBLIC_KEY), bytes(SIGNATURE));
+        assert!(!verify(&public_key, b"Polkadot Asset Hub.", &signature));
+        for (i, bit) in [(0, 1), (40, 1), (63, 0x80)] {
+            let mut altered = signature.clone();
+            altered[i] ^= bit;
+            assert!(!verify(&public_key, MESSAGE, &altered));
+        }
+        let mut other_key = public_key.clone();
+        other_key[0] ^= 2;
+        assert!(!verify(&other_key, MESSAGE, &signature));
+    }
+
+    #[test]
+    fn encoding_round_trips() {
+        let point = decode(&bytes(PUBLIC_KEY).try_into().unwrap()).unwrap();
+        assert_eq!(encode(&point).to_vec(), bytes(PUBLIC_KEY));
+    }
+}
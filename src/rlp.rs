@@ -0,0 +1,188 @@
+//! Recursive Length Prefix, Ethereum's serialization of nested byte strings.
+//!
+//! A single byte below `0x80` is its own encoding. Other strings take a prefix of `0x80` plus
+//! their length, or for more than 55 bytes `0xB7` plus the length of a big-endian length; lists
+//! do the same from `0xC0` and `0xF7` over the concatenated encodings of their items. Decoding
+//! accepts only the canonical form of each item.
+
+use alloc::vec::Vec;
+
+/// A decoded item: a string's bytes, or a list's whole encoding, to be split by `decode_list`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Item<'a> {
+    String(&'a [u8]),
+    List(&'a [u8]),
+}
+
+/// The big-endian length after a long-form prefix; `None` unless it is minimal and above 55.
+fn long_length(input: &mut &[u8], length_len: usize) -> Option<usize> {
+    if input.len() < length_len || input[0] == 0 { return None; }
+    let (bytes, rest) = input.split_at(length_len);
+    *input = rest;
+    let len = bytes.iter().try_fold(0usize, |len, &b| len.checked_mul(256)?.checked_add(b as usize))?;
+    if len <= 55 { return None; }
+    Some(len)
+}
+
+/// Splits the item at the front of `input` off it.
+pub fn next_item<'a>(input: &mut &'a [u8]) -> Option<Item<'a>> {
+    let start = *input;
+    let prefix = *input.first()?;
+    *input = &input[1..];
+    let (is_list, len) = match prefix {
+        0x00..=0x7F => return Some(Item::String(&start[..1])),
+        0x80..=0xB7 => (false, (prefix - 0x80) as usize),
+        0xB8..=0xBF => (false, long_length(input, (prefix - 0xB7) as usize)?),
+        0xC0..=0xF7 => (true, (prefix - 0xC0) as usize),
+        0xF8..=0xFF => (true, long_length(input, (prefix - 0xF7) as usize)?),
+    };
+    if input.len() < len { return None; }
+    let (payload, rest) = input.split_at(len);
+    *input = rest;
+    if is_list {
+        return Some(Item::List(&start[..start.len() - rest.len()]));
+    }
+    // A single byte below 0x80 must be encoded as itself.
+    if len == 1 && payload[0] < 0x80 { return None; }
+    Some(Item::String(payload))
+}
+
+/// The items of the list that `data` encodes, with nothing after it.
+pub fn decode_list(data: &[u8]) -> Option<Vec<Item<'_>>> {
+    let mut input = data;
+    let Item::List(list) = next_item(&mut input)? else { return None; };
+    if !input.is_empty() { return None; }
+    // Skip the list's own prefix to reach its items.
+    let mut items = &list[if list[0] <= 0xF7 { 1 } else { 1 + (list[0] - 0xF7) as usize }..];
+    let mut output = Vec::new();
+    while !items.is_empty() {
+        output.push(next_item(&mut items)?);
+    }
+    Some(output)
+}
+
+fn encode_header(len: usize, offset: u8, out: &mut Vec<u8>) {
+    if len <= 55 {
+        out.push(offset + len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        out.push(offset + 55 + (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+pub fn encode_string(data: &[u8], out: &mut Vec<u8>) {
+    if let [byte @ 0x00..=0x7F] = data {
+        out.push(*byte);
+        return;
+    }
+    encode_header(data.len(), 0x80, out);
+    out.extend_from_slice(data);
+}
+
+/// A list whose items' encodings, concatenated, are `payload`.
+pub fn encode_list(payload: &[u8], out: &mut Vec<u8>) {
+    encode_header(payload.len(), 0xC0, out);
+    out.extend_from_slice(payload);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.as_bytes()).unwrap()
+    }
+
+    /// The single item `data` encodes, with nothing after it.
+    fn decode(data: &[u8]) -> Option<Item<'_>> {
+        let mut input = data;
+        let item = next_item(&mut input)?;
+        input.is_empty().then_some(item)
+    }
+
+    const LOREM: &[u8] = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+
+    /// Strings from `ethereum/tests`' `rlptest.json`.
+    #[test]
+    fn string_vectors() {
+        let long = [b'a'; 1024];
+        let cases: [(&[u8], Vec<u8>); 8] = [
+            (b"", bytes("80")),
+            (b"\x00", bytes("00")),
+            (b"\x7f", bytes("7f")),
+            (b"\x80", bytes("8180")),
+            (b"dog", bytes("83646f67")),
+            (&LOREM[..55], [&[0xb7], &LOREM[..55]].concat()),
+            (LOREM, [&[0xb8, 0x38], LOREM].concat()),
+            (&long, [&[0xb9, 0x04, 0x00][..], &long].concat()),
+        ];
+        for (string, encoding) in cases {
+            let mut out = Vec::new();
+            encode_string(string, &mut out);
+            assert_eq!(out, encoding);
+            assert!(decode(&encoding) == Some(Item::String(string)));
+        }
+    }
+
+    #[test]
+    fn list_vectors() {
+        // ["cat", "dog"]
+        let mut payload = Vec::new();
+        encode_string(b"cat", &mut payload);
+        encode_string(b"dog", &mut payload);
+        let mut out = Vec::new();
+        encode_list(&payload, &mut out);
+        assert_eq!(out, bytes("c88363617483646f67"));
+        assert!(decode_list(&out) == Some(vec![Item::String(b"cat"), Item::String(b"dog")]));
+        assert!(decode_list(&bytes("c0")) == Some(vec![]));
+
+        // The set-theoretical representation of three: [ [], [[]], [ [], [[]] ] ].
+        let three = bytes("c7c0c1c0c3c0c1c0");
+        let items = decode_list(&three).unwrap();
+        assert!(items == [Item::List(&three[1..2]), Item::List(&three[2..4]), Item::List(&three[4..])]);
+        assert!(decode_list(&three[4..]).unwrap() == [Item::List(&three[5..6]), Item::List(&three[6..])]);
+
+        // Two long strings make a list past 55 bytes.
+        let mut payload = Vec::new();
+        encode_string(LOREM, &mut payload);
+        encode_string(LOREM, &mut payload);
+        let mut out = Vec::new();
+        encode_list(&payload, &mut out);
+        assert_eq!(out[..2], [0xf8, 0x74]);
+        assert!(decode_list(&out) == Some(vec![Item::String(LOREM), Item::String(LOREM)]));
+    }
+
+    #[test]
+    fn rejects_non_canonical_encodings() {
+        let short_in_long_form = [&[0xb8, 0x37], &LOREM[..55]].concat();
+        let zero_padded_length = [&[0xb9, 0x00, 0x38], LOREM].concat();
+        let cases = [
+            // A single byte below 0x80 given a prefix.
+            bytes("8100"),
+            bytes("817f"),
+            // Long forms for 55 bytes or fewer, and lengths with leading zeros.
+            bytes("b80161"),
+            short_in_long_form,
+            zero_padded_length,
+            bytes("f800"),
+            // Lengths past the input, or past any memory.
+            bytes("83646f"),
+            bytes("b90400"),
+            bytes("bfffffffffffffffff00"),
+            bytes(""),
+        ];
+        for encoding in &cases {
+            assert!(decode(encoding).is_none(), "{encoding:02x?}");
+        }
+        // Non-canonical or truncated items inside a list, and bytes after one.
+        assert!(decode_list(&bytes("c28100")).is_none());
+        assert!(decode_list(&bytes("c3836361")).is_none());
+        assert!(decode_list(&bytes("c3b80161")).is_none());
+        assert!(decode_list(&bytes("c0c0")).is_none());
+        assert!(decode_list(&bytes("83646f67")).is_none());
+        assert!(decode_list(&bytes("c1")).is_none());
+    }
+}
@@ -0,0 +1,397 @@
+//! SCALE, the Substrate codec: fixed-width integers little-endian, compact integers, and
+//! length-prefixed byte strings, converted to and from Solidity ABI values.
+//!
+//! A compact integer keeps its mode in the two low bits of the first byte: `00` a 6-bit value
+//! in that byte, `01` a 14-bit value in two bytes, `10` a 30-bit value in four, and `11` a value
+//! in the `(first >> 2) + 4` bytes that follow. Only the shortest encoding of a value is
+//! accepted, and values are limited to 256 bits.
+//!
+//! A struct is its fields concatenated. Its layout is given by a descriptor, one byte per
+//! field, each mapping to an ABI type:
+//!
+//! | code | SCALE | ABI |
+//! |------|-------|-----|
+//! | `0x00` | `bool` | `bool` |
+//! | `0x01`–`0x06` | `u8`, `u16`, `u32`, `u64`, `u128`, `U256` | `uintN` |
+//! | `0x07`–`0x0B` | `i8`, `i16`, `i32`, `i64`, `i128` | `intN` |
+//! | `0x0C` | `Compact<U256>` | `uint256` |
+//! | `0x0D` | `Vec<u8>` | `bytes` |
+//! | `0x0E` | `[u8; 32]` (`AccountId32`, `H256`) | `bytes32` |
+//! | `0x0F` | `[u8; 20]` (`H160`) | `address` |
+//!
+//! Enum variant indices are `u8` fields.
+
+use alloc::vec::Vec;
+
+use crate::abi;
+use crate::uint::U256;
+
+/// Splits `len` bytes off the front of `input`.
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if input.len() < len { return None; }
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Some(head)
+}
+
+/// Little-endian bytes as a `U256`; `None` beyond 32 bytes.
+fn u256_from_le(bytes: &[u8]) -> Option<U256> {
+    let mut be: Vec<u8> = bytes.to_vec();
+    be.reverse();
+    U256::from_be_slice(&be)
+}
+
+/// Reads a compact integer from the front of `input`.
+pub fn decode_compact(input: &mut &[u8]) -> Option<U256> {
+    let first = *input.first()?;
+    let (value, min) = match first & 0b11 {
+        0b00 => (U256::from_u64(take(input, 1)?[0] as u64 >> 2), 0),
+        0b01 => (U256::from_u64(u16::from_le_bytes(take(input, 2)?.try_into().unwrap()) as u64 >> 2), 1 << 6),
+        0b10 => (U256::from_u64(u32::from_le_bytes(take(input, 4)?.try_into().unwrap()) as u64 >> 2), 1 << 14),
+        _ => {
+            take(input, 1)?;
+            let bytes = take(input, (first >> 2) as usize + 4)?;
+            if bytes.last() == Some(&0) { return None; }
+            (u256_from_le(bytes)?, 1 << 30)
+        }
+    };
+    if value < U256::from_u64(min) { return None; }
+    Some(value)
+}
+
+/// Appends `value` as a compact integer, in its shortest mode.
+pub fn encode_compact(value: &U256, out: &mut Vec<u8>) {
+    match value.to_u64() {
+        Some(v) if v < 1 << 6 => out.push((v as u8) << 2),
+        Some(v) if v < 1 << 14 => out.extend_from_slice(&((v as u16) << 2 | 0b01).to_le_bytes()),
+        Some(v) if v < 1 << 30 => out.extend_from_slice(&((v as u32) << 2 | 0b10).to_le_bytes()),
+        _ => {
+            let len = value.bits().div_ceil(8) as usize;
+            out.push(((len - 4) as u8) << 2 | 0b11);
+            let be = value.to_be_bytes();
+            out.extend(be[32 - len..].iter().rev());
+        }
+    }
+}
+
+/// Reads a `Vec<u8>`: its compact length, then the bytes.
+pub fn decode_bytes(input: &mut &[u8]) -> Option<Vec<u8>> {
+    let len = decode_compact(input)?.to_u64()?;
+    Some(take(input, usize::try_from(len).ok()?)?.to_vec())
+}
+
+pub fn encode_bytes(data: &[u8], out: &mut Vec<u8>) {
+    encode_compact(&U256::from_u64(data.len() as u64), out);
+    out.extend_from_slice(data);
+}
+
+/// A struct field, from its descriptor code; integer widths are in bytes.
+#[derive(Clone, Copy)]
+enum Field {
+    Bool,
+    Uint(usize),
+    Int(usize),
+    Compact,
+    Bytes,
+    Bytes32,
+    Address,
+}
+
+impl Field {
+    fn from_code(code: u8) -> Option<Self> {
+        Some(match code {
+            0x00 => Field::Bool,
+            0x01..=0x06 => Field::Uint(1 << (code - 0x01)),
+            0x07..=0x0B => Field::Int(1 << (code - 0x07)),
+            0x0C => Field::Compact,
+            0x0D => Field::Bytes,
+            0x0E => Field::Bytes32,
+            0x0F => Field::Address,
+            _ => return None,
+        })
+    }
+
+    /// Moves one field from SCALE `input` to the ABI `output`.
+    fn decode(self, input: &mut &[u8], output: &mut abi::Encoder) -> Option<()> {
+        match self {
+            Field::Bool => match take(input, 1)?[0] {
+                0 => output.bool(false),
+                1 => output.bool(true),
+                _ => return None,
+            },
+            Field::Uint(width) => output.uint256(&u256_from_le(take(input, width)?)?),
+            Field::Int(width) => {
+                let bytes = take(input, width)?;
+                let negative = bytes[width - 1] & 0x80 != 0;
+                let mut word = if negative { [0xFF; 32] } else { [0; 32] };
+                for (i, &byte) in bytes.iter().enumerate() {
+                    word[31 - i] = byte;
+                }
+                output.word(&word)
+            }
+            Field::Compact => output.uint256(&decode_compact(input)?),
+            Field::Bytes => output.bytes(&decode_bytes(input)?),
+            Field::Bytes32 => output.word(take(input, 32)?.try_into().unwrap()),
+            Field::Address => output.address(take(input, 20)?.try_into().unwrap()),
+        };
+        Some(())
+    }
+
+    /// Moves one field from the ABI `input` to SCALE `output`; `None` if the value is out of
+    /// the field's range.
+    fn encode(self, input: &mut abi::Decoder, output: &mut Vec<u8>) -> Option<()> {
+        match self {
+            Field::Bool => output.push(input.bool()? as u8),
+            Field::Uint(width) => {
+                let value = input.uint256()?;
+                if value.bits() as usize > width * 8 { return None; }
+                output.extend(value.to_be_bytes()[32 - width..].iter().rev());
+            }
+            Field::Int(width) => {
+                // In range exactly when the word is the sign extension of its low `width` bytes.
+                let word = input.word()?;
+                let extension = if word[32 - width] & 0x80 != 0 { 0xFF } else { 0 };
+                if word[..32 - width].iter().any(|&b| b != extension) { return None; }
+                output.extend(word[32 - width..].iter().rev());
+            }
+            Field::Compact => encode_compact(&input.uint256()?, output),
+            Field::Bytes => encode_bytes(&input.bytes()?, output),
+            Field::Bytes32 => output.extend_from_slice(&input.word()?),
+            Field::Address => output.extend_from_slice(&input.address()?),
+        }
+        Some(())
+    }
+}
+
+/// Decodes the struct laid out by `descriptor` from the front of `input` into its ABI
+/// encoding, as `abi.encode` of the fields would give.
+pub fn decode_struct(descriptor: &[u8], input: &mut &[u8]) -> Option<Vec<u8>> {
+    let mut output = abi::Encoder::new();
+    for &code in descriptor {
+        Field::from_code(code)?.decode(input, &mut output)?;
+    }
+    Some(output.finish())
+}
+
+/// Encodes the fields read from `input`, an ABI tuple, as the struct laid out by `descriptor`.
+pub fn encode_struct(descriptor: &[u8], mut input: abi::Decoder) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    for &code in descriptor {
+        Field::from_code(code)?.encode(&mut input, &mut output)?;
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host;
+    use alloc::vec;
+
+    fn bytes(text: &str) -> Vec<u8> {
+        crate::hex::decode(text.replace(' ', "").as_bytes()).unwrap()
+    }
+
+    fn decode_all(data: &[u8]) -> Option<U256> {
+        let mut input = data;
+        let value = decode_compact(&mut input)?;
+        input.is_empty().then_some(value)
+    }
+
+    /// parity-scale-codec's `compact_64_encoding_works`, plus the 128- and 256-bit ends.
+    const COMPACTS: [(u128, &str); 16] = [
+        (0, "00"),
+        (1, "04"),
+        (63, "fc"),
+        (64, "01 01"),
+        (16383, "fd ff"),
+        (16384, "02 00 01 00"),
+        ((1 << 30) - 1, "fe ff ff ff"),
+        (1 << 30, "03 00 00 00 40"),
+        ((1 << 32) - 1, "03 ff ff ff ff"),
+        (1 << 32, "07 00 00 00 00 01"),
+        (1 << 40, "0b 00 00 00 00 00 01"),
+        (1 << 48, "0f 00 00 00 00 00 00 01"),
+        ((1 << 56) - 1, "0f ff ff ff ff ff ff ff"),
+        (1 << 56, "13 00 00 00 00 00 00 00 01"),
+        (u64::MAX as u128, "13 ff ff ff ff ff ff ff ff"),
+        (u128::MAX, "33 ffffffffffffffffffffffffffffffff"),
+    ];
+
+    #[test]
+    fn compact_vectors() {
+        for (value, encoding) in COMPACTS {
+            let value = U256::from_u128(value);
+            let mut out = Vec::new();
+            encode_compact(&value, &mut out);
+            assert_eq!(out, bytes(encoding), "{value:?}");
+            assert_eq!(decode_all(&out), Some(value));
+        }
+        let mut out = Vec::new();
+        encode_compact(&U256::MAX, &mut out);
+        assert_eq!(out, [&[0x73][..], &[0xff; 32]].concat());
+        assert_eq!(decode_all(&out), Some(U256::MAX));
+
+        // Decoding takes one value off the front.
+        let mut input = &bytes("01 01 fc")[..];
+        assert_eq!(decode_compact(&mut input), Some(U256::from_u64(64)));
+        assert_eq!(input, [0xfc]);
+    }
+
+    #[test]
+    fn compact_rejects_non_canonical_and_truncated_input() {
+        for encoding in [
+            // A value that fits a shorter mode.
+            "01 00", "fd 00", "02 00 00 00", "fe ff 00 00", "03 ff ff ff 3f",
+            // A big-integer mode with a zero top byte.
+            "07 ff ff ff ff 00", "13 ff ff ff ff ff ff ff 00",
+            // Truncated.
+            "", "01", "02 00 01", "03 00 00 00", "07 00 00 00 00",
+        ] {
+            assert_eq!(decode_compact(&mut &bytes(encoding)[..]), None, "{encoding}");
+        }
+        // 33 bytes is past 256 bits.
+        let too_long = [&[0x77][..], &[0xff; 33]].concat();
+        assert_eq!(decode_compact(&mut &too_long[..]), None);
+    }
+
+    #[test]
+    fn byte_vectors() {
+        for (data, encoding) in [(vec![], "00"), (vec![1, 2, 3], "0c 01 02 03")] {
+            let mut out = Vec::new();
+            encode_bytes(&data, &mut out);
+            assert_eq!(out, bytes(encoding));
+            assert_eq!(decode_bytes(&mut &out[..]), Some(data));
+        }
+        let long = vec![0xab; 64];
+        let mut out = Vec::new();
+        encode_bytes(&long, &mut out);
+        assert_eq!(out[..2], [0x01, 0x01]);
+        assert_eq!(decode_bytes(&mut &out[..]), Some(long));
+
+        assert_eq!(decode_bytes(&mut &bytes("0c 01 02")[..]), None);
+        assert_eq!(decode_bytes(&mut &bytes("01 00")[..]), None);
+    }
+
+    /// One field of every descriptor code, `0x00` to `0x0F`.
+    const DESCRIPTOR: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F];
+
+    fn scale_struct() -> Vec<u8> {
+        bytes(concat!(
+            "01",                                                               // bool: true
+            "ab",                                                               // u8
+            "3412",                                                             // u16: 0x1234
+            "78563412",                                                         // u32
+            "efcdab8967452301",                                                 // u64
+            "00112233445566778899aabbccddeeff",                                 // u128
+            "0100000000000000000000000000000000000000000000000000000000000080", // U256: 2^255 + 1
+            "ff",                                                               // i8: -1
+            "feff",                                                             // i16: -2
+            "00000080",                                                         // i32: i32::MIN
+            "ffffffffffffff7f",                                                 // i64: i64::MAX
+            "ffffffffffffffffffffffffffffff80",                                 // i128: -2^120 - 1
+            "0300000040",                                                       // compact: 2^30
+            "0c010203",                                                         // Vec<u8>
+            "1111111111111111111111111111111111111111111111111111111111111111", // [u8; 32]
+            "2222222222222222222222222222222222222222",                         // H160
+        ))
+    }
+
+    /// The ABI tuple `scale_struct` decodes to.
+    fn abi_struct() -> Vec<u8> {
+        let sign_extended = |low: &str| {
+            let mut word = [0xff; 32];
+            let low = bytes(low);
+            word[32 - low.len()..].copy_from_slice(&low);
+            word
+        };
+        abi::Encoder::new()
+            .bool(true)
+            .uint64(0xab)
+            .uint64(0x1234)
+            .uint64(0x1234_5678)
+            .uint64(0x0123_4567_89ab_cdef)
+            .uint256(&U256::from_u128(0xffee_ddcc_bbaa_9988_7766_5544_3322_1100))
+            .uint256(&U256::ONE.shl(255).wrapping_add(&U256::ONE))
+            .int64(-1)
+            .int64(-2)
+            .int64(i32::MIN.into())
+            .int64(i64::MAX)
+            .word(&sign_extended("80ffffffffffffffffffffffffffffff"))
+            .uint64(1 << 30)
+            .bytes(&[1, 2, 3])
+            .word(&[0x11; 32])
+            .address(&[0x22; 20])
+            .finish()
+    }
+
+    fn encode_from_abi(descriptor: &[u8], tuple: &[u8]) -> Option<Vec<u8>> {
+        host::set_call_data(&[&[0; 4][..], tuple].concat());
+        encode_struct(descriptor, abi::Decoder::arguments())
+    }
+
+    #[test]
+    fn struct_round_trip_over_every_field() {
+        let scale = scale_struct();
+        let mut input = &scale[..];
+        assert_eq!(decode_struct(&DESCRIPTOR, &mut input), Some(abi_struct()));
+        assert!(input.is_empty());
+        assert_eq!(encode_from_abi(&DESCRIPTOR, &abi_struct()), Some(scale.clone()));
+
+        // A prefix of the fields leaves the rest of the input.
+        let mut input = &scale[..];
+        assert!(decode_struct(&DESCRIPTOR[..2], &mut input).is_some());
+        assert_eq!(input.len(), scale.len() - 2);
+
+        assert_eq!(decode_struct(&DESCRIPTOR, &mut &scale[..scale.len() - 1]), None);
+        assert_eq!(decode_struct(&[0x10], &mut &scale[..]), None);
+        assert_eq!(decode_struct(&[0x00], &mut &[0x02][..]), None);
+        assert_eq!(encode_from_abi(&[0x10], &abi_struct()), None);
+    }
+
+    #[test]
+    fn encode_struct_rejects_values_out_of_range() {
+        let unsigned = |value: &U256| value.to_be_bytes().to_vec();
+        let signed = |value: i64| abi::Encoder::new().int64(value).finish();
+        // Unsigned widths take exactly their range.
+        for (code, bits) in [(0x01, 8), (0x02, 16), (0x03, 32), (0x04, 64), (0x05, 128)] {
+            let max = U256::ONE.shl(bits).wrapping_sub(&U256::ONE);
+            assert_eq!(encode_from_abi(&[code], &unsigned(&max)), Some(vec![0xff; bits as usize / 8]));
+            assert_eq!(encode_from_abi(&[code], &unsigned(&max.wrapping_add(&U256::ONE))), None, "{code:#04x}");
+        }
+        assert_eq!(encode_from_abi(&[0x06], &unsigned(&U256::MAX)), Some(vec![0xff; 32]));
+
+        // Signed widths take `[-2^(n-1), 2^(n-1) - 1]`.
+        for (code, bits) in [(0x07, 8), (0x08, 16), (0x09, 32), (0x0A, 64)] {
+            let (min, max) = (-1i64 << (bits - 1), ((1u64 << (bits - 1)) - 1) as i64);
+            let width = bits as usize / 8;
+            let mut expected = vec![0xff; width];
+            expected[width - 1] = 0x7f;
+            assert_eq!(encode_from_abi(&[code], &signed(max)), Some(expected), "{code:#04x}");
+            let mut expected = vec![0; width];
+            expected[width - 1] = 0x80;
+            assert_eq!(encode_from_abi(&[code], &signed(min)), Some(expected));
+            if bits < 64 {
+                assert_eq!(encode_from_abi(&[code], &signed(max + 1)), None, "{code:#04x}");
+                assert_eq!(encode_from_abi(&[code], &signed(min - 1)), None);
+            }
+        }
+        // i64 and i128 against words just past their range.
+        let mut word = [0u8; 32];
+        word[24] = 0x80;
+        assert_eq!(encode_from_abi(&[0x0A], &word), None);
+        word[16] = 0x80;
+        word[24] = 0;
+        assert_eq!(encode_from_abi(&[0x0B], &word), None);
+        let mut word = [0xff; 32];
+        word[16] = 0x7f;
+        assert_eq!(encode_from_abi(&[0x0B], &word), None);
+
+        // Strict `bool` and `address` words.
+        assert_eq!(encode_from_abi(&[0x00], &unsigned(&U256::from_u64(2))), None);
+        let mut address = abi::Encoder::new().address(&[0x22; 20]).finish();
+        address[0] = 1;
+        assert_eq!(encode_from_abi(&[0x0F], &address), None);
+        assert_eq!(encode_from_abi(&[0x01], &[]), None);
+    }
+}
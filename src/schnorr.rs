@@ -0,0 +1,235 @@
+//! BIP-340 Schnorr signatures on secp256k1: x-only public keys, 64-byte `(r, s)` signatures
+//! and SHA-256 tagged hashes.
+//!
+//! Batch verification folds all equations `s_i * G = R_i + e_i * P_i` into one with random
+//! weights `a_i` (and `a_1 = 1`), checked by a single multi-scalar multiplication. The weights
+//! are derived by hashing the whole batch, so they are fixed only after every signature is.
+
+use alloc::vec::Vec;
+
+use crate::curve::{AffinePoint, JacobianPoint, SECP256K1};
+use crate::host;
+use crate::uint::U256;
+
+/// `sha256(sha256(tag) || sha256(tag) || data)`.
+fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> [u8; 32] {
+    let mut tag_hash = [0u8; 32];
+    host::hash_sha2_256(tag, &mut tag_hash);
+    let mut preimage = Vec::with_capacity(64 + data.iter().map(|part| part.len()).sum::<usize>());
+    preimage.extend_from_slice(&tag_hash);
+    preimage.extend_from_slice(&tag_hash);
+    for part in data {
+        preimage.extend_from_slice(part);
+    }
+    let mut hash = [0u8; 32];
+    host::hash_sha2_256(&preimage, &mut hash);
+    hash
+}
+
+/// The point with x-coordinate `x` and even `y`, or `None` if there is none.
+fn lift_x(x: &[u8; 32]) -> Option<JacobianPoint> {
+    let mut encoded = [0u8; 33];
+    encoded[0] = 0x02;
+    encoded[1..].copy_from_slice(x);
+    SECP256K1.to_jacobian(&SECP256K1.decode_sec1(&encoded)?)
+}
+
+/// A 32-byte hash as a scalar, reduced mod `n`, in Montgomery form.
+fn hash_to_scalar(hash: &[u8; 32]) -> U256 {
+    SECP256K1.scalar.element_reduced(&U256::from_be_bytes(hash))
+}
+
+/// A signature with its public key lifted and its challenge `e` computed; `None` if the key
+/// has no point or `r >= p` or `s >= n`. `s` and `e` are Montgomery scalars.
+struct Parsed {
+    public_key: JacobianPoint,
+    r: [u8; 32],
+    s: U256,
+    e: U256,
+}
+
+fn parse(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> Option<Parsed> {
+    let p = lift_x(public_key)?;
+    let r: [u8; 32] = signature[..32].try_into().unwrap();
+    if U256::from_be_bytes(&r) >= SECP256K1.field.modulus { return None; }
+    let s = SECP256K1.scalar.element(&U256::from_be_bytes(signature[32..].try_into().unwrap()))?;
+    let e = hash_to_scalar(&tagged_hash(b"BIP0340/challenge", &[&r, public_key, message]));
+    Some(Parsed { public_key: p, r, s, e })
+}
+
+pub fn verify(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    let n = &SECP256K1.scalar;
+    let Some(sig) = parse(public_key, message, signature) else { return false };
+    let g = SECP256K1.to_jacobian(&SECP256K1.generator).unwrap();
+    // R = s * G - e * P must have even y and x-coordinate r.
+    let r_point = SECP256K1.double_scalar_mul(&g, &n.canonical(&sig.s), &sig.public_key, &n.canonical(&n.neg(&sig.e)));
+    match SECP256K1.to_affine(&r_point) {
+        AffinePoint::Infinity => false,
+        AffinePoint::Coordinate { x, y } => !y.is_odd() && x.to_be_bytes() == sig.r,
+    }
+}
+
+/// Verifies `signatures[i]` by `public_keys[i]` over `messages[i]` for every `i` at once; true
+/// only if each would pass `verify`, up to a negligible probability. An empty batch passes.
+pub fn batch_verify(public_keys: &[[u8; 32]], messages: &[Vec<u8>], signatures: &[[u8; 64]]) -> bool {
+    let n = &SECP256K1.scalar;
+    if public_keys.len() != messages.len() || public_keys.len() != signatures.len() { return false; }
+    // Each message is prefixed with its length, so that no two batches share a seed.
+    let lengths: Vec<[u8; 8]> = messages.iter().map(|message| (message.len() as u64).to_be_bytes()).collect();
+    let mut seed_parts: Vec<&[u8]> = Vec::with_capacity(4 * public_keys.len());
+    for (((public_key, length), message), signature) in public_keys.iter().zip(&lengths).zip(messages).zip(signatures) {
+        seed_parts.extend_from_slice(&[public_key, length, message, signature]);
+    }
+    let seed = tagged_hash(b"BIP0340/batch", &seed_parts);
+
+    // sum(a_i * s_i) * G == sum(a_i * R_i) + sum(a_i * e_i * P_i), as an MSM equal to infinity.
+    let mut points = Vec::with_capacity(2 * public_keys.len() + 1);
+    let mut scalars = Vec::with_capacity(2 * public_keys.len() + 1);
+    let mut s_sum = U256::ZERO;
+    for (i, ((public_key, message), signature)) in public_keys.iter().zip(messages).zip(signatures).enumerate() {
+        let Some(sig) = parse(public_key, message, signature) else { return false };
+        let Some(r_point) = lift_x(&sig.r) else { return false };
+        let a = if i == 0 {
+            n.one
+        } else {
+            hash_to_scalar(&tagged_hash(b"BIP0340/batch", &[&seed, &(i as u32).to_be_bytes()]))
+        };
+        s_sum = n.add(&s_sum, &n.mul(&a, &sig.s));
+        points.push(r_point);
+        scalars.push(n.canonical(&a));
+        points.push(sig.public_key);
+        scalars.push(n.canonical(&n.mul(&a, &sig.e)));
+    }
+    points.push(SECP256K1.to_jacobian(&SECP256K1.generator).unwrap());
+    scalars.push(n.canonical(&n.neg(&s_sum)));
+    SECP256K1.msm(&points, &scalars).is_infinity()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes<const N: usize>(text: &str) -> [u8; N] {
+        crate::hex::decode(text.as_bytes()).unwrap().try_into().unwrap()
+    }
+
+    /// The verification cases of BIP-340's `test-vectors.csv`:
+    /// `(index, public key, message, signature, valid)`.
+    const VECTORS: [(u8, &str, &str, &str, bool); 19] = [
+        (0, "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0", true),
+        (1, "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A", true),
+        (2, "DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8",
+            "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+            "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7", true),
+        // The message is not reduced mod p or n.
+        (3, "25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517",
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+            "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3", true),
+        (4, "D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9",
+            "4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703",
+            "00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4", true),
+        // The public key is not on the curve.
+        (5, "EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+        // R has odd y.
+        (6, "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A14602975563CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2", false),
+        // Negated message.
+        (7, "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD", false),
+        // Negated s.
+        (8, "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6", false),
+        // s * G - e * P is infinity, with r = 0 and then r = 1.
+        (9, "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0000000000000000000000000000000000000000000000000000000000000000123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051", false),
+        (10, "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "00000000000000000000000000000000000000000000000000000000000000017615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197", false),
+        // r is not the x-coordinate of a point.
+        (11, "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+        // r = p.
+        (12, "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+        // s = n.
+        (13, "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141", false),
+        // The public key is past the field size.
+        (14, "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+        // Messages that are not 32 bytes long.
+        (15, "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+            "",
+            "71535DB165ECD9FBBC046E5FFAEA61186BB6AD436732FCCC25291A55895464CF6069CE26BF03466228F19A3A62DB8A649F2D560FAC652827D1AF0574E427AB63", true),
+        (16, "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+            "11",
+            "08A20A0AFEF64124649232E0693C583AB1B9934AE63B4C3511F3AE1134C6A303EA3173BFEA6683BD101FA5AA5DBC1996FE7CACFC5A577D33EC14564CEC2BACBF", true),
+        (17, "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+            "0102030405060708090A0B0C0D0E0F1011",
+            "5130F39A4059B43BC7CAC09A19ECE52B5D8699D1A71E3C52DA9AFDB6B50AC370C4A482B77BF960F8681540E25B6771ECE1E5A37FD80E5A51897C5566A97EA5A5", true),
+        (18, "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+            "99999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999",
+            "403B12B0D8555A344175EA7EC746566303321E5DBFA8BE6F091635163ECA79A8585ED3E3170807E7C03B720FC54C7B23897FCBA0E9D0B4A06894CFD249F22367", true),
+    ];
+
+    type Case = ([u8; 32], Vec<u8>, [u8; 64]);
+
+    fn case(index: usize) -> Case {
+        let (_, public_key, message, signature, _) = VECTORS[index];
+        (bytes(public_key), crate::hex::decode(message.as_bytes()).unwrap(), bytes(signature))
+    }
+
+    fn batch(cases: &[Case]) -> bool {
+        let public_keys: Vec<[u8; 32]> = cases.iter().map(|case| case.0).collect();
+        let messages: Vec<Vec<u8>> = cases.iter().map(|case| case.1.clone()).collect();
+        let signatures: Vec<[u8; 64]> = cases.iter().map(|case| case.2).collect();
+        batch_verify(&public_keys, &messages, &signatures)
+    }
+
+    #[test]
+    fn bip340_vectors() {
+        for (index, (number, _, _, _, valid)) in VECTORS.into_iter().enumerate() {
+            let (public_key, message, signature) = case(index);
+            assert_eq!(verify(&public_key, &message, &signature), valid, "vector {number}");
+            assert_eq!(batch(&[case(index)]), valid, "vector {number} as a batch of one");
+        }
+    }
+
+    #[test]
+    fn batch_with_one_bad_signature() {
+        let valid: Vec<Case> = (0..VECTORS.len()).filter(|&index| VECTORS[index].4).map(case).collect();
+        assert!(batch(&valid));
+        assert!(batch(&[]));
+        for bad in (0..VECTORS.len()).filter(|&index| !VECTORS[index].4) {
+            for position in [0, valid.len() / 2, valid.len()] {
+                let mut cases = valid.clone();
+                cases.insert(position, case(bad));
+                assert!(!batch(&cases), "vector {bad} at position {position}");
+            }
+        }
+        // Two valid signatures swapped between their keys and messages.
+        let mut swapped = valid.clone();
+        let first = swapped[1].2;
+        swapped[1].2 = swapped[2].2;
+        swapped[2].2 = first;
+        assert!(!batch(&swapped));
+        let public_keys: Vec<[u8; 32]> = valid.iter().map(|case| case.0).collect();
+        let messages: Vec<Vec<u8>> = valid.iter().map(|case| case.1.clone()).collect();
+        let signatures: Vec<[u8; 64]> = valid.iter().map(|case| case.2).collect();
+        assert!(!batch_verify(&public_keys, &messages[1..], &signatures));
+    }
+}
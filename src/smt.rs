@@ -0,0 +1,126 @@
+//! Sparse Merkle trees of depth 256 keyed by `bytes32`, with compressed proofs.
+//!
+//! A key's path runs from its most significant bit at the root down to its least significant
+//! bit above the leaf. A present leaf is `H(key || valueHash)`, an absent one is zero, and a
+//! parent of two zero nodes is zero, so every empty subtree is zero whatever its height. Proofs
+//! therefore list only the non-empty siblings, with a bitmap whose bit `i` marks the sibling at
+//! height `i` (0 being the leaf's) as present.
+
+use crate::merkle::{Node, NodeHash};
+use crate::uint::U256;
+
+const EMPTY: Node = [0; 32];
+
+fn parent(hash: NodeHash, left: &Node, right: &Node) -> Node {
+    if *left == EMPTY && *right == EMPTY { EMPTY } else { hash.merge(left, right) }
+}
+
+/// The root reached from `leaf` at `key`; `None` if `siblings` does not hold one node per bit
+/// set in `bitmap`.
+fn compute_root(hash: NodeHash, key: &Node, leaf: Node, bitmap: &U256, siblings: &[Node]) -> Option<Node> {
+    let path = U256::from_be_bytes(key);
+    let mut siblings = siblings.iter();
+    let mut node = leaf;
+    for height in 0..256 {
+        let sibling = if bitmap.bit(height) { *siblings.next()? } else { EMPTY };
+        node = if path.bit(height) { parent(hash, &sibling, &node) } else { parent(hash, &node, &sibling) };
+    }
+    if siblings.next().is_some() { return None; }
+    Some(node)
+}
+
+/// Whether `key` maps to `value_hash` under `root`.
+pub fn verify_inclusion(hash: NodeHash, root: &Node, key: &Node, value_hash: &Node, bitmap: &U256, siblings: &[Node]) -> Option<bool> {
+    let leaf = hash.merge(key, value_hash);
+    Some(compute_root(hash, key, leaf, bitmap, siblings)? == *root)
+}
+
+/// Whether `key` is absent under `root`.
+pub fn verify_non_inclusion(hash: NodeHash, root: &Node, key: &Node, bitmap: &U256, siblings: &[Node]) -> Option<bool> {
+    Some(compute_root(hash, key, EMPTY, bitmap, siblings)? == *root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn key(text: &str) -> Node {
+        U256::from_be_hex(text).to_be_bytes()
+    }
+
+    /// The node at `height` above the leaves, computed top-down from every entry below it,
+    /// recording the non-empty siblings along `path` from the top.
+    fn subtree(hash: NodeHash, entries: &[(U256, Node)], height: u32, path: &U256, siblings: &mut Vec<(u32, Node)>) -> Node {
+        if entries.is_empty() { return EMPTY; }
+        if height == 0 { return entries[0].1; }
+        let (right, left): (Vec<_>, Vec<_>) = entries.iter().partition(|(key, _)| key.bit(height - 1));
+        let (on_path, off_path) = if path.bit(height - 1) { (&right, &left) } else { (&left, &right) };
+        let sibling = subtree(hash, off_path, height - 1, path, &mut Vec::new());
+        if sibling != EMPTY { siblings.push((height - 1, sibling)); }
+        let node = subtree(hash, on_path, height - 1, path, siblings);
+        let (left, right) = if path.bit(height - 1) { (sibling, node) } else { (node, sibling) };
+        parent(hash, &left, &right)
+    }
+
+    /// The root of `entries` and the compressed proof for `path`.
+    fn prove(hash: NodeHash, entries: &[(Node, Node)], path: &Node) -> (Node, U256, Vec<Node>) {
+        let leaves: Vec<(U256, Node)> = entries.iter().map(|(key, value_hash)| (U256::from_be_bytes(key), hash.merge(key, value_hash))).collect();
+        let mut siblings = Vec::new();
+        let root = subtree(hash, &leaves, 256, &U256::from_be_bytes(path), &mut siblings);
+        let bitmap = siblings.iter().fold(U256::ZERO, |bitmap, (height, _)| bitmap.wrapping_add(&U256::ONE.shl(*height)));
+        (root, bitmap, siblings.into_iter().rev().map(|(_, sibling)| sibling).collect())
+    }
+
+    #[test]
+    fn compressed_proofs() {
+        let entries = [
+            // Two keys a single bit apart, whose only non-empty sibling is at height 1.
+            (key("01"), [1; 32]),
+            (key("03"), [2; 32]),
+            (key("8000000000000000000000000000000000000000000000000000000000000000"), [3; 32]),
+            (crate::hash::keccak256(b"key"), [4; 32]),
+        ];
+        for hash in [NodeHash::Keccak256, NodeHash::Blake2b256] {
+            for (key, value_hash) in &entries {
+                let (root, bitmap, siblings) = prove(hash, &entries, key);
+                assert!(siblings.len() < 4);
+                assert_eq!(verify_inclusion(hash, &root, key, value_hash, &bitmap, &siblings), Some(true));
+                assert_eq!(verify_inclusion(hash, &root, key, &[9; 32], &bitmap, &siblings), Some(false));
+                assert_eq!(verify_non_inclusion(hash, &root, key, &bitmap, &siblings), Some(false));
+                // A sibling too few or too many for the bitmap.
+                assert_eq!(verify_inclusion(hash, &root, key, value_hash, &bitmap, &siblings[1..]), None);
+                let mut longer = siblings.clone();
+                longer.push(root);
+                assert_eq!(verify_inclusion(hash, &root, key, value_hash, &bitmap, &longer), None);
+                // The same siblings claimed at other heights.
+                let moved = bitmap.shl(1);
+                assert_ne!(verify_inclusion(hash, &root, key, value_hash, &moved, &siblings), Some(true));
+            }
+            // Absent keys: beside a present leaf, inside a shared subtree, and under an empty
+            // subtree just below the root.
+            for absent in [key("00"), key("05"), key("4000000000000000000000000000000000000000000000000000000000000000")] {
+                let (root, bitmap, siblings) = prove(hash, &entries, &absent);
+                assert_eq!(verify_non_inclusion(hash, &root, &absent, &bitmap, &siblings), Some(true));
+                assert_eq!(verify_inclusion(hash, &root, &absent, &[0; 32], &bitmap, &siblings), Some(false));
+                let (other_root, _, _) = prove(hash, &entries[1..], &absent);
+                assert_eq!(verify_non_inclusion(hash, &other_root, &absent, &bitmap, &siblings), Some(false));
+            }
+            // The empty tree is zero, and proves every key absent with no siblings.
+            assert_eq!(verify_non_inclusion(hash, &EMPTY, &key("05"), &U256::ZERO, &[]), Some(true));
+        }
+    }
+
+    #[test]
+    fn single_entry_root() {
+        // One entry at key 0 climbs 256 levels, each with an empty right sibling.
+        let hash = NodeHash::Keccak256;
+        let (key, value_hash) = (key("00"), [7; 32]);
+        let mut node = hash.merge(&key, &value_hash);
+        for _ in 0..256 {
+            node = hash.merge(&node, &EMPTY);
+        }
+        assert_eq!(verify_inclusion(hash, &node, &key, &value_hash, &U256::ZERO, &[]), Some(true));
+        assert_eq!(prove(hash, &[(key, value_hash)], &key).0, node);
+    }
+}
@@ -0,0 +1,280 @@
+//! The `Fp2 -> Fp6 -> Fp12` extension tower used by pairings:
+//! `Fp2 = Fp[u]/(u^2 + 1)`, `Fp6 = Fp2[v]/(v^3 - xi)` and `Fp12 = Fp6[w]/(w^2 - v)`.
+//!
+//! All coefficients are Montgomery-form `Uint<L>` values over `Tower::fp`. Multiplications are
+//! Karatsuba-style, with sparse variants for the line functions of the Miller loop.
+
+use crate::field::PrimeField;
+use crate::uint::Uint;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fp2<const L: usize> {
+    pub c0: Uint<L>,
+    pub c1: Uint<L>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fp6<const L: usize> {
+    pub c0: Fp2<L>,
+    pub c1: Fp2<L>,
+    pub c2: Fp2<L>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fp12<const L: usize> {
+    pub c0: Fp6<L>,
+    pub c1: Fp6<L>,
+}
+
+impl<const L: usize> Fp2<L> {
+    pub const ZERO: Self = Fp2 { c0: Uint::ZERO, c1: Uint::ZERO };
+
+    pub fn is_zero(&self) -> bool { self.c0.is_zero() && self.c1.is_zero() }
+}
+
+impl<const L: usize> Fp6<L> {
+    pub const ZERO: Self = Fp6 { c0: Fp2::ZERO, c1: Fp2::ZERO, c2: Fp2::ZERO };
+}
+
+/// Canonical big-endian hex for the two halves of an `Fp2` constant, `(c0, c1)`.
+pub type Fp2Hex = (&'static str, &'static str);
+
+pub struct Tower<const L: usize> {
+    pub fp: PrimeField<L>,
+    /// The cubic non-residue `xi` defining `Fp6`.
+    pub xi: Fp2<L>,
+    /// `xi^((p^i - 1) / 3)` and `xi^((2p^i - 2) / 3)` for the Frobenius map on `Fp6`.
+    frobenius_fp6_c1: [Fp2<L>; 4],
+    frobenius_fp6_c2: [Fp2<L>; 4],
+    /// `xi^((p^i - 1) / 6)` for the Frobenius map on `Fp12`.
+    frobenius_fp12_c1: [Fp2<L>; 4],
+}
+
+impl<const L: usize> Tower<L> {
+    /// Builds a tower from hex constants; the Frobenius tables cover powers 0 to 3.
+    pub const fn new(modulus: &str, xi: Fp2Hex, fp6_c1: [Fp2Hex; 4], fp6_c2: [Fp2Hex; 4], fp12_c1: [Fp2Hex; 4]) -> Self {
+        let fp = PrimeField::new(Uint::from_be_hex(modulus));
+        let mut frobenius_fp6_c1 = [Fp2::ZERO; 4];
+        let mut frobenius_fp6_c2 = [Fp2::ZERO; 4];
+        let mut frobenius_fp12_c1 = [Fp2::ZERO; 4];
+        let mut i = 0;
+        while i < 4 {
+            frobenius_fp6_c1[i] = fp2_from_hex(&fp, fp6_c1[i]);
+            frobenius_fp6_c2[i] = fp2_from_hex(&fp, fp6_c2[i]);
+            frobenius_fp12_c1[i] = fp2_from_hex(&fp, fp12_c1[i]);
+            i += 1;
+        }
+        let xi = fp2_from_hex(&fp, xi);
+        Tower { fp, xi, frobenius_fp6_c1, frobenius_fp6_c2, frobenius_fp12_c1 }
+    }
+
+    // --- Fp2 ---
+
+    /// Converts canonical coefficients to Montgomery form, or `None` if either is `>= p`.
+    pub fn fp2_element(&self, a: &Fp2<L>) -> Option<Fp2<L>> {
+        Some(Fp2 { c0: self.fp.element(&a.c0)?, c1: self.fp.element(&a.c1)? })
+    }
+
+    pub fn fp2_canonical(&self, a: &Fp2<L>) -> Fp2<L> {
+        Fp2 { c0: self.fp.canonical(&a.c0), c1: self.fp.canonical(&a.c1) }
+    }
+
+    pub fn fp2_one(&self) -> Fp2<L> { Fp2 { c0: self.fp.one, c1: Uint::ZERO } }
+
+    pub fn fp2_add(&self, a: &Fp2<L>, b: &Fp2<L>) -> Fp2<L> {
+        Fp2 { c0: self.fp.add(&a.c0, &b.c0), c1: self.fp.add(&a.c1, &b.c1) }
+    }
+
+    pub fn fp2_sub(&self, a: &Fp2<L>, b: &Fp2<L>) -> Fp2<L> {
+        Fp2 { c0: self.fp.sub(&a.c0, &b.c0), c1: self.fp.sub(&a.c1, &b.c1) }
+    }
+
+    pub fn fp2_double(&self, a: &Fp2<L>) -> Fp2<L> { self.fp2_add(a, a) }
+
+    pub fn fp2_neg(&self, a: &Fp2<L>) -> Fp2<L> {
+        Fp2 { c0: self.fp.neg(&a.c0), c1: self.fp.neg(&a.c1) }
+    }
+
+    pub fn fp2_conjugate(&self, a: &Fp2<L>) -> Fp2<L> { Fp2 { c0: a.c0, c1: self.fp.neg(&a.c1) } }
+
+    pub fn fp2_mul(&self, a: &Fp2<L>, b: &Fp2<L>) -> Fp2<L> {
+        let f = &self.fp;
+        let aa = f.mul(&a.c0, &b.c0);
+        let bb = f.mul(&a.c1, &b.c1);
+        let cross = f.mul(&f.add(&a.c0, &a.c1), &f.add(&b.c0, &b.c1));
+        Fp2 { c0: f.sub(&aa, &bb), c1: f.sub(&f.sub(&cross, &aa), &bb) }
+    }
+
+    pub fn fp2_square(&self, a: &Fp2<L>) -> Fp2<L> {
+        let f = &self.fp;
+        let c0 = f.mul(&f.add(&a.c0, &a.c1), &f.sub(&a.c0, &a.c1));
+        Fp2 { c0, c1: f.double(&f.mul(&a.c0, &a.c1)) }
+    }
+
+    pub fn fp2_mul_fp(&self, a: &Fp2<L>, b: &Uint<L>) -> Fp2<L> {
+        Fp2 { c0: self.fp.mul(&a.c0, b), c1: self.fp.mul(&a.c1, b) }
+    }
+
+    pub fn fp2_inv(&self, a: &Fp2<L>) -> Option<Fp2<L>> {
+        let f = &self.fp;
+        let norm_inv = f.inv(&f.add(&f.square(&a.c0), &f.square(&a.c1)))?;
+        Some(Fp2 { c0: f.mul(&a.c0, &norm_inv), c1: f.neg(&f.mul(&a.c1, &norm_inv)) })
+    }
+
+    /// A square root of `a`, or `None` for a non-residue: with `n = sqrt(a0^2 + a1^2)`, the
+    /// root is `x0 + x1 u` where `x0^2` is one of `(a0 +- n) / 2` and `x1 = a1 / (2 x0)`.
+    pub fn fp2_sqrt(&self, a: &Fp2<L>) -> Option<Fp2<L>> {
+        let f = &self.fp;
+        let half = f.inv(&f.double(&f.one))?;
+        let norm = f.sqrt(&f.add(&f.square(&a.c0), &f.square(&a.c1)))?;
+        let mut root = None;
+        for delta in [f.add(&a.c0, &norm), f.sub(&a.c0, &norm)] {
+            if let Some(x0) = f.sqrt(&f.mul(&delta, &half)).filter(|x0| !x0.is_zero()) {
+                let x1 = f.mul(&a.c1, &f.inv(&f.double(&x0))?);
+                root = Some(Fp2 { c0: x0, c1: x1 });
+                break;
+            }
+        }
+        // Neither half-sum has a non-zero root only when the root is purely imaginary (or zero).
+        let root = match root {
+            Some(root) => root,
+            None => Fp2 { c0: Uint::ZERO, c1: f.sqrt(&f.neg(&a.c0))? },
+        };
+        (self.fp2_square(&root) == *a).then_some(root)
+    }
+
+    /// The Frobenius map `a -> a^(p^power)`, which on `Fp2` is conjugation for odd powers.
+    pub fn fp2_frobenius(&self, a: &Fp2<L>, power: usize) -> Fp2<L> {
+        if power % 2 == 1 { self.fp2_conjugate(a) } else { *a }
+    }
+
+    // --- Fp6 ---
+
+    pub fn fp6_add(&self, a: &Fp6<L>, b: &Fp6<L>) -> Fp6<L> {
+        Fp6 { c0: self.fp2_add(&a.c0, &b.c0), c1: self.fp2_add(&a.c1, &b.c1), c2: self.fp2_add(&a.c2, &b.c2) }
+    }
+
+    pub fn fp6_sub(&self, a: &Fp6<L>, b: &Fp6<L>) -> Fp6<L> {
+        Fp6 { c0: self.fp2_sub(&a.c0, &b.c0), c1: self.fp2_sub(&a.c1, &b.c1), c2: self.fp2_sub(&a.c2, &b.c2) }
+    }
+
+    pub fn fp6_neg(&self, a: &Fp6<L>) -> Fp6<L> {
+        Fp6 { c0: self.fp2_neg(&a.c0), c1: self.fp2_neg(&a.c1), c2: self.fp2_neg(&a.c2) }
+    }
+
+    /// Multiplication by `v`: `(c0, c1, c2) -> (xi * c2, c0, c1)`.
+    fn fp6_mul_by_v(&self, a: &Fp6<L>) -> Fp6<L> {
+        Fp6 { c0: self.fp2_mul(&a.c2, &self.xi), c1: a.c0, c2: a.c1 }
+    }
+
+    pub fn fp6_mul(&self, a: &Fp6<L>, b: &Fp6<L>) -> Fp6<L> {
+        let aa = self.fp2_mul(&a.c0, &b.c0);
+        let bb = self.fp2_mul(&a.c1, &b.c1);
+        let cc = self.fp2_mul(&a.c2, &b.c2);
+        let t1 = self.fp2_mul(&self.fp2_add(&a.c1, &a.c2), &self.fp2_add(&b.c1, &b.c2));
+        let t1 = self.fp2_add(&self.fp2_mul(&self.fp2_sub(&self.fp2_sub(&t1, &bb), &cc), &self.xi), &aa);
+        let t2 = self.fp2_mul(&self.fp2_add(&a.c0, &a.c1), &self.fp2_add(&b.c0, &b.c1));
+        let t2 = self.fp2_add(&self.fp2_sub(&self.fp2_sub(&t2, &aa), &bb), &self.fp2_mul(&cc, &self.xi));
+        let t3 = self.fp2_mul(&self.fp2_add(&a.c0, &a.c2), &self.fp2_add(&b.c0, &b.c2));
+        let t3 = self.fp2_sub(&self.fp2_add(&self.fp2_sub(&t3, &aa), &bb), &cc);
+        Fp6 { c0: t1, c1: t2, c2: t3 }
+    }
+
+    /// Multiplication by the sparse element `b0 + b1 v`.
+    fn fp6_mul_by_01(&self, a: &Fp6<L>, b0: &Fp2<L>, b1: &Fp2<L>) -> Fp6<L> {
+        let aa = self.fp2_mul(&a.c0, b0);
+        let bb = self.fp2_mul(&a.c1, b1);
+        let t1 = self.fp2_sub(&self.fp2_mul(&self.fp2_add(&a.c1, &a.c2), b1), &bb);
+        let t1 = self.fp2_add(&self.fp2_mul(&t1, &self.xi), &aa);
+        let t2 = self.fp2_mul(&self.fp2_add(b0, b1), &self.fp2_add(&a.c0, &a.c1));
+        let t2 = self.fp2_sub(&self.fp2_sub(&t2, &aa), &bb);
+        let t3 = self.fp2_mul(&self.fp2_add(&a.c0, &a.c2), b0);
+        let t3 = self.fp2_add(&self.fp2_sub(&t3, &aa), &bb);
+        Fp6 { c0: t1, c1: t2, c2: t3 }
+    }
+
+    /// Multiplication by the sparse element `b1 v`.
+    fn fp6_mul_by_1(&self, a: &Fp6<L>, b1: &Fp2<L>) -> Fp6<L> {
+        let bb = self.fp2_mul(&a.c1, b1);
+        let t1 = self.fp2_sub(&self.fp2_mul(&self.fp2_add(&a.c1, &a.c2), b1), &bb);
+        let t2 = self.fp2_sub(&self.fp2_mul(&self.fp2_add(&a.c0, &a.c1), b1), &bb);
+        Fp6 { c0: self.fp2_mul(&t1, &self.xi), c1: t2, c2: bb }
+    }
+
+    fn fp6_inv(&self, a: &Fp6<L>) -> Option<Fp6<L>> {
+        let c0 = self.fp2_sub(&self.fp2_square(&a.c0), &self.fp2_mul(&self.fp2_mul(&a.c1, &a.c2), &self.xi));
+        let c1 = self.fp2_sub(&self.fp2_mul(&self.fp2_square(&a.c2), &self.xi), &self.fp2_mul(&a.c0, &a.c1));
+        let c2 = self.fp2_sub(&self.fp2_square(&a.c1), &self.fp2_mul(&a.c0, &a.c2));
+        let t = self.fp2_add(&self.fp2_mul(&a.c2, &c1), &self.fp2_mul(&a.c1, &c2));
+        let t = self.fp2_inv(&self.fp2_add(&self.fp2_mul(&t, &self.xi), &self.fp2_mul(&a.c0, &c0)))?;
+        Some(Fp6 { c0: self.fp2_mul(&c0, &t), c1: self.fp2_mul(&c1, &t), c2: self.fp2_mul(&c2, &t) })
+    }
+
+    fn fp6_frobenius(&self, a: &Fp6<L>, power: usize) -> Fp6<L> {
+        Fp6 {
+            c0: self.fp2_frobenius(&a.c0, power),
+            c1: self.fp2_mul(&self.fp2_frobenius(&a.c1, power), &self.frobenius_fp6_c1[power]),
+            c2: self.fp2_mul(&self.fp2_frobenius(&a.c2, power), &self.frobenius_fp6_c2[power]),
+        }
+    }
+
+    // --- Fp12 ---
+
+    pub fn fp12_one(&self) -> Fp12<L> {
+        Fp12 { c0: Fp6 { c0: self.fp2_one(), ..Fp6::ZERO }, c1: Fp6::ZERO }
+    }
+
+    pub fn fp12_mul(&self, a: &Fp12<L>, b: &Fp12<L>) -> Fp12<L> {
+        let aa = self.fp6_mul(&a.c0, &b.c0);
+        let bb = self.fp6_mul(&a.c1, &b.c1);
+        let cross = self.fp6_mul(&self.fp6_add(&a.c0, &a.c1), &self.fp6_add(&b.c0, &b.c1));
+        Fp12 { c0: self.fp6_add(&aa, &self.fp6_mul_by_v(&bb)), c1: self.fp6_sub(&self.fp6_sub(&cross, &aa), &bb) }
+    }
+
+    pub fn fp12_square(&self, a: &Fp12<L>) -> Fp12<L> {
+        // (c0 + c1 w)^2 = (c0^2 + v c1^2) + 2 c0 c1 w, with c0^2 + v c1^2 from one product.
+        let ab = self.fp6_mul(&a.c0, &a.c1);
+        let sum = self.fp6_mul(&self.fp6_add(&a.c0, &a.c1), &self.fp6_add(&a.c0, &self.fp6_mul_by_v(&a.c1)));
+        let c0 = self.fp6_sub(&self.fp6_sub(&sum, &ab), &self.fp6_mul_by_v(&ab));
+        Fp12 { c0, c1: self.fp6_add(&ab, &ab) }
+    }
+
+    pub fn fp12_inv(&self, a: &Fp12<L>) -> Option<Fp12<L>> {
+        let norm = self.fp6_sub(&self.fp6_mul(&a.c0, &a.c0), &self.fp6_mul_by_v(&self.fp6_mul(&a.c1, &a.c1)));
+        let t = self.fp6_inv(&norm)?;
+        Some(Fp12 { c0: self.fp6_mul(&a.c0, &t), c1: self.fp6_neg(&self.fp6_mul(&a.c1, &t)) })
+    }
+
+    /// `a^(p^6)`, which is the inverse for elements of the cyclotomic subgroup.
+    pub fn fp12_conjugate(&self, a: &Fp12<L>) -> Fp12<L> { Fp12 { c0: a.c0, c1: self.fp6_neg(&a.c1) } }
+
+    /// `a^(p^power)` for `power` in 0..4.
+    pub fn fp12_frobenius(&self, a: &Fp12<L>, power: usize) -> Fp12<L> {
+        let c1 = self.fp6_frobenius(&a.c1, power);
+        let k = &self.frobenius_fp12_c1[power];
+        Fp12 {
+            c0: self.fp6_frobenius(&a.c0, power),
+            c1: Fp6 { c0: self.fp2_mul(&c1.c0, k), c1: self.fp2_mul(&c1.c1, k), c2: self.fp2_mul(&c1.c2, k) },
+        }
+    }
+
+    /// Multiplication by the sparse line value `b0 + (b3 + b4 v) w` (D-type twists).
+    pub fn fp12_mul_by_034(&self, a: &Fp12<L>, b0: &Fp2<L>, b3: &Fp2<L>, b4: &Fp2<L>) -> Fp12<L> {
+        let aa = Fp6 { c0: self.fp2_mul(&a.c0.c0, b0), c1: self.fp2_mul(&a.c0.c1, b0), c2: self.fp2_mul(&a.c0.c2, b0) };
+        let bb = self.fp6_mul_by_01(&a.c1, b3, b4);
+        let e = self.fp6_mul_by_01(&self.fp6_add(&a.c0, &a.c1), &self.fp2_add(b0, b3), b4);
+        Fp12 { c0: self.fp6_add(&self.fp6_mul_by_v(&bb), &aa), c1: self.fp6_sub(&e, &self.fp6_add(&aa, &bb)) }
+    }
+
+    /// Multiplication by the sparse line value `(b0 + b1 v) + b4 v w` (M-type twists).
+    pub fn fp12_mul_by_014(&self, a: &Fp12<L>, b0: &Fp2<L>, b1: &Fp2<L>, b4: &Fp2<L>) -> Fp12<L> {
+        let aa = self.fp6_mul_by_01(&a.c0, b0, b1);
+        let bb = self.fp6_mul_by_1(&a.c1, b4);
+        let e = self.fp6_mul_by_01(&self.fp6_add(&a.c0, &a.c1), b0, &self.fp2_add(b1, b4));
+        Fp12 { c0: self.fp6_add(&self.fp6_mul_by_v(&bb), &aa), c1: self.fp6_sub(&self.fp6_sub(&e, &aa), &bb) }
+    }
+}
+
+pub const fn fp2_from_hex<const L: usize>(fp: &PrimeField<L>, (c0, c1): Fp2Hex) -> Fp2<L> {
+    Fp2 { c0: fp.element_reduced(&Uint::from_be_hex(c0)), c1: fp.element_reduced(&Uint::from_be_hex(c1)) }
+}
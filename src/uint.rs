@@ -0,0 +1,418 @@
+//! Fixed-width unsigned integers built from little-endian `u64` limbs.
+//!
+//! `U256` matches a single Solidity ABI word, so wide results can be returned without
+//! truncation. Arithmetic is written out limb by limb to stay `no_std` and allocation free.
+
+use core::cmp::Ordering;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Uint<const LIMBS: usize>(pub [u64; LIMBS]);
+
+pub type U256 = Uint<4>;
+
+impl<const LIMBS: usize> Uint<LIMBS> {
+    pub const ZERO: Self = Uint([0; LIMBS]);
+    pub const ONE: Self = Self::from_u64(1);
+    pub const MAX: Self = Uint([u64::MAX; LIMBS]);
+    pub const BITS: u32 = 64 * LIMBS as u32;
+
+    pub const fn from_u64(value: u64) -> Self {
+        let mut limbs = [0u64; LIMBS];
+        limbs[0] = value;
+        Uint(limbs)
+    }
+
+    pub const fn from_u128(value: u128) -> Self {
+        let mut limbs = [0u64; LIMBS];
+        limbs[0] = value as u64;
+        limbs[1] = (value >> 64) as u64;
+        Uint(limbs)
+    }
+
+    /// Parses a big-endian hex literal (no `0x` prefix). Meant for constants, so malformed
+    /// input is a compile-time panic.
+    pub const fn from_be_hex(hex: &str) -> Self {
+        let bytes = hex.as_bytes();
+        assert!(bytes.len() <= 16 * LIMBS, "hex literal too long");
+        let mut limbs = [0u64; LIMBS];
+        let mut i = 0;
+        while i < bytes.len() {
+            let digit = match bytes[bytes.len() - 1 - i] {
+                c @ b'0'..=b'9' => c - b'0',
+                c @ b'a'..=b'f' => c - b'a' + 10,
+                c @ b'A'..=b'F' => c - b'A' + 10,
+                _ => panic!("invalid hex digit"),
+            };
+            limbs[i / 16] |= (digit as u64) << (4 * (i % 16));
+            i += 1;
+        }
+        Uint(limbs)
+    }
+
+    /// Returns the value as a `u64` if it fits.
+    pub fn to_u64(self) -> Option<u64> {
+        if self.0[1..].iter().all(|&limb| limb == 0) { Some(self.0[0]) } else { None }
+    }
+
+    /// Returns the value as a `u128` if it fits.
+    pub fn to_u128(self) -> Option<u128> {
+        if self.0[2..].iter().all(|&limb| limb == 0) {
+            Some(((self.0[1] as u128) << 64) | self.0[0] as u128)
+        } else {
+            None
+        }
+    }
+
+    pub fn low_u64(&self) -> u64 { self.0[0] }
+    pub fn is_zero(&self) -> bool { self.0.iter().all(|&limb| limb == 0) }
+    pub fn is_odd(&self) -> bool { self.0[0] & 1 == 1 }
+
+    /// Number of significant bits (0 for zero).
+    pub fn bits(&self) -> u32 {
+        for i in (0..LIMBS).rev() {
+            if self.0[i] != 0 {
+                return 64 * i as u32 + (64 - self.0[i].leading_zeros());
+            }
+        }
+        0
+    }
+
+    pub fn bit(&self, index: u32) -> bool {
+        let limb = (index / 64) as usize;
+        limb < LIMBS && (self.0[limb] >> (index % 64)) & 1 == 1
+    }
+
+    pub fn trailing_zeros(&self) -> u32 {
+        for i in 0..LIMBS {
+            if self.0[i] != 0 {
+                return 64 * i as u32 + self.0[i].trailing_zeros();
+            }
+        }
+        Self::BITS
+    }
+
+    /// Parses a big-endian byte string, returning `None` if the value does not fit.
+    pub fn from_be_slice(bytes: &[u8]) -> Option<Self> {
+        let mut limbs = [0u64; LIMBS];
+        for (i, &byte) in bytes.iter().rev().enumerate() {
+            if i >= LIMBS * 8 {
+                if byte != 0 { return None; }
+                continue;
+            }
+            limbs[i / 8] |= (byte as u64) << (8 * (i % 8));
+        }
+        Some(Uint(limbs))
+    }
+
+    /// Writes the value big-endian into `out`, which must be exactly `LIMBS * 8` bytes long.
+    pub fn write_be_bytes(&self, out: &mut [u8]) {
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = (LIMBS - 1 - i) * 8;
+            out[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+    }
+
+    // The carry chains and comparison below are `const` (hence the `while` loops) so prime
+    // field parameters can be derived at compile time.
+    pub const fn overflowing_add(&self, rhs: &Self) -> (Self, bool) {
+        let mut out = [0u64; LIMBS];
+        let mut carry = false;
+        let mut i = 0;
+        while i < LIMBS {
+            let (sum, c1) = self.0[i].overflowing_add(rhs.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            out[i] = sum;
+            carry = c1 || c2;
+            i += 1;
+        }
+        (Uint(out), carry)
+    }
+
+    pub const fn overflowing_sub(&self, rhs: &Self) -> (Self, bool) {
+        let mut out = [0u64; LIMBS];
+        let mut borrow = false;
+        let mut i = 0;
+        while i < LIMBS {
+            let (diff, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            out[i] = diff;
+            borrow = b1 || b2;
+            i += 1;
+        }
+        (Uint(out), borrow)
+    }
+
+    /// `self >= rhs`, usable in `const` contexts where `Ord` is not.
+    pub const fn const_ge(&self, rhs: &Self) -> bool {
+        let mut i = LIMBS;
+        while i > 0 {
+            i -= 1;
+            if self.0[i] != rhs.0[i] { return self.0[i] > rhs.0[i]; }
+        }
+        true
+    }
+
+    pub const fn wrapping_add(&self, rhs: &Self) -> Self { self.overflowing_add(rhs).0 }
+    pub const fn wrapping_sub(&self, rhs: &Self) -> Self { self.overflowing_sub(rhs).0 }
+
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        match self.overflowing_add(rhs) { (sum, false) => Some(sum), _ => None }
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) { (diff, false) => Some(diff), _ => None }
+    }
+
+    /// Full product as `(low, high)` halves.
+    pub fn widening_mul(&self, rhs: &Self) -> (Self, Self) {
+        let mut lo = [0u64; LIMBS];
+        let mut hi = [0u64; LIMBS];
+        for i in 0..LIMBS {
+            let mut carry = 0u128;
+            for j in 0..LIMBS {
+                let k = i + j;
+                let current = if k < LIMBS { lo[k] } else { hi[k - LIMBS] };
+                let t = self.0[i] as u128 * rhs.0[j] as u128 + current as u128 + carry;
+                if k < LIMBS { lo[k] = t as u64; } else { hi[k - LIMBS] = t as u64; }
+                carry = t >> 64;
+            }
+            hi[i] = carry as u64;
+        }
+        (Uint(lo), Uint(hi))
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        let (lo, hi) = self.widening_mul(rhs);
+        if hi.is_zero() { Some(lo) } else { None }
+    }
+
+    /// Multiplies by a single limb, returning the product and the limb shifted out of the top.
+    pub fn mul_u64(&self, rhs: u64) -> (Self, u64) {
+        let mut out = [0u64; LIMBS];
+        let mut carry = 0u128;
+        for (limb, &value) in out.iter_mut().zip(self.0.iter()) {
+            let t = value as u128 * rhs as u128 + carry;
+            *limb = t as u64;
+            carry = t >> 64;
+        }
+        (Uint(out), carry as u64)
+    }
+
+    pub fn checked_mul_u64(&self, rhs: u64) -> Option<Self> {
+        match self.mul_u64(rhs) { (product, 0) => Some(product), _ => None }
+    }
+
+    pub fn shl(&self, shift: u32) -> Self {
+        if shift >= Self::BITS { return Self::ZERO; }
+        let limb_shift = (shift / 64) as usize;
+        let bit_shift = shift % 64;
+        let mut out = [0u64; LIMBS];
+        for i in (limb_shift..LIMBS).rev() {
+            out[i] = self.0[i - limb_shift] << bit_shift;
+            if bit_shift > 0 && i > limb_shift {
+                out[i] |= self.0[i - limb_shift - 1] >> (64 - bit_shift);
+            }
+        }
+        Uint(out)
+    }
+
+    pub fn shr(&self, shift: u32) -> Self {
+        if shift >= Self::BITS { return Self::ZERO; }
+        let limb_shift = (shift / 64) as usize;
+        let bit_shift = shift % 64;
+        let mut out = [0u64; LIMBS];
+        for (i, limb) in out.iter_mut().take(LIMBS - limb_shift).enumerate() {
+            *limb = self.0[i + limb_shift] >> bit_shift;
+            if bit_shift > 0 && i + limb_shift + 1 < LIMBS {
+                *limb |= self.0[i + limb_shift + 1] << (64 - bit_shift);
+            }
+        }
+        Uint(out)
+    }
+
+    /// Quotient and remainder of division by a single limb; `None` if `divisor` is zero.
+    pub fn div_rem_u64(&self, divisor: u64) -> Option<(Self, u64)> {
+        if divisor == 0 { return None; }
+        let mut out = [0u64; LIMBS];
+        let mut rem = 0u128;
+        for i in (0..LIMBS).rev() {
+            let cur = (rem << 64) | self.0[i] as u128;
+            out[i] = (cur / divisor as u128) as u64;
+            rem = cur % divisor as u128;
+        }
+        Some((Uint(out), rem as u64))
+    }
+
+    /// Quotient and remainder; `None` if `divisor` is zero.
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        if divisor.is_zero() { return None; }
+        if let Some(d) = divisor.to_u64() {
+            let (q, r) = self.div_rem_u64(d)?;
+            return Some((q, Self::from_u64(r)));
+        }
+        if self < divisor { return Some((Self::ZERO, *self)); }
+        // Shift-subtract long division, starting from the highest quotient bit that can be set.
+        let shift = self.bits() - divisor.bits();
+        let mut quotient = Self::ZERO;
+        let mut rem = *self;
+        let mut d = divisor.shl(shift);
+        for i in (0..=shift).rev() {
+            if rem >= d {
+                rem = rem.wrapping_sub(&d);
+                quotient.0[(i / 64) as usize] |= 1 << (i % 64);
+            }
+            d = d.shr(1);
+        }
+        Some((quotient, rem))
+    }
+
+    /// `(hi * 2^BITS + lo) mod modulus` for a non-zero `modulus`.
+    pub fn rem_wide(lo: &Self, hi: &Self, modulus: &Self) -> Self {
+        let mut rem = hi.div_rem(modulus).map(|(_, r)| r).unwrap_or(Self::ZERO);
+        for i in (0..Self::BITS).rev() {
+            // rem < modulus, so doubling it overflows at most one bit.
+            let top = rem.bit(Self::BITS - 1);
+            rem = rem.shl(1);
+            if lo.bit(i) { rem.0[0] |= 1; }
+            if top || rem >= *modulus {
+                rem = rem.wrapping_sub(modulus);
+            }
+        }
+        rem
+    }
+
+    /// `(self + rhs) mod modulus` for operands already reduced below `modulus`.
+    pub const fn add_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        let (sum, carry) = self.overflowing_add(rhs);
+        if carry || sum.const_ge(modulus) { sum.wrapping_sub(modulus) } else { sum }
+    }
+
+    /// `(self - rhs) mod modulus` for operands already reduced below `modulus`.
+    pub const fn sub_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        let (diff, borrow) = self.overflowing_sub(rhs);
+        if borrow { diff.wrapping_add(modulus) } else { diff }
+    }
+
+    pub fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        let (lo, hi) = self.widening_mul(rhs);
+        Self::rem_wide(&lo, &hi, modulus)
+    }
+
+    pub fn pow_mod(&self, exp: &Self, modulus: &Self) -> Self {
+        if *modulus == Self::ONE { return Self::ZERO; }
+        let mut result = Self::ONE;
+        let base = self.div_rem(modulus).map(|(_, r)| r).unwrap_or(Self::ZERO);
+        for i in (0..exp.bits()).rev() {
+            result = result.mul_mod(&result, modulus);
+            if exp.bit(i) { result = result.mul_mod(&base, modulus); }
+        }
+        result
+    }
+}
+
+impl<const LIMBS: usize> Ord for Uint<LIMBS> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for i in (0..LIMBS).rev() {
+            match self.0[i].cmp(&other.0[i]) {
+                Ordering::Equal => continue,
+                ord => return ord,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl<const LIMBS: usize> PartialOrd for Uint<LIMBS> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl U256 {
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = (3 - i) * 8;
+            *limb = u64::from_be_bytes(bytes[start..start + 8].try_into().unwrap());
+        }
+        Uint(limbs)
+    }
+
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        self.write_be_bytes(&mut out);
+        out
+    }
+}
+
+/// The operations the width-generic number theory routines need, implemented for the native
+/// `u64`/`u128` types and for `Uint`. Signatures mirror the inherent `Uint` methods.
+pub trait Unsigned: Copy + Ord {
+    const ZERO: Self;
+    const ONE: Self;
+    const BITS: u32;
+
+    fn from_u64(value: u64) -> Self;
+    fn is_zero(&self) -> bool;
+    fn is_odd(&self) -> bool;
+    /// Number of significant bits (0 for zero).
+    fn bits(&self) -> u32;
+    fn trailing_zeros(&self) -> u32;
+    fn shl(&self, shift: u32) -> Self;
+    fn shr(&self, shift: u32) -> Self;
+    fn wrapping_add(&self, rhs: &Self) -> Self;
+    fn wrapping_sub(&self, rhs: &Self) -> Self;
+    fn wrapping_mul(&self, rhs: &Self) -> Self;
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+    /// Quotient and remainder for a non-zero `rhs`.
+    fn div_mod(&self, rhs: &Self) -> (Self, Self);
+    /// `(self * rhs) % modulus` for a non-zero `modulus`, without overflow.
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self;
+}
+
+macro_rules! impl_unsigned_native {
+    ($t:ty, $mul_mod:expr) => {
+        impl Unsigned for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const BITS: u32 = <$t>::BITS;
+
+            fn from_u64(value: u64) -> Self { value as $t }
+            fn is_zero(&self) -> bool { *self == 0 }
+            fn is_odd(&self) -> bool { *self & 1 == 1 }
+            fn bits(&self) -> u32 { <$t>::BITS - self.leading_zeros() }
+            fn trailing_zeros(&self) -> u32 { <$t>::trailing_zeros(*self) }
+            fn shl(&self, shift: u32) -> Self { self.checked_shl(shift).unwrap_or(0) }
+            fn shr(&self, shift: u32) -> Self { self.checked_shr(shift).unwrap_or(0) }
+            fn wrapping_add(&self, rhs: &Self) -> Self { <$t>::wrapping_add(*self, *rhs) }
+            fn wrapping_sub(&self, rhs: &Self) -> Self { <$t>::wrapping_sub(*self, *rhs) }
+            fn wrapping_mul(&self, rhs: &Self) -> Self { <$t>::wrapping_mul(*self, *rhs) }
+            fn checked_mul(&self, rhs: &Self) -> Option<Self> { <$t>::checked_mul(*self, *rhs) }
+            fn div_mod(&self, rhs: &Self) -> (Self, Self) { (*self / *rhs, *self % *rhs) }
+            fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self { $mul_mod(*self, *rhs, *modulus) }
+        }
+    };
+}
+
+impl_unsigned_native!(u64, |a: u64, b: u64, m: u64| (a as u128 * b as u128 % m as u128) as u64);
+impl_unsigned_native!(u128, |a: u128, b: u128, m: u128| {
+    U256::from_u128(a).mul_mod(&U256::from_u128(b), &U256::from_u128(m)).to_u128().unwrap()
+});
+
+impl<const LIMBS: usize> Unsigned for Uint<LIMBS> {
+    const ZERO: Self = Uint::ZERO;
+    const ONE: Self = Uint::ONE;
+    const BITS: u32 = Uint::<LIMBS>::BITS;
+
+    fn from_u64(value: u64) -> Self { Uint::from_u64(value) }
+    fn is_zero(&self) -> bool { Uint::is_zero(self) }
+    fn is_odd(&self) -> bool { Uint::is_odd(self) }
+    fn bits(&self) -> u32 { Uint::bits(self) }
+    fn trailing_zeros(&self) -> u32 { Uint::trailing_zeros(self) }
+    fn shl(&self, shift: u32) -> Self { Uint::shl(self, shift) }
+    fn shr(&self, shift: u32) -> Self { Uint::shr(self, shift) }
+    fn wrapping_add(&self, rhs: &Self) -> Self { Uint::wrapping_add(self, rhs) }
+    fn wrapping_sub(&self, rhs: &Self) -> Self { Uint::wrapping_sub(self, rhs) }
+    fn wrapping_mul(&self, rhs: &Self) -> Self { self.widening_mul(rhs).0 }
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> { Uint::checked_mul(self, rhs) }
+    fn div_mod(&self, rhs: &Self) -> (Self, Self) { Uint::div_rem(self, rhs).unwrap() }
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self { Uint::mul_mod(self, rhs, modulus) }
+}